@@ -1,10 +1,39 @@
-use crate::{G, HALF_STADIUM_LENGTH, HALF_STADIUM_WIDTH};
-
-use raylib::{
-    ffi::{DrawSphere, GetFrameTime},
-    prelude::*,
+use crate::{
+    BACKBOARD_DROP, BACKBOARD_HEIGHT, BACKBOARD_OFFSET, BACKBOARD_WIDTH, BALL_RADIUS,
+    BLUE_RING_POSITION, G, HALF_STADIUM_LENGTH, HALF_STADIUM_WIDTH, PI, RED_RING_POSITION,
+    RIM_THICKNESS, RING_RADIUS, ROBOT_RADIUS, STADIUM_FLOOR_THICKNESS,
 };
 
+use super::math::Vector3;
+use super::shot::Shot;
+
+/// Arrastre cuadrático del aire: la aceleración es `-AIR_DRAG * |v| * v` (1/m).
+pub const AIR_DRAG: f32 = 0.02;
+/// Fracción de la velocidad normal que conserva la pelota al rebotar en el
+/// piso o en los bordes del estadio.
+pub const FLOOR_RESTITUTION: f32 = 0.75;
+pub const BACKBOARD_RESTITUTION: f32 = 0.6;
+pub const RIM_RESTITUTION: f32 = 0.5;
+/// Coeficiente de roce de rodadura con el piso.
+pub const ROLLING_FRICTION: f32 = 0.05;
+
+/// Altura máxima del bote (m) y duración de un bote (s) mientras un robot
+/// lleva la pelota.
+pub const DRIBBLE_HEIGHT: f32 = 0.35;
+pub const DRIBBLE_PERIOD: f32 = 0.5;
+
+/// Bajo esta rapidez vertical (m/s) la pelota deja de rebotar y rueda.
+const MIN_BOUNCE_SPEED: f32 = 0.5;
+/// Subpasos de integración por paso, para no atravesar el aro ni el tablero.
+const BALL_SUBSTEPS: usize = 4;
+/// Altura del centro de la pelota apoyada en el piso.
+const FLOOR_LEVEL: f32 = STADIUM_FLOOR_THICKNESS / 2.0 + BALL_RADIUS;
+
+const RINGS: [(&str, Vector3); 2] = [
+    ("blue_hoop", BLUE_RING_POSITION),
+    ("red_hoop", RED_RING_POSITION),
+];
+
 #[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct Ball {
@@ -14,69 +43,341 @@ pub struct Ball {
     pub y_axis_proj: f32,   // Proyección de la pelota en eje Y (Plano XY)
     pub velocity: Vector3,  // Velocidad de la Pelota (x, y, z)
     pub position: Vector3,  // Posicion de la Pelota (x, y, z)
-    pub is_shooting: bool,  // Indicador booleano de si el balón está en el aire
+    pub state: BallState,   // Quién controla la pelota
+    pub shot_distance: f32, // Distancia horizontal al aro al momento del disparo (m)
 }
 
-impl Ball {
-    pub fn new(base_position: Vector3) -> Self {
-        let velocity = Vector3::zero();
-        let position = Vector3 {
-            x: base_position.x,
-            y: base_position.y + 0.15,
-            z: base_position.z,
-        };
+/// Estado de la pelota.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BallState {
+    Held,   // La lleva un robot, botándola
+    Flying, // Disparo en el aire, todavía sin resultado
+    Loose,  // Suelta (pase, rebote o después de una canasta) hasta que un robot la recoja
+}
 
+/// Cómo terminó un disparo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShotOutcome {
+    Made(&'static str), // Aro por el que pasó la pelota ("blue_hoop" o "red_hoop")
+    Missed,
+}
+
+impl Ball {
+    /// Pelota suelta en el piso, bajo `position`.
+    pub fn new(position: Vector3) -> Self {
         Ball {
             shot_velocity: 0.0,
             elev_angle: 0.0,
             dir_angle: 0.0,
             y_axis_proj: 0.0,
-            velocity,
-            position,
-            is_shooting: false,
+            velocity: Vector3::zero(),
+            position: Vector3::new(position.x, FLOOR_LEVEL, position.z),
+            state: BallState::Loose,
+            shot_distance: 0.0,
+        }
+    }
+
+    /// Avanza la pelota `dt` segundos: vuela con gravedad y arrastre y rebota
+    /// en el piso, los bordes, los tableros y los aros. Si la lleva un robot
+    /// no hace nada (ver `carry`). Devuelve el resultado del disparo cuando la
+    /// pelota pasa por un aro o toca el piso por primera vez.
+    pub fn update(&mut self, dt: f32) -> Option<ShotOutcome> {
+        if self.state == BallState::Held {
+            return None;
+        }
+
+        let h = dt / BALL_SUBSTEPS as f32;
+        let mut outcome = None;
+
+        for _ in 0..BALL_SUBSTEPS {
+            let previous = self.position;
+            self.integrate(h);
+
+            if self.state == BallState::Flying
+                && let Some(&(hoop, _)) = RINGS
+                    .iter()
+                    .find(|(_, ring)| Ball::passes_through(previous, self.position, *ring))
+            {
+                self.state = BallState::Loose;
+                outcome = Some(ShotOutcome::Made(hoop));
+            }
+
+            for (_, ring) in RINGS {
+                self.collide_with_backboard(ring);
+                self.collide_with_rim(ring);
+            }
+
+            self.collide_with_walls();
+
+            if self.collide_with_floor(h) && self.state == BallState::Flying {
+                self.state = BallState::Loose;
+                outcome = Some(ShotOutcome::Missed);
+            }
+        }
+
+        outcome
+    }
+
+    /// La pelota bota en el borde del robot en `base_position`, a
+    /// `ROBOT_RADIUS` en la dirección de su rumbo `heading`; `time` marca la
+    /// fase del bote.
+    pub fn carry(&mut self, base_position: Vector3, heading: f32, time: f32) {
+        let bounce = (PI * time / DRIBBLE_PERIOD).sin().abs();
+
+        self.state = BallState::Held;
+        self.velocity = Vector3::zero();
+        self.position = Vector3 {
+            x: base_position.x + ROBOT_RADIUS * heading.sin(),
+            y: FLOOR_LEVEL + DRIBBLE_HEIGHT * bounce,
+            z: base_position.z + ROBOT_RADIUS * heading.cos(),
+        };
+    }
+
+    /// Lanza la pelota desde donde está con los parámetros de `shot`.
+    pub fn launch(&mut self, shot: Shot) {
+        let vxz = shot.speed * shot.elevation.cos();
+
+        self.shot_velocity = shot.speed;
+        self.elev_angle = shot.elevation;
+        self.dir_angle = shot.direction;
+        self.state = BallState::Flying;
+        self.velocity = Vector3 {
+            x: vxz * shot.direction.cos(),
+            y: shot.speed * shot.elevation.sin(),
+            z: vxz * shot.direction.sin(),
+        };
+    }
+
+    pub(crate) fn integrate(&mut self, h: f32) {
+        let drag = AIR_DRAG * self.velocity.length();
+
+        self.velocity.x -= drag * self.velocity.x * h;
+        self.velocity.y -= (G + drag * self.velocity.y) * h;
+        self.velocity.z -= drag * self.velocity.z * h;
+
+        self.position += self.velocity * h;
+    }
+
+    /// Rebota en el piso y, si ya no rebota, aplica el roce de rodadura.
+    /// Devuelve si la pelota llegó al piso bajando.
+    fn collide_with_floor(&mut self, h: f32) -> bool {
+        if self.position.y > FLOOR_LEVEL {
+            return false;
+        }
+
+        self.position.y = FLOOR_LEVEL;
+        let falling = self.velocity.y < 0.0;
+
+        if falling {
+            self.velocity.y = -self.velocity.y * FLOOR_RESTITUTION;
+
+            if self.velocity.y < MIN_BOUNCE_SPEED {
+                self.velocity.y = 0.0;
+            }
+        }
+
+        if self.velocity.y == 0.0 {
+            let speed = (self.velocity.x.powi(2) + self.velocity.z.powi(2)).sqrt();
+
+            if speed > 0.0 {
+                let scale = (speed - ROLLING_FRICTION * G * h).max(0.0) / speed;
+                self.velocity.x *= scale;
+                self.velocity.z *= scale;
+            }
+        }
+
+        falling
+    }
+
+    /// Los bordes del estadio se tratan como paredes.
+    fn collide_with_walls(&mut self) {
+        let max_x = HALF_STADIUM_WIDTH - BALL_RADIUS;
+        let max_z = HALF_STADIUM_LENGTH - BALL_RADIUS;
+
+        if self.position.x.abs() > max_x {
+            self.position.x = max_x.copysign(self.position.x);
+
+            if self.velocity.x * self.position.x > 0.0 {
+                self.velocity.x = -self.velocity.x * FLOOR_RESTITUTION;
+            }
+        }
+
+        if self.position.z.abs() > max_z {
+            self.position.z = max_z.copysign(self.position.z);
+
+            if self.velocity.z * self.position.z > 0.0 {
+                self.velocity.z = -self.velocity.z * FLOOR_RESTITUTION;
+            }
+        }
+    }
+
+    /// Tablero vertical detrás de `ring`, mirando hacia la cancha. Solo choca
+    /// la cara delantera.
+    fn collide_with_backboard(&mut self, ring: Vector3) {
+        // +1 para el aro azul, -1 para el rojo
+        let side = ring.z.signum();
+        let face = ring.z + side * (BACKBOARD_OFFSET - BALL_RADIUS);
+        let bottom = ring.y - BACKBOARD_DROP;
+
+        let in_front = (self.position.x - ring.x).abs() < BACKBOARD_WIDTH / 2.0
+            && self.position.y > bottom
+            && self.position.y < bottom + BACKBOARD_HEIGHT;
+        let penetration = (self.position.z - face) * side;
+
+        if in_front && penetration > 0.0 && penetration < 2.0 * BALL_RADIUS {
+            self.position.z = face;
+
+            if self.velocity.z * side > 0.0 {
+                self.velocity.z = -self.velocity.z * BACKBOARD_RESTITUTION;
+            }
         }
     }
 
-    pub fn update(&mut self, base_position: Vector3) {
-        if !self.is_shooting {
-            self.position = Vector3 {
-                x: base_position.x,
-                y: base_position.y + 0.15,
-                z: base_position.z,
-            };
-            self.velocity = Vector3::default();
+    /// El aro es un anillo horizontal de radio `RING_RADIUS` y grosor
+    /// `RIM_THICKNESS`: la pelota choca con el punto del anillo más cercano.
+    fn collide_with_rim(&mut self, ring: Vector3) {
+        let dx = self.position.x - ring.x;
+        let dz = self.position.z - ring.z;
+        let horizontal = (dx * dx + dz * dz).sqrt();
+
+        let (ux, uz) = if horizontal > f32::EPSILON {
+            (dx / horizontal, dz / horizontal)
+        } else {
+            (1.0, 0.0)
+        };
+
+        let closest = Vector3::new(ring.x + ux * RING_RADIUS, ring.y, ring.z + uz * RING_RADIUS);
+        let offset = self.position - closest;
+        let distance = offset.length();
+        let contact = BALL_RADIUS + RIM_THICKNESS;
+
+        if distance >= contact || distance <= f32::EPSILON {
             return;
         }
 
-        let time = Ball::get_delta_time();
+        let normal = offset / distance;
+        self.position = closest + normal * contact;
+
+        let normal_speed = self.velocity.dot(normal);
 
-        self.position.x += self.velocity.x * time;
-        self.position.z += self.velocity.z * time;
-        self.position.y += self.velocity.y * time - 0.5 * G * time.powi(2);
+        if normal_speed < 0.0 {
+            self.velocity -= normal * ((1.0 + RIM_RESTITUTION) * normal_speed);
+        }
+    }
+
+    /// Verdadero si entre `previous` y `current` la pelota cruza el plano del
+    /// aro bajando y sin tocar el borde (el centro queda a menos de
+    /// `RING_RADIUS - BALL_RADIUS` del centro del aro).
+    fn passes_through(previous: Vector3, current: Vector3, ring: Vector3) -> bool {
+        if previous.y < ring.y || current.y >= ring.y {
+            return false;
+        }
 
-        self.velocity.y -= G * time;
+        // Punto donde la trayectoria cruza la altura del aro
+        let t = (previous.y - ring.y) / (previous.y - current.y);
+        let x = previous.x + (current.x - previous.x) * t;
+        let z = previous.z + (current.z - previous.z) * t;
 
-        let is_outside_stadium = self.position.x > HALF_STADIUM_WIDTH
-            || self.position.x < -HALF_STADIUM_WIDTH
-            || self.position.z > HALF_STADIUM_LENGTH
-            || self.position.z < -HALF_STADIUM_LENGTH
-            || self.position.y < 0.0;
+        let distance = ((x - ring.x).powi(2) + (z - ring.z).powi(2)).sqrt();
 
-        if is_outside_stadium {
-            self.is_shooting = false;
-            self.position = Vector3 {
-                x: base_position.x,
-                y: base_position.y + 0.15,
-                z: base_position.z,
-            };
+        distance < RING_RADIUS - BALL_RADIUS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FIXED_DT;
+
+    fn loose(position: Vector3, velocity: Vector3) -> Ball {
+        let mut ball = Ball::new(Vector3::zero());
+        ball.position = position;
+        ball.velocity = velocity;
+        ball
+    }
+
+    #[test]
+    fn bounces_get_lower_until_the_ball_rests() {
+        let mut ball = loose(Vector3::new(0.0, 2.0, 0.0), Vector3::zero());
+        let mut peaks = Vec::new();
+        let mut rising = false;
+
+        for _ in 0..600 {
+            ball.update(FIXED_DT);
+
+            if rising && ball.velocity.y <= 0.0 {
+                peaks.push(ball.position.y);
+            }
+            rising = ball.velocity.y > 0.0;
         }
+
+        assert!(peaks.len() >= 2);
+        assert!(peaks.windows(2).all(|pair| pair[1] < pair[0]));
+        assert!((ball.position.y - FLOOR_LEVEL).abs() < 1e-4);
+        assert_eq!(ball.velocity.y, 0.0);
     }
 
-    pub fn draw(&self) {
-        unsafe { DrawSphere(self.position.into(), 0.15, Color::WHITESMOKE.into()) };
+    #[test]
+    fn rolling_ball_comes_to_a_stop() {
+        let mut ball = loose(
+            Vector3::new(0.0, FLOOR_LEVEL, 0.0),
+            Vector3::new(2.0, 0.0, 0.0),
+        );
+
+        for _ in 0..600 {
+            ball.update(FIXED_DT);
+        }
+
+        assert_eq!(ball.velocity.x, 0.0);
+        assert!(ball.position.x > 0.0 && ball.position.x < HALF_STADIUM_WIDTH);
     }
 
-    fn get_delta_time() -> f32 {
-        unsafe { GetFrameTime() }
+    #[test]
+    fn backboard_sends_the_ball_back_to_the_court() {
+        let ring = BLUE_RING_POSITION;
+        let mut ball = loose(
+            Vector3::new(0.5, ring.y + 0.3, ring.z),
+            Vector3::new(0.0, 2.0, 6.0),
+        );
+
+        for _ in 0..15 {
+            ball.update(FIXED_DT);
+        }
+
+        assert!(ball.velocity.z < 0.0);
+        assert!(ball.position.z < ring.z + BACKBOARD_OFFSET);
+    }
+
+    #[test]
+    fn ball_dropped_on_the_rim_bounces_off() {
+        let ring = RED_RING_POSITION;
+        let mut ball = loose(
+            Vector3::new(ring.x + RING_RADIUS + 0.05, ring.y + 1.0, ring.z),
+            Vector3::zero(),
+        );
+        ball.state = BallState::Flying;
+
+        let mut outcome = None;
+        for _ in 0..120 {
+            outcome = outcome.or(ball.update(FIXED_DT));
+        }
+
+        assert_eq!(outcome, Some(ShotOutcome::Missed));
+        assert!(ball.position.x > ring.x + RING_RADIUS);
+    }
+
+    #[test]
+    fn ball_dropped_through_the_center_scores() {
+        let ring = BLUE_RING_POSITION;
+        let mut ball = loose(Vector3::new(ring.x, ring.y + 1.0, ring.z), Vector3::zero());
+        ball.state = BallState::Flying;
+
+        let mut outcome = None;
+        for _ in 0..120 {
+            outcome = outcome.or(ball.update(FIXED_DT));
+        }
+
+        assert_eq!(outcome, Some(ShotOutcome::Made("blue_hoop")));
+        assert_eq!(ball.state, BallState::Loose);
     }
 }
@@ -0,0 +1,391 @@
+use rand::{Rng, rngs::StdRng};
+
+use super::controller::{self, Action, Command, Observation, Possession, RobotController};
+use super::math::Vector3;
+use super::shot::{Arc, Shot, ShotPlanner};
+
+/// Probabilidad por paso de que quien lleva la pelota dispare, si camina al azar.
+pub const SHOT_PROBABILITY: f32 = 0.1;
+/// Probabilidad por paso de que quien lleva la pelota la pase a un compañero.
+pub const PASS_PROBABILITY: f32 = 0.01;
+/// Distancia horizontal (m) al aro desde la que se dispara.
+pub const SHOOTING_RANGE: f32 = 4.0;
+/// Más cerca que esto (m) el aro y el tablero tapan el disparo, así que se
+/// vuelve al punto de disparo.
+pub const MIN_SHOOTING_RANGE: f32 = 1.0;
+/// Distancia (m) entre el aro y el punto de disparo al que se dirige el equipo
+/// que ataca.
+pub const SHOOTING_SPOT_DISTANCE: f32 = 3.0;
+/// Distancia (m) del aro propio a la que se ubican los defensores.
+pub const DEFENSE_DISTANCE: f32 = 2.5;
+/// Radio (m) dentro del cual un robot se aparta de sus compañeros.
+pub const AVOID_RADIUS: f32 = 1.5;
+/// Rapidez máxima (m/s) de los robots que siguen la máquina de estados.
+pub const MAX_SPEED: f32 = 4.0;
+/// Radio (m) en que un robot empieza a frenar al llegar a su destino.
+pub const ARRIVE_RADIUS: f32 = 0.5;
+
+/// Controladores incluidos, para elegirlos por nombre.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Behavior {
+    RandomWalk, // `RandomWalkController`
+    #[default]
+    StateMachine, // `StateMachineController`
+}
+
+impl Behavior {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "random" => Some(Behavior::RandomWalk),
+            "fsm" => Some(Behavior::StateMachine),
+            _ => None,
+        }
+    }
+
+    /// Controlador nuevo de este tipo, para un robot.
+    pub fn controller(self) -> Box<dyn RobotController> {
+        match self {
+            Behavior::RandomWalk => Box::new(RandomWalkController::default()),
+            Behavior::StateMachine => Box::new(StateMachineController::default()),
+        }
+    }
+}
+
+/// Ángulo y rapidez al azar por un número de pasos al azar. Con la pelota
+/// dispara con una rapidez al azar o pasa al azar.
+#[derive(Debug, Default)]
+pub struct RandomWalkController {
+    n_step: i16,
+    angle: f32,
+    speed: f32,
+}
+
+impl RobotController for RandomWalkController {
+    fn command(&mut self, observation: &Observation, rng: &mut StdRng) -> Command {
+        self.n_step -= 1;
+
+        if self.n_step <= 0 {
+            self.n_step = rng.gen_range(10..=200);
+            self.angle = random_angle(rng);
+            self.speed = random_speed(rng);
+        }
+
+        let mut action = Action::Keep;
+
+        if observation.possession == Possession::Mine {
+            let roll = rng.r#gen::<f32>();
+
+            if roll < SHOT_PROBABILITY {
+                action = random_shot(observation, rng).map_or(Action::Keep, Action::Shoot);
+            } else if roll < SHOT_PROBABILITY + PASS_PROBABILITY {
+                action = random_pass(observation, rng);
+            }
+        }
+
+        let (linear, angular) =
+            controller::drive_towards(observation.heading, self.angle, self.speed);
+
+        Command {
+            linear,
+            angular,
+            action,
+        }
+    }
+}
+
+/// Sigue la máquina de estados de `think`: al llegar al estado `Shoot`
+/// dispara el tiro más lento de `planner` y, mientras tanto, a veces pasa la
+/// pelota.
+#[derive(Debug, Default)]
+pub struct StateMachineController {
+    pub planner: ShotPlanner,
+}
+
+impl RobotController for StateMachineController {
+    fn command(&mut self, observation: &Observation, rng: &mut StdRng) -> Command {
+        let plan = think(observation);
+
+        let action = match (plan.state, observation.possession) {
+            (RobotState::Shoot, Possession::Mine) => {
+                let shot = self.planner.plan(observation.ball, observation.target_ring);
+                shot.map_or(Action::Keep, Action::Shoot)
+            }
+            (_, Possession::Mine) if rng.r#gen::<f32>() < PASS_PROBABILITY => {
+                random_pass(observation, rng)
+            }
+            _ => Action::Keep,
+        };
+
+        let (linear, angular) =
+            controller::drive_towards(observation.heading, plan.angle, plan.speed);
+
+        Command {
+            linear,
+            angular,
+            action,
+        }
+    }
+}
+
+/// Estado de la máquina de estados.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RobotState {
+    SeekBall,    // Va por la pelota suelta o a quitársela a quien la lleva
+    DriveToSpot, // Va al punto de disparo, con la pelota o para recibir un pase
+    Shoot,       // Con la pelota y en rango: se detiene y dispara
+    Defend,      // Se ubica entre el aro propio y la pelota
+}
+
+/// Lo que decide la máquina de estados en un paso.
+#[derive(Debug, Clone, Copy)]
+pub struct Plan {
+    pub state: RobotState,
+    pub target: Vector3,
+    pub angle: f32, // Rumbo, con la misma convención que `Robot::angle`
+    pub speed: f32, // Rapidez (m/s)
+}
+
+/// Elige el estado del robot que observa `observation` según quién tiene la
+/// pelota:
+///
+/// - si la tiene él, dispara en rango (entre `MIN_SHOOTING_RANGE` y
+///   `SHOOTING_RANGE`) o va al punto de disparo;
+/// - si la tiene un compañero, va al punto de disparo a esperar un pase;
+/// - si la tiene un rival o está suelta, el compañero más cercano a la pelota
+///   va por ella y el resto defiende.
+///
+/// En todos los casos se aparta de los compañeros que tenga muy cerca.
+pub fn think(observation: &Observation) -> Plan {
+    let (state, target) = match observation.possession {
+        Possession::Mine => {
+            let distance = observation.distance_to(observation.target_ring);
+
+            if (MIN_SHOOTING_RANGE..=SHOOTING_RANGE).contains(&distance) {
+                (RobotState::Shoot, observation.position)
+            } else {
+                (RobotState::DriveToSpot, shooting_spot(observation))
+            }
+        }
+        Possession::Teammate => (RobotState::DriveToSpot, shooting_spot(observation)),
+        _ if is_closest_teammate(observation, observation.ball) => {
+            (RobotState::SeekBall, observation.ball)
+        }
+        _ => (RobotState::Defend, defense_spot(observation)),
+    };
+
+    let (angle, speed) = match state {
+        RobotState::Shoot => (observation.heading, 0.0),
+        _ => steer(observation, target),
+    };
+
+    Plan {
+        state,
+        target,
+        angle,
+        speed,
+    }
+}
+
+/// Punto frente al aro que ataca el robot, a `SHOOTING_SPOT_DISTANCE`, sin
+/// alejarse mucho de su carril.
+fn shooting_spot(observation: &Observation) -> Vector3 {
+    let ring = observation.target_ring;
+    let side = ring.z.signum();
+
+    Vector3::new(
+        observation.position.x.clamp(-2.0, 2.0),
+        observation.position.y,
+        ring.z - side * SHOOTING_SPOT_DISTANCE,
+    )
+}
+
+/// Punto en la línea entre el aro propio y la pelota, a `DEFENSE_DISTANCE`
+/// del aro.
+fn defense_spot(observation: &Observation) -> Vector3 {
+    let ring = observation.own_ring;
+    let dx = observation.ball.x - ring.x;
+    let dz = observation.ball.z - ring.z;
+    let distance = (dx * dx + dz * dz).sqrt().max(f32::EPSILON);
+    let reach = DEFENSE_DISTANCE.min(distance);
+
+    Vector3::new(
+        ring.x + dx / distance * reach,
+        observation.position.y,
+        ring.z + dz / distance * reach,
+    )
+}
+
+/// Verdadero si el robot es el de su equipo más cercano a `point`.
+fn is_closest_teammate(observation: &Observation, point: Vector3) -> bool {
+    let distance = observation.distance_to(point);
+
+    observation.teammates.iter().all(|teammate| {
+        ((teammate.x - point.x).powi(2) + (teammate.z - point.z).powi(2)).sqrt() > distance
+    })
+}
+
+/// Rumbo y rapidez para llegar a `target`, frenando al final y apartándose
+/// de los compañeros dentro de `AVOID_RADIUS`.
+fn steer(observation: &Observation, target: Vector3) -> (f32, f32) {
+    let position = observation.position;
+    let distance = observation.distance_to(target);
+
+    let (mut avoid_x, mut avoid_z) = (0.0, 0.0);
+
+    for teammate in &observation.teammates {
+        let gap = observation.distance_to(*teammate);
+
+        if gap >= AVOID_RADIUS || gap <= f32::EPSILON {
+            continue;
+        }
+
+        let push = (AVOID_RADIUS - gap) / AVOID_RADIUS;
+        avoid_x += (position.x - teammate.x) / gap * push;
+        avoid_z += (position.z - teammate.z) / gap * push;
+    }
+
+    let (mut x, mut z) = (avoid_x, avoid_z);
+
+    if distance > f32::EPSILON {
+        x += (target.x - position.x) / distance;
+        z += (target.z - position.z) / distance;
+    }
+
+    if (x * x + z * z).sqrt() <= f32::EPSILON {
+        return (observation.heading, 0.0);
+    }
+
+    // Frena al llegar, salvo que tenga que apartarse de un compañero
+    let arrive = (distance / ARRIVE_RADIUS).min(1.0);
+    let avoid = (avoid_x * avoid_x + avoid_z * avoid_z).sqrt().min(1.0);
+
+    (x.atan2(z), MAX_SPEED * arrive.max(avoid))
+}
+
+/// Disparo al aro que ataca el robot con una rapidez al azar, por la rama
+/// alta y sin planificar, como en la caminata original: no se corrige el
+/// arrastre ni se descartan los tiros que entran muy planos, así que muchos
+/// fallan. `None` solo si con esa rapidez la pelota no llega al aro.
+fn random_shot(observation: &Observation, rng: &mut StdRng) -> Option<Shot> {
+    let velocities = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0];
+    let speed = velocities[rng.gen_range(0..velocities.len())];
+
+    Shot::with_speed(observation.ball, observation.target_ring, speed, Arc::High)
+}
+
+/// Pase a un compañero al azar, si tiene alguno.
+fn random_pass(observation: &Observation, rng: &mut StdRng) -> Action {
+    match observation.teammates.len() {
+        0 => Action::Keep,
+        n => Action::Pass(rng.gen_range(0..n)),
+    }
+}
+
+/// Rumbo al azar, en múltiplos de 15°.
+pub(crate) fn random_angle(rng: &mut StdRng) -> f32 {
+    let angles = [
+        0, 15, 30, 45, 60, 75, 90, 105, 120, 135, 150, 165, 180, 195, 210, 225, 240, 255, 270, 285,
+        300, 315, 330, 345,
+    ];
+
+    let angle = angles[rng.gen_range(0..=angles.len() - 1)] as f32;
+
+    angle.to_radians()
+}
+
+fn random_speed(rng: &mut StdRng) -> f32 {
+    let velocities = [1.0, 2.0, 4.0, 5.0, 6.0];
+    velocities[rng.gen_range(0..=velocities.len() - 1)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ball::BallState;
+    use crate::kinematics::Odometry;
+    use crate::{BLUE_RING_POSITION, FIXED_DT, PI, RED_RING_POSITION};
+
+    /// Lo que ve un robot rojo en `(x, z)`, con compañeros en `teammates`.
+    fn red(position: (f32, f32), teammates: &[(f32, f32)], possession: Possession) -> Observation {
+        let point = |(x, z): (f32, f32)| Vector3::new(x, 0.05, z);
+
+        Observation {
+            position: point(position),
+            heading: 0.0,
+            velocity: 0.0,
+            angular_velocity: 0.0,
+            odometry: Odometry::new(point(position), 0.0, 0.0),
+            ball: Vector3::zero(),
+            ball_velocity: Vector3::zero(),
+            ball_state: BallState::Held,
+            possession,
+            teammates: teammates.iter().copied().map(point).collect(),
+            opponents: Vec::new(),
+            target_ring: BLUE_RING_POSITION,
+            own_ring: RED_RING_POSITION,
+            dt: FIXED_DT,
+        }
+    }
+
+    #[test]
+    fn carrier_shoots_in_range_and_drives_otherwise() {
+        // Los rojos atacan el aro azul
+        let near = red((0.0, BLUE_RING_POSITION.z - 2.0), &[], Possession::Mine);
+        let far = red((0.0, 0.0), &[], Possession::Mine);
+
+        assert_eq!(think(&near).state, RobotState::Shoot);
+        assert_eq!(think(&near).speed, 0.0);
+
+        let plan = think(&far);
+        assert_eq!(plan.state, RobotState::DriveToSpot);
+        assert!(plan.angle.cos() > 0.99, "debe ir hacia +z");
+
+        // Bajo el aro no dispara: vuelve hacia el punto de disparo
+        let under = red((0.0, BLUE_RING_POSITION.z - 0.3), &[], Possession::Mine);
+        let plan = think(&under);
+        assert_eq!(plan.state, RobotState::DriveToSpot);
+        assert!(plan.angle.cos() < -0.99, "debe ir hacia -z");
+    }
+
+    #[test]
+    fn closest_defender_chases_and_the_rest_defend() {
+        // Un rival lleva la pelota en el origen
+        let chaser = think(&red((1.0, 0.0), &[(0.0, -6.0)], Possession::Opponent));
+        let defender = think(&red((0.0, -6.0), &[(1.0, 0.0)], Possession::Opponent));
+        let teammate = think(&red((0.0, 4.0), &[], Possession::Teammate));
+
+        assert_eq!(chaser.state, RobotState::SeekBall);
+        assert_eq!(defender.state, RobotState::Defend);
+        assert_eq!(teammate.state, RobotState::DriveToSpot);
+
+        // El defensor rojo se para frente a su aro, en la línea hacia la pelota
+        let spot = defender.target;
+        assert!((spot.z - (RED_RING_POSITION.z + DEFENSE_DISTANCE)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn robots_move_away_from_close_teammates() {
+        let observation = red((0.5, 0.0), &[(0.0, 0.0), (3.0, 0.0)], Possession::Teammate);
+        let (angle, speed) = steer(&observation, observation.position);
+
+        assert!(speed > 0.0);
+        assert!(angle.sin() > 0.99, "debe alejarse del compañero hacia +x");
+    }
+
+    #[test]
+    fn state_machine_turns_before_driving() {
+        let mut rng = crate::utils::rng(Some(0));
+        let mut observation = red((0.0, 0.0), &[], Possession::Mine);
+
+        // Mirando hacia -z, con el punto de disparo hacia +z
+        observation.heading = PI;
+        let backwards = StateMachineController::default().command(&observation, &mut rng);
+        assert_eq!(backwards.linear, 0.0);
+        assert!(backwards.angular.abs() > 1.0);
+
+        observation.heading = 0.0;
+        let forwards = StateMachineController::default().command(&observation, &mut rng);
+        assert!((forwards.linear - MAX_SPEED).abs() < 1e-4);
+        assert!(forwards.angular.abs() < 1e-4);
+    }
+}
@@ -0,0 +1,257 @@
+use super::math::Vector3;
+use super::robot::Robot;
+
+use crate::{
+    BLUE_POLE_POSITION, HALF_STADIUM_LENGTH, HALF_STADIUM_WIDTH, POLE_RADIUS, RED_POLE_POSITION,
+    ROBOT_RADIUS, STADIUM_LENGTH, STADIUM_WIDTH,
+};
+
+/// Pasadas de resolución por paso. Con muchos robots juntos, separar un par
+/// puede meter a uno de ellos en otro, así que se repite unas pocas veces.
+pub const COLLISION_ITERATIONS: usize = 4;
+
+/// Obstáculo fijo circular en el plano XZ.
+#[derive(Debug, Clone, Copy)]
+pub struct Obstacle {
+    pub position: Vector3,
+    pub radius: f32,
+}
+
+/// Postes de los aros.
+pub const HOOP_POLES: [Obstacle; 2] = [
+    Obstacle {
+        position: BLUE_POLE_POSITION,
+        radius: POLE_RADIUS,
+    },
+    Obstacle {
+        position: RED_POLE_POSITION,
+        radius: POLE_RADIUS,
+    },
+];
+
+/// Grilla uniforme sobre el estadio para no comparar todos los pares de
+/// robots: solo se prueban los que están en la misma celda o en una vecina.
+/// Con celdas de lado `2 * ROBOT_RADIUS` ningún par en contacto queda fuera.
+#[derive(Debug, Clone)]
+pub struct SpatialGrid {
+    cell_size: f32,
+    cols: usize,
+    rows: usize,
+    cells: Vec<Vec<usize>>,
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f32) -> Self {
+        let cols = (STADIUM_WIDTH / cell_size).ceil() as usize;
+        let rows = (STADIUM_LENGTH / cell_size).ceil() as usize;
+
+        Self {
+            cell_size,
+            cols,
+            rows,
+            cells: vec![Vec::new(); cols * rows],
+        }
+    }
+
+    fn cell_of(&self, position: Vector3) -> (usize, usize) {
+        let col = ((position.x + HALF_STADIUM_WIDTH) / self.cell_size) as usize;
+        let row = ((position.z + HALF_STADIUM_LENGTH) / self.cell_size) as usize;
+
+        (col.min(self.cols - 1), row.min(self.rows - 1))
+    }
+
+    pub fn rebuild(&mut self, robots: &[Robot]) {
+        for cell in &mut self.cells {
+            cell.clear();
+        }
+
+        for (i, robot) in robots.iter().enumerate() {
+            let (col, row) = self.cell_of(robot.position);
+            self.cells[row * self.cols + col].push(i);
+        }
+    }
+
+    /// Pares `(i, j)` con `i < j` que pueden estar en contacto.
+    pub fn candidate_pairs(&self) -> Vec<(usize, usize)> {
+        let mut pairs = Vec::new();
+
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                let cell = &self.cells[row * self.cols + col];
+
+                for (k, &i) in cell.iter().enumerate() {
+                    // Misma celda
+                    for &j in &cell[k + 1..] {
+                        pairs.push((i.min(j), i.max(j)));
+                    }
+
+                    // Vecinas hacia adelante, para no repetir pares
+                    for (dc, dr) in [(1, 0), (-1, 1), (0, 1), (1, 1)] {
+                        let (c, r) = (col as isize + dc, row as isize + dr);
+
+                        if c < 0 || c >= self.cols as isize || r >= self.rows as isize {
+                            continue;
+                        }
+
+                        for &j in &self.cells[r as usize * self.cols + c as usize] {
+                            pairs.push((i.min(j), i.max(j)));
+                        }
+                    }
+                }
+            }
+        }
+
+        pairs
+    }
+}
+
+impl Default for SpatialGrid {
+    fn default() -> Self {
+        SpatialGrid::new(2.0 * ROBOT_RADIUS)
+    }
+}
+
+/// Separa los robots que se tocan entre sí o con un obstáculo fijo.
+/// Devuelve cuántos contactos entre robots hubo en la primera pasada.
+pub fn resolve_collisions(
+    robots: &mut [Robot],
+    grid: &mut SpatialGrid,
+    obstacles: &[Obstacle],
+) -> usize {
+    let mut contacts = 0;
+
+    for iteration in 0..COLLISION_ITERATIONS {
+        grid.rebuild(robots);
+        let mut resolved = 0;
+
+        for (i, j) in grid.candidate_pairs() {
+            let (left, right) = robots.split_at_mut(j);
+
+            if separate(&mut left[i], &mut right[0]) {
+                resolved += 1;
+            }
+        }
+
+        for robot in robots.iter_mut() {
+            for obstacle in obstacles {
+                push_out(robot, obstacle);
+            }
+
+            robot.clamp_to_stadium();
+        }
+
+        if iteration == 0 {
+            contacts = resolved;
+        }
+
+        if resolved == 0 {
+            break;
+        }
+    }
+
+    contacts
+}
+
+/// Si `a` y `b` se traslapan, los aleja la mitad del traslape cada uno a lo
+/// largo de la línea entre sus centros.
+fn separate(a: &mut Robot, b: &mut Robot) -> bool {
+    let dx = b.position.x - a.position.x;
+    let dz = b.position.z - a.position.z;
+    let distance = (dx * dx + dz * dz).sqrt();
+    let overlap = 2.0 * ROBOT_RADIUS - distance;
+
+    if overlap <= 0.0 {
+        return false;
+    }
+
+    // Con los centros en el mismo punto cualquier dirección sirve
+    let (nx, nz) = if distance > f32::EPSILON {
+        (dx / distance, dz / distance)
+    } else {
+        (1.0, 0.0)
+    };
+
+    a.position.x -= nx * overlap / 2.0;
+    a.position.z -= nz * overlap / 2.0;
+    b.position.x += nx * overlap / 2.0;
+    b.position.z += nz * overlap / 2.0;
+
+    true
+}
+
+/// Saca a `robot` de `obstacle` si se traslapan.
+fn push_out(robot: &mut Robot, obstacle: &Obstacle) {
+    let dx = robot.position.x - obstacle.position.x;
+    let dz = robot.position.z - obstacle.position.z;
+    let distance = (dx * dx + dz * dz).sqrt();
+    let overlap = ROBOT_RADIUS + obstacle.radius - distance;
+
+    if overlap <= 0.0 {
+        return;
+    }
+
+    // Los postes están contra la línea de fondo, así que por defecto se
+    // empuja hacia el centro de la cancha
+    let (nx, nz) = if distance > f32::EPSILON {
+        (dx / distance, dz / distance)
+    } else {
+        (0.0, -obstacle.position.z.signum())
+    };
+
+    robot.position.x += nx * overlap;
+    robot.position.z += nz * overlap;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils;
+
+    fn distance_xz(a: Vector3, b: Vector3) -> f32 {
+        ((a.x - b.x).powi(2) + (a.z - b.z).powi(2)).sqrt()
+    }
+
+    fn robots(n: usize, seed: u64) -> Vec<Robot> {
+        let mut rng = utils::rng(Some(seed));
+        (1..=n).map(|i| Robot::new(i, &mut rng)).collect()
+    }
+
+    #[test]
+    fn grid_finds_every_touching_pair() {
+        let robots = robots(200, 1);
+        let mut grid = SpatialGrid::default();
+        grid.rebuild(&robots);
+
+        let candidates = grid.candidate_pairs();
+
+        for i in 0..robots.len() {
+            for j in i + 1..robots.len() {
+                if distance_xz(robots[i].position, robots[j].position) < 2.0 * ROBOT_RADIUS {
+                    assert!(candidates.contains(&(i, j)), "falta el par ({i}, {j})");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn robots_do_not_overlap_after_resolving() {
+        let mut robots = robots(60, 2);
+        let mut grid = SpatialGrid::default();
+
+        for _ in 0..20 {
+            resolve_collisions(&mut robots, &mut grid, &HOOP_POLES);
+        }
+
+        for i in 0..robots.len() {
+            for j in i + 1..robots.len() {
+                let distance = distance_xz(robots[i].position, robots[j].position);
+                assert!(distance > 2.0 * ROBOT_RADIUS - 0.05);
+            }
+
+            for pole in &HOOP_POLES {
+                let distance = distance_xz(robots[i].position, pole.position);
+                assert!(distance >= ROBOT_RADIUS + pole.radius - 1e-3);
+            }
+        }
+    }
+}
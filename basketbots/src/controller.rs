@@ -0,0 +1,75 @@
+use rand::rngs::StdRng;
+
+use super::ball::BallState;
+use super::kinematics::Odometry;
+use super::math::Vector3;
+use super::shot::Shot;
+
+/// Ganancia (1/s) del giro proporcional de `drive_towards`.
+pub const TURN_GAIN: f32 = 6.0;
+
+/// Quién tiene la pelota, visto por el robot que observa.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Possession {
+    Mine,
+    Teammate,
+    Opponent,
+    Nobody, // Suelta o en el aire
+}
+
+/// Lo que ve un robot al comienzo de un paso.
+#[derive(Debug, Clone)]
+pub struct Observation {
+    pub position: Vector3,      // Posición propia
+    pub heading: f32,           // Rumbo propio, con la misma convención que `Robot::angle`
+    pub velocity: f32,          // Rapidez de avance actual (m/s)
+    pub angular_velocity: f32,  // Velocidad de giro actual (rad/s)
+    pub odometry: Odometry,     // Pose que estima el propio robot
+    pub ball: Vector3,          // Posición de la pelota
+    pub ball_velocity: Vector3, // Velocidad de la pelota (m/s)
+    pub ball_state: BallState,
+    pub possession: Possession,
+    pub teammates: Vec<Vector3>, // Posiciones de los compañeros, sin el propio robot
+    pub opponents: Vec<Vector3>, // Posiciones de los rivales
+    pub target_ring: Vector3,    // Aro que ataca
+    pub own_ring: Vector3,       // Aro que defiende
+    pub dt: f32,                 // Duración del paso (s)
+}
+
+impl Observation {
+    /// Distancia en el plano XZ entre el robot y `position`.
+    pub fn distance_to(&self, position: Vector3) -> f32 {
+        ((position.x - self.position.x).powi(2) + (position.z - self.position.z).powi(2)).sqrt()
+    }
+}
+
+/// Qué hace el robot con la pelota. Se ignora si no la tiene.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Keep, // Sigue botándola
+    Shoot(Shot),
+    Pass(usize), // Índice del compañero en `Observation::teammates`
+}
+
+/// Lo que decide un robot en un paso.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Command {
+    pub linear: f32,  // Rapidez hacia adelante (m/s)
+    pub angular: f32, // Velocidad de giro (rad/s), positiva hacia +x desde +z
+    pub action: Action,
+}
+
+/// Decide los movimientos de un robot a partir de lo que observa. El estadio
+/// guarda uno por robot, así que la implementación puede tener estado propio
+/// (una máquina de estados, una política aprendida o un proceso externo).
+pub trait RobotController: std::fmt::Debug {
+    fn command(&mut self, observation: &Observation, rng: &mut StdRng) -> Command;
+}
+
+/// Rapidez y giro para seguir el rumbo `angle` a `speed` con tracción
+/// diferencial: gira en proporción al error de rumbo, por el lado más corto,
+/// y avanza menos cuanto peor orientado está (nada si mira hacia atrás).
+pub fn drive_towards(heading: f32, angle: f32, speed: f32) -> (f32, f32) {
+    let delta = (angle - heading).sin().atan2((angle - heading).cos());
+    (speed * delta.cos().max(0.0), TURN_GAIN * delta)
+}
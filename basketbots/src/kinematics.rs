@@ -0,0 +1,197 @@
+use rand::rngs::StdRng;
+
+use crate::{PI, math::Vector3, utils};
+
+/// Distancia entre las ruedas (m).
+pub const WHEEL_BASE: f32 = 0.4;
+/// Rapidez máxima de cada rueda (m/s).
+pub const MAX_WHEEL_SPEED: f32 = 6.0;
+/// Aceleración máxima de cada rueda (m/s²).
+pub const MAX_WHEEL_ACCELERATION: f32 = 8.0;
+/// Velocidad de giro máxima del robot (rad/s).
+pub const MAX_TURN_RATE: f32 = 2.0 * PI;
+/// Desviación estándar del error de cada rueda medido por la odometría,
+/// relativa a lo que avanzó la rueda.
+pub const ODOMETRY_NOISE: f32 = 0.02;
+
+/// Límites de un robot de tracción diferencial.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DriveLimits {
+    pub wheel_base: f32,       // Distancia entre ruedas (m)
+    pub max_wheel_speed: f32,  // m/s
+    pub max_acceleration: f32, // m/s² por rueda
+    pub max_turn_rate: f32,    // rad/s
+}
+
+impl Default for DriveLimits {
+    fn default() -> Self {
+        Self {
+            wheel_base: WHEEL_BASE,
+            max_wheel_speed: MAX_WHEEL_SPEED,
+            max_acceleration: MAX_WHEEL_ACCELERATION,
+            max_turn_rate: MAX_TURN_RATE,
+        }
+    }
+}
+
+/// Velocidades de las dos ruedas (m/s). Un giro positivo (de +z hacia +x,
+/// como `Robot::angle`) hace que la rueda derecha vaya más rápido.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DifferentialDrive {
+    pub left: f32,
+    pub right: f32,
+}
+
+impl DifferentialDrive {
+    /// Rapidez de avance del centro del robot (m/s).
+    pub fn linear(&self) -> f32 {
+        (self.left + self.right) / 2.0
+    }
+
+    /// Velocidad de giro (rad/s) con ruedas separadas por `wheel_base`.
+    pub fn angular(&self, wheel_base: f32) -> f32 {
+        (self.right - self.left) / wheel_base
+    }
+
+    /// Velocidades de rueda que piden `linear` y `angular`, dentro de los
+    /// límites. Si una rueda se pasa de `max_wheel_speed` se escalan las dos,
+    /// así que se respeta la curva a costa de la rapidez.
+    pub fn wheel_targets(limits: &DriveLimits, linear: f32, angular: f32) -> DifferentialDrive {
+        let angular = angular.clamp(-limits.max_turn_rate, limits.max_turn_rate);
+        let half_turn = angular * limits.wheel_base / 2.0;
+        let (left, right) = (linear - half_turn, linear + half_turn);
+
+        let fastest = left.abs().max(right.abs());
+        let scale = match fastest > limits.max_wheel_speed {
+            true => limits.max_wheel_speed / fastest,
+            false => 1.0,
+        };
+
+        DifferentialDrive {
+            left: left * scale,
+            right: right * scale,
+        }
+    }
+
+    /// Acerca cada rueda a la velocidad pedida sin pasar `max_acceleration`.
+    pub fn step(&mut self, limits: &DriveLimits, linear: f32, angular: f32, dt: f32) {
+        let target = DifferentialDrive::wheel_targets(limits, linear, angular);
+        let max_change = limits.max_acceleration * dt;
+
+        self.left += (target.left - self.left).clamp(-max_change, max_change);
+        self.right += (target.right - self.right).clamp(-max_change, max_change);
+    }
+}
+
+/// Pose que estima el robot integrando lo que miden sus ruedas. No ve los
+/// choques ni el borde del estadio, así que se aleja de la real con el tiempo.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Odometry {
+    pub position: Vector3,
+    pub heading: f32,
+    pub noise: f32, // Como `ODOMETRY_NOISE`
+}
+
+impl Odometry {
+    pub fn new(position: Vector3, heading: f32, noise: f32) -> Self {
+        Self {
+            position,
+            heading,
+            noise,
+        }
+    }
+
+    /// Integra un paso en que las ruedas avanzaron `drive * dt`, con un error
+    /// gaussiano en cada rueda.
+    pub fn update(
+        &mut self,
+        drive: &DifferentialDrive,
+        wheel_base: f32,
+        dt: f32,
+        rng: &mut StdRng,
+    ) {
+        let mut measure = |speed: f32| speed * dt * (1.0 + self.noise * utils::gaussian(rng));
+        let (left, right) = (measure(drive.left), measure(drive.right));
+
+        let distance = (left + right) / 2.0;
+        let turn = (right - left) / wheel_base;
+
+        advance(&mut self.position, &mut self.heading, distance, turn);
+    }
+
+    /// Distancia en el plano XZ entre la pose estimada y `position`.
+    pub fn error(&self, position: Vector3) -> f32 {
+        ((position.x - self.position.x).powi(2) + (position.z - self.position.z).powi(2)).sqrt()
+    }
+}
+
+/// Avanza la pose `distance` metros girando `turn` radianes, con el rumbo
+/// del punto medio del arco.
+pub fn advance(position: &mut Vector3, heading: &mut f32, distance: f32, turn: f32) {
+    let middle = *heading + turn / 2.0;
+
+    position.x += distance * middle.sin();
+    position.z += distance * middle.cos();
+    *heading += turn;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FIXED_DT;
+
+    #[test]
+    fn wheels_respect_acceleration_and_speed_limits() {
+        let limits = DriveLimits::default();
+        let mut drive = DifferentialDrive::default();
+
+        drive.step(&limits, 100.0, 0.0, FIXED_DT);
+        assert!((drive.linear() - MAX_WHEEL_ACCELERATION * FIXED_DT).abs() < 1e-5);
+
+        for _ in 0..600 {
+            drive.step(&limits, 100.0, 100.0, FIXED_DT);
+        }
+
+        assert!(drive.left.abs() <= MAX_WHEEL_SPEED + 1e-4);
+        assert!(drive.right.abs() <= MAX_WHEEL_SPEED + 1e-4);
+        assert!(drive.angular(limits.wheel_base) <= MAX_TURN_RATE + 1e-4);
+    }
+
+    #[test]
+    fn turning_in_place_keeps_the_position() {
+        let limits = DriveLimits::default();
+        let drive = DifferentialDrive::wheel_targets(&limits, 0.0, 1.0);
+        let (mut position, mut heading) = (Vector3::zero(), 0.0);
+
+        for _ in 0..60 {
+            let turn = drive.angular(limits.wheel_base) * FIXED_DT;
+            advance(&mut position, &mut heading, drive.linear() * FIXED_DT, turn);
+        }
+
+        assert!((heading - 1.0).abs() < 1e-4);
+        assert!(position.length() < 1e-6);
+    }
+
+    #[test]
+    fn odometry_drifts_only_with_noise() {
+        let mut rng = utils::rng(Some(0));
+        let drive = DifferentialDrive {
+            left: 2.0,
+            right: 2.5,
+        };
+        let mut exact = Odometry::new(Vector3::zero(), 0.0, 0.0);
+        let mut noisy = Odometry::new(Vector3::zero(), 0.0, 0.1);
+        let (mut position, mut heading) = (Vector3::zero(), 0.0);
+
+        for _ in 0..600 {
+            exact.update(&drive, WHEEL_BASE, FIXED_DT, &mut rng);
+            noisy.update(&drive, WHEEL_BASE, FIXED_DT, &mut rng);
+
+            let turn = drive.angular(WHEEL_BASE) * FIXED_DT;
+            advance(&mut position, &mut heading, drive.linear() * FIXED_DT, turn);
+        }
+
+        assert!(exact.error(position) < 1e-3);
+        assert!(noisy.error(position) > 1e-2);
+    }
+}
@@ -0,0 +1,72 @@
+pub mod ball;
+pub mod behavior;
+pub mod collision;
+pub mod controller;
+pub mod kinematics;
+pub mod math;
+#[cfg(feature = "render")]
+pub mod render;
+pub mod robot;
+pub mod score;
+pub mod shot;
+pub mod stadium;
+mod utils;
+
+use math::Vector3;
+
+pub const MAX_ROBOTS: usize = 10;
+pub const STADIUM_WIDTH: f32 = 10.0;
+pub const STADIUM_LENGTH: f32 = 20.0;
+
+pub const HALF_STADIUM_WIDTH: f32 = STADIUM_WIDTH / 2.0;
+pub const HALF_STADIUM_LENGTH: f32 = STADIUM_LENGTH / 2.0;
+
+pub const STADIUM_FLOOR_THICKNESS: f32 = 0.1;
+pub const ROBOT_RADIUS: f32 = 0.5;
+pub const BALL_RADIUS: f32 = 0.15;
+pub const RING_RADIUS: f32 = 0.23;
+pub const RIM_THICKNESS: f32 = 0.01;
+
+// Tableros: distancia del centro del aro a la cara del tablero, cuánto baja
+// el tablero bajo el aro y su tamaño
+pub const BACKBOARD_OFFSET: f32 = 0.35;
+pub const BACKBOARD_DROP: f32 = 0.1;
+pub const BACKBOARD_WIDTH: f32 = 1.2;
+pub const BACKBOARD_HEIGHT: f32 = 0.7;
+
+pub const G: f32 = 9.8;
+
+pub const BLUE_RING_POSITION: Vector3 = Vector3 {
+    x: 0.0,
+    y: 1.25,
+    z: HALF_STADIUM_LENGTH - 0.55,
+};
+
+pub const RED_RING_POSITION: Vector3 = Vector3 {
+    x: 0.0,
+    y: 1.25,
+    z: -(HALF_STADIUM_LENGTH - 0.55),
+};
+
+pub const POLE_RADIUS: f32 = 0.15;
+
+// Postes de los aros, contra la línea de fondo detrás de cada tablero
+pub const BLUE_POLE_POSITION: Vector3 = Vector3 {
+    x: 0.0,
+    y: 0.0,
+    z: HALF_STADIUM_LENGTH - 0.2,
+};
+
+pub const RED_POLE_POSITION: Vector3 = Vector3 {
+    x: 0.0,
+    y: 0.0,
+    z: -(HALF_STADIUM_LENGTH - 0.2),
+};
+
+pub const PI: f32 = std::f32::consts::PI;
+
+/// Paso fijo de la física (s), independiente de los FPS.
+pub const FIXED_DT: f32 = 1.0 / 60.0;
+/// Tiempo máximo de un cuadro que se simula, para no quedar atrás sin remedio
+/// después de una pausa larga (por ejemplo al mover la ventana).
+pub const MAX_FRAME_TIME: f32 = 0.25;
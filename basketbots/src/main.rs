@@ -1,39 +1,94 @@
-mod ball;
+#[cfg(feature = "render")]
 mod camera;
-mod robot;
-mod stadium;
-mod utils;
 
-use raylib::prelude::*;
-use stadium::Stadium;
+use basketbots::{
+    BLUE_RING_POSITION, MAX_ROBOTS,
+    behavior::Behavior,
+    math::Vector3,
+    shot::{self, ShotNoise, ShotPlanner},
+    stadium::Stadium,
+};
+use rand::{SeedableRng, rngs::StdRng};
 
-pub const MAX_ROBOTS: u8 = 10;
-pub const STADIUM_WIDTH: f32 = 10.0;
-pub const STADIUM_LENGTH: f32 = 20.0;
+/// Duración de un partido sin ventana (s), si `--headless` no indica otra.
+const MATCH_DURATION: f32 = 120.0;
+/// Disparos por distancia en `--shot-study`, si no indica otra cantidad.
+const STUDY_TRIALS: usize = 500;
 
-pub const HALF_STADIUM_WIDTH: f32 = STADIUM_WIDTH / 2.0;
-pub const HALF_STADIUM_LENGTH: f32 = STADIUM_LENGTH / 2.0;
+fn main() {
+    // Con --shot-study[=disparos] se mide la probabilidad de acierto según la distancia
+    if let Some(trials) = std::env::args().find_map(|arg| {
+        (arg == "--shot-study")
+            .then_some(STUDY_TRIALS)
+            .or_else(|| arg.strip_prefix("--shot-study=")?.parse().ok())
+    }) {
+        shot_study(trials);
+        return;
+    }
 
-pub const STADIUM_FLOOR_THICKNESS: f32 = 0.1;
-pub const ROBOT_RADIUS: f32 = 0.5;
+    let n_robots = std::env::args()
+        .find_map(|arg| arg.strip_prefix("--robots=")?.parse().ok())
+        .unwrap_or(MAX_ROBOTS);
 
-pub const G: f32 = 9.8;
+    let mut stadium = Stadium::with_robots(n_robots, None);
 
-pub const BLUE_RING_POSITION: Vector3 = Vector3 {
-    x: 0.0,
-    y: 1.25,
-    z: HALF_STADIUM_LENGTH - 0.55,
-};
+    // --red=random|fsm y --blue=random|fsm eligen el comportamiento de cada equipo
+    for (flag, color) in [("--red=", "red_robot"), ("--blue=", "blue_robot")] {
+        let name = std::env::args().find_map(|arg| arg.strip_prefix(flag).map(str::to_string));
 
-pub const RED_RING_POSITION: Vector3 = Vector3 {
-    x: 0.0,
-    y: 1.25,
-    z: -(HALF_STADIUM_LENGTH - 0.55),
-};
+        if let Some(name) = name {
+            match Behavior::from_name(&name) {
+                Some(behavior) => stadium.set_behavior(color, behavior),
+                None => eprintln!("Unknown behavior {name:?}, expected random or fsm"),
+            }
+        }
+    }
 
-pub const PI: f32 = std::f32::consts::PI;
+    // --odometry-noise=X cambia el error relativo de las ruedas que mide la odometría
+    if let Some(noise) =
+        std::env::args().find_map(|arg| arg.strip_prefix("--odometry-noise=")?.parse().ok())
+    {
+        stadium.set_odometry_noise(noise);
+    }
+
+    // Con --headless[=segundos] se juega un partido sin abrir la ventana
+    if let Some(duration) = std::env::args().find_map(|arg| {
+        (arg == "--headless")
+            .then_some(MATCH_DURATION)
+            .or_else(|| arg.strip_prefix("--headless=")?.parse().ok())
+    }) {
+        let score = stadium.run(duration);
+        let made = stadium.shots().iter().filter(|shot| shot.made).count();
+
+        println!(
+            "Match of {duration:.0} s: RED {} - {} BLUE",
+            score.red, score.blue
+        );
+        println!("Shots: {}  Made: {}", stadium.shots().len(), made);
+        println!(
+            "Passes: {}  Steals: {}  Rebounds: {}",
+            stadium.stats().passes,
+            stadium.stats().steals,
+            stadium.stats().rebounds
+        );
+        println!("Robot contacts: {}", stadium.contacts());
+        println!("Mean odometry error: {:.2} m", stadium.odometry_error());
+        return;
+    }
+
+    #[cfg(feature = "render")]
+    run_window(stadium);
+
+    #[cfg(not(feature = "render"))]
+    eprintln!("Built without the render feature: use --headless or cargo run --features render");
+}
+
+/// Abre la ventana y juega el partido en tiempo real.
+#[cfg(feature = "render")]
+fn run_window(mut stadium: Stadium) {
+    use basketbots::{FIXED_DT, MAX_FRAME_TIME, render::Renderer};
+    use raylib::prelude::{Color, RaylibDraw, RaylibMode3DExt};
 
-fn main() {
     let (mut rlib, thread) = raylib::init()
         .size(950, 800)
         .title("Basketbots - Robotics INFO1167")
@@ -45,15 +100,63 @@ fn main() {
     rlib.set_target_fps(60);
 
     let mut camera = camera::init();
-    let mut stadium = Stadium::new(&mut rlib, &thread);
+    let renderer = Renderer::new(&mut rlib, &thread);
+    let mut accumulator = 0.0;
 
     while !rlib.window_should_close() {
         camera::update(&rlib, &mut camera);
 
+        // La física avanza en pasos fijos; el tiempo que sobra queda para el
+        // siguiente cuadro
+        accumulator += rlib.get_frame_time().min(MAX_FRAME_TIME);
+
+        while accumulator >= FIXED_DT {
+            stadium.update(FIXED_DT);
+            accumulator -= FIXED_DT;
+        }
+
         let mut drawer = rlib.begin_drawing(&thread);
         drawer.clear_background(Color::BLACK);
 
-        stadium.update();
-        stadium.draw(&mut drawer.begin_mode3D(camera));
+        renderer.draw(&mut drawer.begin_mode3D(camera), &stadium);
+        renderer.draw_hud(&mut drawer, &stadium);
+    }
+}
+
+/// Imprime el disparo que elige `ShotPlanner` a cada distancia del aro y la
+/// fracción que entra sin error y con el error por defecto.
+fn shot_study(trials: usize) {
+    let planner = ShotPlanner::default();
+    let noise = ShotNoise::default();
+    let mut rng = StdRng::seed_from_u64(0);
+
+    println!(
+        "Shot study: {trials} shots per distance, noise {:.0}% speed, {:.1} deg angle",
+        noise.speed * 100.0,
+        noise.angle.to_degrees()
+    );
+    println!("Distance  Speed  Elevation  Entry  Made (exact)  Made (noisy)");
+
+    for distance in 1..=9 {
+        let distance = distance as f32;
+        let ring = BLUE_RING_POSITION;
+        let origin = Vector3::new(ring.x, shot::RELEASE_HEIGHT, ring.z - distance);
+
+        let Some(plan) = planner.plan(origin, ring) else {
+            println!("{distance:>6.1} m  out of range");
+            continue;
+        };
+
+        let exact = shot::success_rate(&planner, &ShotNoise::NONE, distance, 1, &mut rng);
+        let noisy = shot::success_rate(&planner, &noise, distance, trials, &mut rng);
+
+        println!(
+            "{distance:>6.1} m {:>5.2} {:>8.1}° {:>5.1}° {:>11.0}% {:>12.1}%",
+            plan.speed,
+            plan.elevation.to_degrees(),
+            plan.entry_angle(origin, ring).to_degrees(),
+            exact * 100.0,
+            noisy * 100.0
+        );
     }
 }
@@ -0,0 +1,92 @@
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+/// Vector de la física (m, m/s). No depende de Raylib, así que la simulación
+/// se puede usar y probar sin la biblioteca gráfica; `render` lo convierte
+/// con `From` al dibujar.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Vector3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Vector3 {
+    pub const fn new(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z }
+    }
+
+    pub const fn zero() -> Self {
+        Self::new(0.0, 0.0, 0.0)
+    }
+
+    pub fn dot(&self, other: Vector3) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn length(&self) -> f32 {
+        self.dot(*self).sqrt()
+    }
+
+    pub fn distance_to(&self, other: Vector3) -> f32 {
+        (*self - other).length()
+    }
+}
+
+impl Add for Vector3 {
+    type Output = Vector3;
+
+    fn add(self, other: Vector3) -> Vector3 {
+        Vector3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl Sub for Vector3 {
+    type Output = Vector3;
+
+    fn sub(self, other: Vector3) -> Vector3 {
+        Vector3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl Mul<f32> for Vector3 {
+    type Output = Vector3;
+
+    fn mul(self, factor: f32) -> Vector3 {
+        Vector3::new(self.x * factor, self.y * factor, self.z * factor)
+    }
+}
+
+impl Div<f32> for Vector3 {
+    type Output = Vector3;
+
+    fn div(self, divisor: f32) -> Vector3 {
+        Vector3::new(self.x / divisor, self.y / divisor, self.z / divisor)
+    }
+}
+
+impl Neg for Vector3 {
+    type Output = Vector3;
+
+    fn neg(self) -> Vector3 {
+        self * -1.0
+    }
+}
+
+impl AddAssign for Vector3 {
+    fn add_assign(&mut self, other: Vector3) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Vector3 {
+    fn sub_assign(&mut self, other: Vector3) {
+        *self = *self - other;
+    }
+}
+
+#[cfg(feature = "render")]
+impl From<Vector3> for raylib::prelude::Vector3 {
+    fn from(vector: Vector3) -> Self {
+        raylib::prelude::Vector3::new(vector.x, vector.y, vector.z)
+    }
+}
@@ -0,0 +1,261 @@
+use raylib::prelude::*;
+use std::collections::HashMap;
+
+use super::ball::Ball;
+use super::robot::Robot;
+use super::stadium::Stadium;
+
+use crate::{BALL_RADIUS, HALF_STADIUM_LENGTH, STADIUM_LENGTH, STADIUM_WIDTH};
+
+const LINE_THICKNESS: f32 = 0.1;
+const FLOOR_THICKNESS: f32 = 0.1;
+const LINE_HEIGHT: f32 = 0.01;
+const SHOT_LOG_LINES: usize = 8;
+
+/// Dibuja un `Stadium` con Raylib. Es lo único que necesita una ventana.
+#[derive(Debug)]
+pub struct Renderer {
+    models: HashMap<&'static str, Model>,
+}
+
+impl Renderer {
+    pub fn new(rlib: &mut RaylibHandle, thread: &RaylibThread) -> Self {
+        Self {
+            models: load_models(rlib, thread),
+        }
+    }
+
+    pub fn draw(&self, drawer: &mut RaylibMode3D<'_, RaylibDrawHandle<'_>>, stadium: &Stadium) {
+        self.draw_floor(drawer);
+        self.draw_hoops(drawer);
+
+        for robot in stadium.robots() {
+            Renderer::draw_robot(drawer, robot);
+        }
+
+        Renderer::draw_ball(drawer, stadium.ball());
+    }
+
+    /// Marcador y últimos disparos, dibujados en 2D sobre la vista 3D.
+    pub fn draw_hud(&self, drawer: &mut RaylibDrawHandle<'_>, stadium: &Stadium) {
+        // Draw the scoreboard
+
+        drawer.draw_rectangle(10, 10, 260, 50, Color::BLACK.alpha(0.6));
+        drawer.draw_text(
+            &format!("RED {}", stadium.score().red),
+            20,
+            22,
+            28,
+            Color::RED,
+        );
+        drawer.draw_text("-", 135, 22, 28, Color::WHITESMOKE);
+        drawer.draw_text(
+            &format!("{} BLUE", stadium.score().blue),
+            160,
+            22,
+            28,
+            Color::BLUE,
+        );
+
+        // Draw the shot log
+
+        let shots = stadium.shots();
+        let made = shots.iter().filter(|shot| shot.made).count();
+        let stats = stadium.stats();
+        let height = 55 + 20 * SHOT_LOG_LINES.min(shots.len()) as i32;
+
+        drawer.draw_rectangle(10, 70, 260, height, Color::BLACK.alpha(0.6));
+        drawer.draw_text(
+            &format!("Shots: {}  Made: {}", shots.len(), made),
+            20,
+            78,
+            18,
+            Color::WHITESMOKE,
+        );
+        drawer.draw_text(
+            &format!(
+                "Passes: {}  Steals: {}  Rebounds: {}",
+                stats.passes, stats.steals, stats.rebounds
+            ),
+            20,
+            100,
+            14,
+            Color::LIGHTGRAY,
+        );
+
+        for (line, shot) in shots.iter().rev().take(SHOT_LOG_LINES).enumerate() {
+            let color = match shot.color {
+                "red_robot" => Color::RED,
+                _ => Color::SKYBLUE,
+            };
+
+            drawer.draw_text(
+                &format!(
+                    "#{:<2} {:>5.1} m {:>4.1} m/s {}",
+                    shot.shooter,
+                    shot.distance,
+                    shot.velocity,
+                    if shot.made { "made" } else { "missed" }
+                ),
+                20,
+                125 + 20 * line as i32,
+                16,
+                color,
+            );
+        }
+    }
+
+    fn draw_floor(&self, drawer: &mut RaylibMode3D<'_, RaylibDrawHandle<'_>>) {
+        // Draw the stadium floor
+
+        drawer.draw_cube(
+            Vector3::new(0.0, 0.0, 0.0),
+            STADIUM_WIDTH,
+            FLOOR_THICKNESS,
+            STADIUM_LENGTH,
+            Color::DARKSLATEGRAY,
+        );
+
+        // Draw stadium floor outside white lines
+
+        drawer.draw_cube(
+            Vector3::new(0.0, -0.005, 0.0),
+            STADIUM_WIDTH + 0.2,
+            FLOOR_THICKNESS,
+            STADIUM_LENGTH + 0.2,
+            Color::WHITESMOKE,
+        );
+
+        // Draw the stadium black platform
+
+        drawer.draw_cube(
+            Vector3::new(0.0, -10.0, 0.0),
+            STADIUM_WIDTH,
+            20.0,
+            STADIUM_LENGTH * 2.0,
+            Color::BLACK,
+        );
+
+        // Draw the center line
+
+        drawer.draw_cube(
+            Vector3::new(0.0, 0.08, 0.0),
+            STADIUM_WIDTH + 0.1,
+            LINE_HEIGHT,
+            LINE_THICKNESS,
+            Color::WHITESMOKE,
+        );
+
+        // Draw the center circle perimeter
+
+        drawer.draw_cylinder(
+            Vector3::new(0.0, 0.05, 0.0),
+            2.0,         // radio arriba
+            2.0,         // radio abajo
+            LINE_HEIGHT, // altura
+            64,          // cantidad de lados
+            Color::WHITESMOKE,
+        );
+
+        // Draw the center circle content
+
+        drawer.draw_cylinder(
+            Vector3::new(0.0, 0.05, 0.0),
+            1.9,                // radio arriba
+            1.9,                // radio abajo
+            LINE_HEIGHT + 0.01, // altura
+            64,                 // cantidad de lados
+            Color::DARKSLATEGRAY,
+        );
+    }
+
+    fn draw_hoops(&self, drawer: &mut RaylibMode3D<'_, RaylibDrawHandle<'_>>) {
+        drawer.draw_model_ex(
+            &self.models["blue_hoop"],
+            Vector3::new(-0.35, -4.0, HALF_STADIUM_LENGTH + 1.0),
+            Vector3::new(0.0, 1.0, 1.0),
+            180.0,
+            Vector3::new(0.018, 0.018, 0.018),
+            Color::WHITESMOKE,
+        );
+
+        drawer.draw_model_ex(
+            &self.models["red_hoop"],
+            Vector3::new(0.35, -4.0, -HALF_STADIUM_LENGTH - 1.0),
+            Vector3::new(1.0, 0.0, 0.0),
+            270.0,
+            Vector3::new(0.018, 0.018, 0.018),
+            Color::WHITESMOKE,
+        );
+    }
+
+    fn draw_robot(drawer: &mut RaylibMode3D<'_, RaylibDrawHandle<'_>>, robot: &Robot) {
+        let color = match robot.color {
+            "red_robot" => Color::DARKRED,
+            "blue_robot" => Color::DARKBLUE,
+            _ => Color::WHITE,
+        };
+
+        drawer.draw_cylinder(
+            Vector3::new(robot.position.x, robot.position.y, robot.position.z),
+            0.25,
+            0.25,
+            0.15,
+            32,
+            Color::BLACK,
+        );
+
+        drawer.draw_cylinder(
+            Vector3::new(robot.position.x, robot.position.y, robot.position.z),
+            0.2,
+            0.2,
+            0.17,
+            32,
+            color,
+        );
+
+        // Draw the heading marker
+
+        let nose = Vector3::new(
+            robot.position.x + 0.2 * robot.angle.sin(),
+            robot.position.y + 0.17,
+            robot.position.z + 0.2 * robot.angle.cos(),
+        );
+        drawer.draw_line_3D(
+            Vector3::new(robot.position.x, nose.y, robot.position.z),
+            nose,
+            Color::WHITESMOKE,
+        );
+        drawer.draw_sphere(nose, 0.04, Color::WHITESMOKE);
+
+        // Draw where the odometry thinks the robot is
+
+        drawer.draw_sphere_wires(Vector3::from(robot.odometry.position), 0.05, 4, 8, color);
+    }
+
+    fn draw_ball(drawer: &mut RaylibMode3D<'_, RaylibDrawHandle<'_>>, ball: &Ball) {
+        drawer.draw_sphere(Vector3::from(ball.position), BALL_RADIUS, Color::WHITESMOKE);
+    }
+}
+
+fn load_models(rlib: &mut RaylibHandle, thread: &RaylibThread) -> HashMap<&'static str, Model> {
+    let mut models = HashMap::new();
+    let model_srcs = HashMap::from([
+        ("red_hoop", "./assets/hoop/red.obj"),
+        ("blue_hoop", "./assets/hoop/blue.obj"),
+        ("ball", "./assets/ball.glb"),
+    ]);
+
+    for (key, value) in model_srcs {
+        models.insert(key, rlib.load_model(thread, value).unwrap());
+    }
+
+    let mut mut_color = |k: &str, c: Color| {
+        models.get_mut(k).unwrap().materials_mut()[0].maps_mut()[0].color = c.into();
+    };
+
+    mut_color("red_hoop", Color::RED);
+    mut_color("blue_hoop", Color::BLUE);
+
+    models
+}
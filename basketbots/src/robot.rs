@@ -1,190 +1,153 @@
 use crate::{
-    BLUE_RING_POSITION, G, HALF_STADIUM_LENGTH, HALF_STADIUM_WIDTH, RED_RING_POSITION,
-    ROBOT_RADIUS, utils::random,
+    BALL_RADIUS, BLUE_RING_POSITION, G, HALF_STADIUM_LENGTH, HALF_STADIUM_WIDTH, RED_RING_POSITION,
+    ROBOT_RADIUS,
 };
 
-use rand::Rng;
-use raylib::ffi::GetFrameTime;
-use raylib::prelude::*;
+use rand::{Rng, rngs::StdRng};
 
-use super::ball::Ball;
+use super::ball::{Ball, BallState};
+use super::behavior;
+use super::controller::Command;
+use super::kinematics::{self, DifferentialDrive, DriveLimits, ODOMETRY_NOISE, Odometry};
+use super::math::Vector3;
+
+/// Altura máxima (m) a la que un robot alcanza una pelota suelta.
+pub const PICKUP_HEIGHT: f32 = 0.6;
+/// Rapidez horizontal de un pase (m/s).
+pub const PASS_SPEED: f32 = 5.0;
+/// Tiempo de vuelo mínimo de un pase (s), para que los pases cortos no vayan rasantes.
+pub const MIN_PASS_TIME: f32 = 0.3;
 
 #[derive(Debug, Clone)]
 pub struct Robot {
     pub angle: f32,
-    pub velocity: f32,
-    pub n_step: i16,
+    pub velocity: f32,         // Rapidez de avance (m/s), la que dan las ruedas
+    pub angular_velocity: f32, // Velocidad de giro (rad/s), la que dan las ruedas
     pub position: Vector3,
     pub color: &'static str,
-    pub ball: Ball,
+    pub drive: DifferentialDrive,
+    pub limits: DriveLimits,
+    pub odometry: Odometry,
+    pub command: (f32, f32), // Rapidez y giro pedidos por el controlador
 }
 
 impl Robot {
-    pub fn new(iter: u8) -> Self {
-        let rd_angle = Robot::get_rd_angle();
-        let rd_velocity = Robot::get_rd_velocity() * Robot::get_delta_time();
-
+    pub fn new(iter: usize, rng: &mut StdRng) -> Self {
         let position = Vector3 {
-            x: random().gen_range(-HALF_STADIUM_WIDTH..=HALF_STADIUM_WIDTH),
+            x: rng.gen_range(-HALF_STADIUM_WIDTH..=HALF_STADIUM_WIDTH),
             y: 0.05,
-            z: random().gen_range(-HALF_STADIUM_LENGTH..=HALF_STADIUM_LENGTH),
+            z: rng.gen_range(-HALF_STADIUM_LENGTH..=HALF_STADIUM_LENGTH),
         };
 
-        let color = match iter % 2 == 0 {
+        let color = match iter.is_multiple_of(2) {
             true => "red_robot",
             false => "blue_robot",
         };
 
+        let angle = behavior::random_angle(rng);
+
         Robot {
-            angle: rd_angle,
-            velocity: rd_velocity,
-            n_step: random().gen_range(10..=200),
+            angle,
+            velocity: 0.0,
+            angular_velocity: 0.0,
             position,
             color,
-            ball: Ball::new(position),
+            drive: DifferentialDrive::default(),
+            limits: DriveLimits::default(),
+            odometry: Odometry::new(position, angle, ODOMETRY_NOISE),
+            command: (0.0, 0.0),
         }
     }
 
-    pub fn draw(&self, drawer: &mut RaylibMode3D<'_, RaylibDrawHandle<'_>>) {
-        let color = match self.color {
-            "red_robot" => Color::DARKRED,
-            "blue_robot" => Color::DARKBLUE,
-            _ => Color::WHITE,
-        };
-
-        drawer.draw_cylinder(
-            Vector3::new(self.position.x, self.position.y, self.position.z),
-            0.25,
-            0.25,
-            0.15,
-            32,
-            Color::BLACK,
-        );
-
-        drawer.draw_cylinder(
-            Vector3::new(self.position.x, self.position.y, self.position.z),
-            0.2,
-            0.2,
-            0.17,
-            32,
-            color,
+    /// Avanza el robot `dt` segundos como un robot de tracción diferencial:
+    /// las ruedas se acercan a lo que pide `command` dentro de `limits`, el
+    /// robot avanza por un arco y la odometría integra lo que midieron las
+    /// ruedas.
+    pub fn update(&mut self, dt: f32, rng: &mut StdRng) {
+        let (linear, angular) = self.command;
+        self.drive.step(&self.limits, linear, angular, dt);
+
+        self.velocity = self.drive.linear();
+        self.angular_velocity = self.drive.angular(self.limits.wheel_base);
+        self.odometry
+            .update(&self.drive, self.limits.wheel_base, dt, rng);
+
+        kinematics::advance(
+            &mut self.position,
+            &mut self.angle,
+            self.velocity * dt,
+            self.angular_velocity * dt,
         );
 
-        self.ball.draw();
+        self.clamp_to_stadium();
     }
 
-    pub fn update(&mut self) {
-        self.n_step -= 1;
-
-        if self.n_step <= 0 {
-            let n_step = random().gen_range(10..=200);
-
-            let rd_angle = Robot::get_rd_angle();
-            let rd_velocity = Robot::get_rd_velocity() * Robot::get_delta_time();
-
-            self.angle = rd_angle;
-            self.n_step = n_step;
-            self.velocity = rd_velocity;
-        }
-
-        let position = Vector3 {
-            x: self.velocity * self.angle.sin(),
-            y: 0.05,
-            z: self.velocity * self.angle.cos(),
-        };
-
-        self.position.x += position.x;
-        self.position.z += position.z;
-
-        if self.position.x > HALF_STADIUM_WIDTH - ROBOT_RADIUS {
-            self.position.x = HALF_STADIUM_WIDTH - ROBOT_RADIUS;
-        }
-
-        if self.position.x < -HALF_STADIUM_WIDTH + ROBOT_RADIUS {
-            self.position.x = -HALF_STADIUM_WIDTH + ROBOT_RADIUS;
-        }
-
-        if self.position.z > HALF_STADIUM_LENGTH - ROBOT_RADIUS {
-            self.position.z = HALF_STADIUM_LENGTH - ROBOT_RADIUS;
-        }
-
-        if self.position.z < -HALF_STADIUM_LENGTH + ROBOT_RADIUS {
-            self.position.z = -HALF_STADIUM_LENGTH + ROBOT_RADIUS;
-        }
-
-        if random().r#gen::<f32>() < 0.1 && !self.ball.is_shooting {
-            self.shot_ball();
-        }
-
-        self.ball.update(self.position);
+    /// Guarda las velocidades que pide `command`; la acción sobre la pelota
+    /// la resuelve el estadio.
+    pub fn apply(&mut self, command: &Command) {
+        self.command = (command.linear, command.angular);
     }
 
-    fn shot_ball(&mut self) {
-        self.ball.position = self.position;
-
-        let target = match self.color {
+    /// Aro que ataca el robot: el del equipo contrario.
+    pub fn target_ring(&self) -> Vector3 {
+        match self.color {
             "red_robot" => BLUE_RING_POSITION,
             "blue_robot" => RED_RING_POSITION,
             _ => panic!("Invalid robot color"),
-        };
-
-        let dx = target.x - self.position.x;
-        let dz = target.z - self.position.z;
-        let dy = target.y - self.position.y;
-
-        let horizontal_dist = (dx * dx + dz * dz).sqrt();
-        let dir_angle = dz.atan2(dx);
-
-        let v = Robot::get_rd_shot_velocity();
-
-        let v_squared = v * v;
-        let sqrt_term = v_squared * v_squared
-            - G * (G * horizontal_dist * horizontal_dist + 2.0 * dy * v_squared);
-
-        if sqrt_term < 0.0 {
-            return;
         }
+    }
 
-        let sqrt_root = sqrt_term.sqrt();
-        let elev_angle = ((v_squared + sqrt_root) / (G * horizontal_dist)).atan();
-
-        self.ball.shot_velocity = v;
-        self.ball.elev_angle = elev_angle;
-        self.ball.dir_angle = dir_angle;
-        self.ball.is_shooting = true;
-
-        let vxz = v * elev_angle.cos();
-
-        self.ball.velocity = Vector3 {
-            x: vxz * dir_angle.cos(),
-            y: v * elev_angle.sin(),
-            z: vxz * dir_angle.sin(),
-        };
+    /// Aro que defiende el robot.
+    pub fn own_ring(&self) -> Vector3 {
+        match self.color {
+            "red_robot" => RED_RING_POSITION,
+            "blue_robot" => BLUE_RING_POSITION,
+            _ => panic!("Invalid robot color"),
+        }
     }
 
-    fn get_rd_angle() -> f32 {
-        let angles = [
-            0, 15, 30, 45, 60, 75, 90, 105, 120, 135, 150, 165, 180, 195, 210, 225, 240, 255, 270,
-            285, 300, 315, 330, 345,
-        ];
+    pub fn is_teammate(&self, other: &Robot) -> bool {
+        self.color == other.color
+    }
 
-        let angle = angles[random().gen_range(0..=angles.len() - 1)] as f32;
+    /// Distancia en el plano XZ entre el robot y `position`.
+    pub fn distance_to(&self, position: Vector3) -> f32 {
+        ((position.x - self.position.x).powi(2) + (position.z - self.position.z).powi(2)).sqrt()
+    }
 
-        angle.to_radians()
+    /// Mantiene al robot completo dentro del estadio.
+    pub fn clamp_to_stadium(&mut self) {
+        self.position.x = self.position.x.clamp(
+            -HALF_STADIUM_WIDTH + ROBOT_RADIUS,
+            HALF_STADIUM_WIDTH - ROBOT_RADIUS,
+        );
+        self.position.z = self.position.z.clamp(
+            -HALF_STADIUM_LENGTH + ROBOT_RADIUS,
+            HALF_STADIUM_LENGTH - ROBOT_RADIUS,
+        );
     }
 
-    fn get_rd_velocity() -> f32 {
-        let velocities = [1.0, 2.0, 4.0, 5.0, 6.0];
-        velocities[random().gen_range(0..=velocities.len() - 1)]
+    /// Verdadero si `ball` está al alcance del robot para recogerla.
+    pub fn can_reach(&self, ball: &Ball) -> bool {
+        self.distance_to(ball.position) < ROBOT_RADIUS + BALL_RADIUS
+            && ball.position.y < PICKUP_HEIGHT
     }
 
-    fn get_rd_shot_velocity() -> f32 {
-        let velocities = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0];
+    /// Pasa `ball` a `teammate` con una parábola que llega a la altura a la
+    /// que se lleva la pelota. Queda suelta en el aire, así que la puede
+    /// atrapar cualquiera que la alcance.
+    pub fn pass_ball(&self, ball: &mut Ball, teammate: &Robot) {
+        let origin = ball.position;
+        let distance = teammate.distance_to(origin);
+        let time = (distance / PASS_SPEED).max(MIN_PASS_TIME);
 
-        velocities[random().gen_range(0..=velocities.len() - 1)] as f32
-    }
+        let dy = teammate.position.y + 0.15 - origin.y;
 
-    fn get_delta_time() -> f32 {
-        unsafe { GetFrameTime() }
+        ball.velocity = Vector3 {
+            x: (teammate.position.x - origin.x) / time,
+            y: (dy + 0.5 * G * time * time) / time,
+            z: (teammate.position.z - origin.z) / time,
+        };
+        ball.state = BallState::Loose;
     }
 }
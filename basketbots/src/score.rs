@@ -0,0 +1,35 @@
+/// Canastas de cada equipo. Un equipo anota en el aro del equipo contrario.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Scoreboard {
+    pub red: u32,
+    pub blue: u32,
+}
+
+impl Scoreboard {
+    /// Suma una canasta al equipo que ataca `hoop`.
+    pub fn add_basket(&mut self, hoop: &str) {
+        match hoop {
+            "blue_hoop" => self.red += 1,
+            "red_hoop" => self.blue += 1,
+            _ => panic!("Invalid hoop"),
+        }
+    }
+}
+
+/// Registro de un disparo terminado.
+#[derive(Debug, Clone)]
+pub struct ShotRecord {
+    pub shooter: usize,      // Número del robot que disparó
+    pub color: &'static str, // Equipo del robot
+    pub distance: f32,       // Distancia horizontal al aro al disparar (m)
+    pub velocity: f32,       // Velocidad del disparo (m/s)
+    pub made: bool,          // Si la pelota pasó por el aro
+}
+
+/// Jugadas con la pelota del partido.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlayStats {
+    pub passes: u32,   // Pases lanzados
+    pub steals: u32,   // Robos por contacto
+    pub rebounds: u32, // Pelotas recogidas después de un disparo fallido
+}
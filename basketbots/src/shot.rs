@@ -0,0 +1,384 @@
+use rand::rngs::StdRng;
+
+use super::ball::{Ball, ShotOutcome};
+use super::math::Vector3;
+use crate::utils;
+use crate::{BALL_RADIUS, BLUE_RING_POSITION, FIXED_DT, G, PI, RING_RADIUS};
+
+/// Rapidez máxima (m/s) con la que un robot puede lanzar la pelota.
+pub const MAX_SHOT_SPEED: f32 = 12.0;
+/// Desviación estándar del error de rapidez de un disparo, relativa a la
+/// rapidez pedida.
+pub const SHOT_SPEED_NOISE: f32 = 0.02;
+/// Desviación estándar (rad) del error de elevación y de rumbo de un disparo.
+pub const SHOT_ANGLE_NOISE: f32 = 0.01;
+
+/// Margen (rad) sobre `min_entry_angle` con que planea `ShotPlanner` por
+/// defecto, para que un error pequeño no haga tocar el aro delantero.
+pub const ENTRY_ANGLE_MARGIN: f32 = 0.17;
+
+/// Paso (s) con que se simula la trayectoria para corregir el arrastre.
+const TRAJECTORY_STEP: f32 = 1.0 / 240.0;
+/// Tiempo de vuelo máximo (s) de una trayectoria simulada.
+const MAX_FLIGHT_TIME: f32 = 5.0;
+/// Iteraciones de la bisección que corrige el arrastre.
+const DRAG_SEARCH_STEPS: usize = 30;
+/// Altura (m) desde la que se lanza en `success_rate`, la de una pelota
+/// botando junto al robot.
+pub const RELEASE_HEIGHT: f32 = 0.5;
+
+/// Parámetros con los que se lanza un disparo.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shot {
+    pub speed: f32,     // Rapidez inicial (m/s)
+    pub elevation: f32, // Ángulo de elevación sobre el plano XZ (rad)
+    pub direction: f32, // Rumbo en el plano XZ (rad), medido desde +x hacia +z
+}
+
+/// Rama de la parábola: con una misma rapidez se llega al aro con un tiro
+/// tendido o con uno bombeado.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arc {
+    Low,
+    High,
+}
+
+/// Error de ejecución de un disparo, con distribución normal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShotNoise {
+    pub speed: f32, // Relativo a la rapidez pedida
+    pub angle: f32, // rad, en elevación y en rumbo
+}
+
+impl ShotNoise {
+    pub const NONE: ShotNoise = ShotNoise {
+        speed: 0.0,
+        angle: 0.0,
+    };
+}
+
+impl Default for ShotNoise {
+    fn default() -> Self {
+        Self {
+            speed: SHOT_SPEED_NOISE,
+            angle: SHOT_ANGLE_NOISE,
+        }
+    }
+}
+
+/// Distancia horizontal, altura y rumbo de `target` visto desde `origin`.
+fn geometry(origin: Vector3, target: Vector3) -> (f32, f32, f32) {
+    let dx = target.x - origin.x;
+    let dz = target.z - origin.z;
+
+    (
+        (dx * dx + dz * dz).sqrt().max(1e-3),
+        target.y - origin.y,
+        dz.atan2(dx),
+    )
+}
+
+impl Shot {
+    /// Disparo con rapidez `speed` desde `origin` que, sin arrastre, pasa por
+    /// `target` por la rama `arc`. `None` si con esa rapidez no llega.
+    pub fn with_speed(origin: Vector3, target: Vector3, speed: f32, arc: Arc) -> Option<Shot> {
+        let (distance, height, direction) = geometry(origin, target);
+        let v_squared = speed * speed;
+        let sqrt_term =
+            v_squared * v_squared - G * (G * distance * distance + 2.0 * height * v_squared);
+
+        if sqrt_term < 0.0 {
+            return None;
+        }
+
+        let root = match arc {
+            Arc::Low => -sqrt_term.sqrt(),
+            Arc::High => sqrt_term.sqrt(),
+        };
+
+        Some(Shot {
+            speed,
+            elevation: ((v_squared + root) / (G * distance)).atan(),
+            direction,
+        })
+    }
+
+    /// El disparo más lento que, sin arrastre, pasa por `target`. Ahí las dos
+    /// ramas coinciden.
+    pub fn minimum_speed(origin: Vector3, target: Vector3) -> Shot {
+        let (distance, height, direction) = geometry(origin, target);
+        let hypotenuse = (height * height + distance * distance).sqrt();
+
+        Shot {
+            speed: (G * (height + hypotenuse)).sqrt(),
+            elevation: ((height + hypotenuse) / distance).atan(),
+            direction,
+        }
+    }
+
+    /// Disparo con elevación `elevation` que, sin arrastre, pasa por
+    /// `target`. `None` si con esa elevación la parábola no sube hasta allá.
+    pub fn with_elevation(origin: Vector3, target: Vector3, elevation: f32) -> Option<Shot> {
+        let (distance, height, direction) = geometry(origin, target);
+        let rise = distance * elevation.tan() - height;
+
+        if rise <= 0.0 || elevation >= PI / 2.0 {
+            return None;
+        }
+
+        Some(Shot {
+            speed: distance / elevation.cos() * (G / (2.0 * rise)).sqrt(),
+            elevation,
+            direction,
+        })
+    }
+
+    /// Ángulo (rad) bajo la horizontal con que la pelota llega a la altura de
+    /// `target`, sin arrastre. Negativo si todavía va subiendo.
+    pub fn entry_angle(&self, origin: Vector3, target: Vector3) -> f32 {
+        let (distance, height, _) = geometry(origin, target);
+
+        // En la parábola, tan(entrada) = tan(elevación) - 2 * altura / distancia
+        (self.elevation.tan() - 2.0 * height / distance).atan()
+    }
+
+    /// El mismo disparo con el error de ejecución de `noise`.
+    pub fn perturbed(&self, noise: &ShotNoise, rng: &mut StdRng) -> Shot {
+        Shot {
+            speed: self.speed * (1.0 + noise.speed * utils::gaussian(rng)),
+            elevation: self.elevation + noise.angle * utils::gaussian(rng),
+            direction: self.direction + noise.angle * utils::gaussian(rng),
+        }
+    }
+}
+
+/// Ángulo de entrada mínimo para que la pelota quepa en el aro: visto en la
+/// dirección en que baja, el aro se ve como una abertura de
+/// `2 * RING_RADIUS * sin(entrada)`, que tiene que ser mayor que la pelota.
+pub fn min_entry_angle() -> f32 {
+    (BALL_RADIUS / RING_RADIUS).asin()
+}
+
+/// Elige la rapidez y los ángulos de un disparo al aro. Solo devuelve
+/// disparos que, sin error de ejecución, entran; sin corregir el arrastre
+/// (`drag: false`) los tiros largos se quedan cortos y se descartan.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShotPlanner {
+    pub min_entry_angle: f32, // Ángulo mínimo (rad) con que la pelota baja al aro
+    pub max_speed: f32,       // Rapidez máxima (m/s) que puede lanzar el robot
+    pub drag: bool,           // Corrige la rapidez por el arrastre del aire
+}
+
+impl Default for ShotPlanner {
+    fn default() -> Self {
+        Self {
+            min_entry_angle: min_entry_angle() + ENTRY_ANGLE_MARGIN,
+            max_speed: MAX_SHOT_SPEED,
+            drag: true,
+        }
+    }
+}
+
+impl ShotPlanner {
+    /// El disparo más lento que entra al aro en `target` con al menos
+    /// `min_entry_angle`. Si el de rapidez mínima entra muy tendido, se sube
+    /// la elevación hasta el ángulo de entrada justo. `None` si hace falta
+    /// más de `max_speed`.
+    pub fn plan(&self, origin: Vector3, target: Vector3) -> Option<Shot> {
+        let (distance, height, _) = geometry(origin, target);
+        let lowest = Shot::minimum_speed(origin, target);
+        let steep = (self.min_entry_angle.tan() + 2.0 * height / distance).atan();
+
+        let shot = Shot::with_elevation(origin, target, lowest.elevation.max(steep))?;
+        self.finish(origin, target, shot)
+    }
+
+    /// Disparo con rapidez `speed`: el tendido si entra con al menos
+    /// `min_entry_angle`, si no el bombeado. `None` si ninguno sirve.
+    pub fn plan_with_speed(&self, origin: Vector3, target: Vector3, speed: f32) -> Option<Shot> {
+        let shot = [Arc::Low, Arc::High]
+            .into_iter()
+            .filter_map(|arc| Shot::with_speed(origin, target, speed, arc))
+            .find(|shot| shot.entry_angle(origin, target) >= self.min_entry_angle)?;
+
+        self.finish(origin, target, shot)
+    }
+
+    fn finish(&self, origin: Vector3, target: Vector3, shot: Shot) -> Option<Shot> {
+        let shot = match self.drag {
+            true => compensate_drag(origin, target, shot)?,
+            false => shot,
+        };
+
+        // Descarta los disparos que el aro o el tablero no dejan entrar, como
+        // los que salen desde debajo del aro
+        (shot.speed <= self.max_speed && goes_in(origin, shot)).then_some(shot)
+    }
+}
+
+/// Verdadero si la pelota lanzada con `shot` desde `origin` entra en un aro,
+/// con toda la física de la pelota: arrastre, aro y tablero.
+fn goes_in(origin: Vector3, shot: Shot) -> bool {
+    let mut ball = Ball::new(origin);
+    ball.position = origin;
+    ball.launch(shot);
+
+    (0..(MAX_FLIGHT_TIME / FIXED_DT) as usize)
+        .find_map(|_| ball.update(FIXED_DT))
+        .is_some_and(|outcome| outcome != ShotOutcome::Missed)
+}
+
+/// Con la misma elevación, la rapidez con que la pelota, frenada por el aire,
+/// baja a la altura de `target` a la distancia horizontal correcta.
+fn compensate_drag(origin: Vector3, target: Vector3, shot: Shot) -> Option<Shot> {
+    let (distance, _, _) = geometry(origin, target);
+    let (mut low, mut high) = (shot.speed, 2.0 * shot.speed);
+
+    if reach(
+        origin,
+        target.y,
+        Shot {
+            speed: high,
+            ..shot
+        },
+    ) < distance
+    {
+        return None;
+    }
+
+    for _ in 0..DRAG_SEARCH_STEPS {
+        let middle = (low + high) / 2.0;
+
+        match reach(
+            origin,
+            target.y,
+            Shot {
+                speed: middle,
+                ..shot
+            },
+        ) < distance
+        {
+            true => low = middle,
+            false => high = middle,
+        }
+    }
+
+    Some(Shot {
+        speed: high,
+        ..shot
+    })
+}
+
+/// Distancia horizontal a la que la pelota lanzada con `shot` cruza la
+/// altura `height` bajando; 0 si no llega a subir hasta ahí.
+fn reach(origin: Vector3, height: f32, shot: Shot) -> f32 {
+    let mut ball = Ball::new(origin);
+    ball.position = origin;
+    ball.launch(shot);
+
+    for _ in 0..(MAX_FLIGHT_TIME / TRAJECTORY_STEP) as usize {
+        let previous = ball.position;
+        ball.integrate(TRAJECTORY_STEP);
+
+        if ball.velocity.y < 0.0 && ball.position.y < height {
+            if previous.y < height {
+                return 0.0;
+            }
+
+            let t = (previous.y - height) / (previous.y - ball.position.y);
+            let x = previous.x + (ball.position.x - previous.x) * t - origin.x;
+            let z = previous.z + (ball.position.z - previous.z) * t - origin.z;
+
+            return (x * x + z * z).sqrt();
+        }
+    }
+
+    0.0
+}
+
+/// Fracción de `trials` disparos de `planner`, con el error de `noise`, que
+/// entran en el aro azul desde `distance` metros frente a él.
+pub fn success_rate(
+    planner: &ShotPlanner,
+    noise: &ShotNoise,
+    distance: f32,
+    trials: usize,
+    rng: &mut StdRng,
+) -> f32 {
+    let ring = BLUE_RING_POSITION;
+    let origin = Vector3::new(ring.x, RELEASE_HEIGHT, ring.z - distance);
+
+    let Some(shot) = planner.plan(origin, ring) else {
+        return 0.0;
+    };
+
+    let made = (0..trials)
+        .filter(|_| goes_in(origin, shot.perturbed(noise, rng)))
+        .count();
+
+    made as f32 / trials.max(1) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn origin(distance: f32) -> Vector3 {
+        Vector3::new(0.0, RELEASE_HEIGHT, BLUE_RING_POSITION.z - distance)
+    }
+
+    #[test]
+    fn both_arcs_reach_the_target_and_the_minimum_is_cheapest() {
+        let (from, ring) = (origin(5.0), BLUE_RING_POSITION);
+        let lowest = Shot::minimum_speed(from, ring);
+        let planner = ShotPlanner::default();
+
+        assert!(Shot::with_speed(from, ring, lowest.speed - 0.01, Arc::High).is_none());
+
+        let low = Shot::with_speed(from, ring, lowest.speed + 1.0, Arc::Low).unwrap();
+        let high = Shot::with_speed(from, ring, lowest.speed + 1.0, Arc::High).unwrap();
+        assert!(low.elevation < lowest.elevation && lowest.elevation < high.elevation);
+        assert!(low.entry_angle(from, ring) < high.entry_angle(from, ring));
+
+        // El plan sube la elevación lo justo para entrar con el ángulo mínimo
+        let plan = planner.plan(from, ring).unwrap();
+        assert!(plan.speed >= lowest.speed);
+        assert!(plan.entry_angle(from, ring) >= planner.min_entry_angle - 1e-4);
+
+        let with_speed = planner
+            .plan_with_speed(from, ring, lowest.speed + 1.0)
+            .unwrap();
+        assert!(with_speed.entry_angle(from, ring) >= planner.min_entry_angle);
+    }
+
+    #[test]
+    fn planned_shots_go_in_without_noise() {
+        let mut rng = utils::rng(Some(0));
+
+        for distance in [1.0, 3.0, 5.0, 7.0] {
+            let rate = success_rate(
+                &ShotPlanner::default(),
+                &ShotNoise::NONE,
+                distance,
+                1,
+                &mut rng,
+            );
+            assert_eq!(rate, 1.0, "desde {distance} m");
+        }
+    }
+
+    #[test]
+    fn noise_makes_long_shots_harder() {
+        let mut rng = utils::rng(Some(0));
+        let planner = ShotPlanner::default();
+        let noise = ShotNoise {
+            speed: 0.05,
+            angle: 0.03,
+        };
+
+        let near = success_rate(&planner, &noise, 2.0, 200, &mut rng);
+        let far = success_rate(&planner, &noise, 8.0, 200, &mut rng);
+
+        assert!(near > far, "cerca {near}, lejos {far}");
+    }
+}
@@ -1,130 +1,554 @@
-use raylib::prelude::*;
-use std::collections::HashMap;
+use rand::{Rng, rngs::StdRng};
 
+use super::ball::{Ball, BallState, ShotOutcome};
+use super::behavior::Behavior;
+use super::collision::{self, HOOP_POLES, SpatialGrid};
+use super::controller::{Action, Command, Observation, Possession, RobotController};
+use super::kinematics::DriveLimits;
+use super::math::Vector3;
 use super::robot::Robot;
+use super::score::{PlayStats, Scoreboard, ShotRecord};
+use super::shot::{Shot, ShotNoise};
 
 use crate::utils;
-use crate::{HALF_STADIUM_LENGTH, MAX_ROBOTS, STADIUM_LENGTH, STADIUM_WIDTH};
+use crate::{FIXED_DT, MAX_ROBOTS, ROBOT_RADIUS};
 
-const LINE_THICKNESS: f32 = 0.1;
-const FLOOR_THICKNESS: f32 = 0.1;
-const LINE_HEIGHT: f32 = 0.01;
+/// Probabilidad por paso de que un rival en contacto robe la pelota.
+pub const STEAL_PROBABILITY: f32 = 0.05;
+/// Distancia extra (m) sobre el contacto a la que un rival puede robar.
+pub const STEAL_REACH: f32 = 0.05;
+/// Tiempo (s) en que un robot no puede volver a tomar la pelota que soltó o
+/// le robaron.
+pub const RELEASE_COOLDOWN: f32 = 0.5;
 
+/// Estado completo de un partido: robots, la pelota, marcador y registro de
+/// disparos. No depende de una ventana, así que se puede crear y avanzar sin
+/// Raylib (ver `render::Renderer` para dibujarlo).
 #[derive(Debug)]
 pub struct Stadium {
     robots: Vec<Robot>,
-    models: HashMap<&'static str, Model>,
+    controllers: Vec<Box<dyn RobotController>>, // Uno por robot, en el mismo orden
+    ball: Ball,
+    possession: Option<usize>,
+    shooter: Option<usize>,
+    released: Option<(usize, f32)>,
+    rebound: bool,
+    score: Scoreboard,
+    stats: PlayStats,
+    shots: Vec<ShotRecord>,
+    grid: SpatialGrid,
+    shot_noise: ShotNoise,
+    contacts: usize,
+    time: f32,
+    rng: StdRng,
 }
 
 impl Stadium {
-    pub fn new(rlib: &mut RaylibHandle, thread: &RaylibThread) -> Self {
+    pub fn new() -> Self {
+        Stadium::with_robots(MAX_ROBOTS, None)
+    }
+
+    /// Estadio reproducible: la misma semilla da siempre el mismo partido.
+    pub fn with_seed(seed: u64) -> Self {
+        Stadium::with_robots(MAX_ROBOTS, Some(seed))
+    }
+
+    /// Estadio con `n_robots` robots, alternando equipos.
+    pub fn with_robots(n_robots: usize, seed: Option<u64>) -> Self {
+        let mut rng = utils::rng(seed);
         let mut robots = Vec::new();
-        let models = utils::load_models(rlib, thread);
 
-        for i in 1..=MAX_ROBOTS {
-            robots.push(Robot::new(i));
+        for i in 1..=n_robots {
+            robots.push(Robot::new(i, &mut rng));
         }
 
-        Self { models, robots }
+        Self {
+            controllers: robots
+                .iter()
+                .map(|_| Behavior::default().controller())
+                .collect(),
+            robots,
+            ball: Ball::new(Vector3::zero()),
+            possession: None,
+            shooter: None,
+            released: None,
+            rebound: false,
+            score: Scoreboard::default(),
+            stats: PlayStats::default(),
+            shots: Vec::new(),
+            grid: SpatialGrid::default(),
+            shot_noise: ShotNoise::default(),
+            contacts: 0,
+            time: 0.0,
+            rng,
+        }
     }
 
+    /// Avanza la simulación un paso de `dt` segundos.
+    pub fn update(&mut self, dt: f32) {
+        self.time += dt;
 
-    pub fn draw(&mut self, drawer: &mut RaylibMode3D<'_, RaylibDrawHandle<'_>>) {
-        self.draw_floor(drawer);
-        self.draw_hoops(drawer);
+        let observations: Vec<_> = (0..self.robots.len())
+            .map(|i| self.observe(i, dt))
+            .collect();
 
-        for robot in &mut self.robots {
-            robot.draw(drawer);
+        let commands: Vec<Command> = self
+            .controllers
+            .iter_mut()
+            .zip(&observations)
+            .map(|(controller, observation)| controller.command(observation, &mut self.rng))
+            .collect();
+
+        // Un disparo sale antes de que los robots se muevan, desde donde el
+        // controlador vio la pelota al planearlo
+        if let Some(carrier) = self.possession
+            && let Action::Shoot(shot) = commands[carrier].action
+        {
+            self.shoot(carrier, shot);
+        }
+
+        for (robot, command) in self.robots.iter_mut().zip(&commands) {
+            robot.apply(command);
+            robot.update(dt, &mut self.rng);
+        }
+
+        self.contacts +=
+            collision::resolve_collisions(&mut self.robots, &mut self.grid, &HOOP_POLES);
+
+        match self.possession {
+            Some(carrier) => self.play(carrier, commands[carrier].action),
+            None => self.update_loose_ball(dt),
+        }
+    }
+
+    /// Lo que ve el robot `me`: su pose, la pelota, los demás robots y los aros.
+    fn observe(&self, me: usize, dt: f32) -> Observation {
+        let robot = &self.robots[me];
+        let (mut teammates, mut opponents) = (Vec::new(), Vec::new());
+
+        for (i, other) in self.robots.iter().enumerate() {
+            if i == me {
+                continue;
+            }
+
+            match other.is_teammate(robot) {
+                true => teammates.push(other.position),
+                false => opponents.push(other.position),
+            }
+        }
+
+        let possession = match self.possession {
+            Some(carrier) if carrier == me => Possession::Mine,
+            Some(carrier) if self.robots[carrier].is_teammate(robot) => Possession::Teammate,
+            Some(_) => Possession::Opponent,
+            None => Possession::Nobody,
+        };
+
+        Observation {
+            position: robot.position,
+            heading: robot.angle,
+            velocity: robot.velocity,
+            angular_velocity: robot.angular_velocity,
+            odometry: robot.odometry,
+            ball: self.ball.position,
+            ball_velocity: self.ball.velocity,
+            ball_state: self.ball.state,
+            possession,
+            teammates,
+            opponents,
+            target_ring: robot.target_ring(),
+            own_ring: robot.own_ring(),
+            dt,
+        }
+    }
+
+    /// Quien lleva la pelota la bota, y puede perderla por un robo o, según
+    /// la `action` de su controlador, pasarla. Los disparos ya salieron en
+    /// `update`.
+    fn play(&mut self, carrier: usize, action: Action) {
+        self.carry(carrier);
+
+        if let Some(thief) = self.find_thief(carrier) {
+            self.stats.steals += 1;
+            self.released = Some((carrier, self.time));
+            self.possession = Some(thief);
+            self.carry(thief);
+            return;
+        }
+
+        match action {
+            Action::Keep | Action::Shoot(_) => {}
+            Action::Pass(index) => {
+                // `index` cuenta a los compañeros en el orden de `observe`
+                let teammate = (0..self.robots.len())
+                    .filter(|&i| i != carrier && self.robots[i].is_teammate(&self.robots[carrier]))
+                    .nth(index);
+
+                if let Some(teammate) = teammate {
+                    self.robots[carrier].pass_ball(&mut self.ball, &self.robots[teammate]);
+                    self.release(carrier);
+                    self.stats.passes += 1;
+                }
+            }
+        }
+    }
+
+    /// Rival en contacto con `carrier` que le roba la pelota, si hay uno.
+    fn find_thief(&mut self, carrier: usize) -> Option<usize> {
+        let position = self.robots[carrier].position;
+
+        for i in 0..self.robots.len() {
+            let robot = &self.robots[i];
+
+            if robot.is_teammate(&self.robots[carrier])
+                || robot.distance_to(position) > 2.0 * ROBOT_RADIUS + STEAL_REACH
+                || !self.can_take(i)
+            {
+                continue;
+            }
+
+            if self.rng.r#gen::<f32>() < STEAL_PROBABILITY {
+                return Some(i);
+            }
+        }
+
+        None
+    }
+
+    /// La pelota vuela o rueda; al terminar un disparo se anota, y el robot
+    /// más cercano que la alcance la recoge.
+    fn update_loose_ball(&mut self, dt: f32) {
+        if let Some(outcome) = self.ball.update(dt) {
+            let shooter = self.shooter.take().expect("Shot without shooter");
+
+            if let ShotOutcome::Made(hoop) = outcome {
+                self.score.add_basket(hoop);
+            }
+
+            self.rebound = outcome == ShotOutcome::Missed;
+            self.shots.push(ShotRecord {
+                shooter: shooter + 1,
+                color: self.robots[shooter].color,
+                distance: self.ball.shot_distance,
+                velocity: self.ball.shot_velocity,
+                made: outcome != ShotOutcome::Missed,
+            });
+        }
+
+        // Un disparo en el aire no se puede tomar hasta que tenga resultado
+        if self.ball.state == BallState::Flying {
+            return;
+        }
+
+        let closest = (0..self.robots.len())
+            .filter(|&i| self.can_take(i) && self.robots[i].can_reach(&self.ball))
+            .min_by(|&a, &b| {
+                let da = self.robots[a].distance_to(self.ball.position);
+                let db = self.robots[b].distance_to(self.ball.position);
+                da.total_cmp(&db)
+            });
+
+        if let Some(robot) = closest {
+            if self.rebound {
+                self.stats.rebounds += 1;
+                self.rebound = false;
+            }
+
+            self.possession = Some(robot);
+            self.carry(robot);
+        }
+    }
+
+    /// `carrier` lanza la pelota desde donde la lleva, con el error de
+    /// ejecución de `shot_noise`.
+    fn shoot(&mut self, carrier: usize, shot: Shot) {
+        let robot = &self.robots[carrier];
+
+        self.ball
+            .launch(shot.perturbed(&self.shot_noise, &mut self.rng));
+        self.ball.shot_distance = robot.distance_to(robot.target_ring());
+        self.release(carrier);
+        self.shooter = Some(carrier);
+    }
+
+    /// La pelota pasa a botar delante de `robot`.
+    fn carry(&mut self, robot: usize) {
+        let robot = &self.robots[robot];
+        self.ball.carry(robot.position, robot.angle, self.time);
+    }
+
+    fn release(&mut self, carrier: usize) {
+        self.possession = None;
+        self.released = Some((carrier, self.time));
+    }
+
+    /// Falso mientras `robot` está en el tiempo de espera después de soltar
+    /// la pelota.
+    fn can_take(&self, robot: usize) -> bool {
+        match self.released {
+            Some((released, time)) => released != robot || self.time - time >= RELEASE_COOLDOWN,
+            None => true,
+        }
+    }
+
+    /// Avanza `duration` segundos en pasos de `FIXED_DT` y devuelve el marcador.
+    pub fn run(&mut self, duration: f32) -> Scoreboard {
+        let steps = (duration / FIXED_DT).round() as usize;
+
+        for _ in 0..steps {
+            self.update(FIXED_DT);
+        }
+
+        self.score
+    }
+
+    /// Cambia el controlador de todos los robots de `color`: cada uno recibe
+    /// el que devuelve `make`.
+    pub fn set_controller(&mut self, color: &str, make: impl Fn() -> Box<dyn RobotController>) {
+        for (robot, controller) in self.robots.iter().zip(self.controllers.iter_mut()) {
+            if robot.color == color {
+                *controller = make();
+            }
+        }
+    }
+
+    /// Cambia el controlador de todos los robots de `color` por uno de los
+    /// incluidos.
+    pub fn set_behavior(&mut self, color: &str, behavior: Behavior) {
+        self.set_controller(color, || behavior.controller());
+    }
+
+    /// Cambia los límites de las ruedas de todos los robots de `color`.
+    pub fn set_drive_limits(&mut self, color: &str, limits: DriveLimits) {
+        for robot in self.robots.iter_mut().filter(|robot| robot.color == color) {
+            robot.limits = limits;
         }
     }
 
-    pub fn update(&mut self) {
+    /// Cambia el ruido de la odometría de todos los robots (ver `ODOMETRY_NOISE`).
+    pub fn set_odometry_noise(&mut self, noise: f32) {
         for robot in &mut self.robots {
-            robot.update();
+            robot.odometry.noise = noise;
         }
     }
 
-    fn draw_floor(&self, drawer: &mut RaylibMode3D<'_, RaylibDrawHandle<'_>>) {
-        // Draw the stadium floor
+    /// Cambia el error de ejecución de los disparos de todos los robots.
+    pub fn set_shot_noise(&mut self, noise: ShotNoise) {
+        self.shot_noise = noise;
+    }
 
-        drawer.draw_cube(
-            Vector3::new(0.0, 0.0, 0.0),
-            STADIUM_WIDTH,
-            FLOOR_THICKNESS,
-            STADIUM_LENGTH,
-            Color::DARKSLATEGRAY,
-        );
+    /// Error medio (m) entre la posición que estima la odometría y la real.
+    pub fn odometry_error(&self) -> f32 {
+        let total: f32 = self
+            .robots
+            .iter()
+            .map(|robot| robot.odometry.error(robot.position))
+            .sum();
 
-        // Draw stadium floor outside white lines
+        total / self.robots.len().max(1) as f32
+    }
 
-        drawer.draw_cube(
-            Vector3::new(0.0, -0.005, 0.0),
-            STADIUM_WIDTH + 0.2,
-            FLOOR_THICKNESS,
-            STADIUM_LENGTH + 0.2,
-            Color::WHITESMOKE,
-        );
+    pub fn robots(&self) -> &[Robot] {
+        &self.robots
+    }
 
-        // Draw the stadium black platform
+    pub fn ball(&self) -> &Ball {
+        &self.ball
+    }
 
-        drawer.draw_cube(
-            Vector3::new(0.0, -10.0, 0.0),
-            STADIUM_WIDTH,
-            20.0,
-            STADIUM_LENGTH * 2.0,
-            Color::BLACK,
-        );
+    /// Robot que lleva la pelota, si alguno.
+    pub fn possession(&self) -> Option<usize> {
+        self.possession
+    }
 
-        // Draw the center line
+    pub fn score(&self) -> Scoreboard {
+        self.score
+    }
 
-        drawer.draw_cube(
-            Vector3::new(0.0, 0.08, 0.0),
-            STADIUM_WIDTH + 0.1,
-            LINE_HEIGHT,
-            LINE_THICKNESS,
-            Color::WHITESMOKE,
-        );
+    pub fn stats(&self) -> PlayStats {
+        self.stats
+    }
 
-        // Draw the center circle perimeter
+    pub fn shots(&self) -> &[ShotRecord] {
+        &self.shots
+    }
 
-        drawer.draw_cylinder(
-            Vector3::new(0.0, 0.05, 0.0),
-            2.0,         // radio arriba
-            2.0,         // radio abajo
-            LINE_HEIGHT, // altura
-            64,          // cantidad de lados
-            Color::WHITESMOKE,
-        );
+    /// Contactos entre robots desde el inicio del partido.
+    pub fn contacts(&self) -> usize {
+        self.contacts
+    }
 
-        // Draw the center circle content
+    /// Segundos simulados desde el inicio del partido.
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+}
 
-        drawer.draw_cylinder(
-            Vector3::new(0.0, 0.05, 0.0),
-            1.9,                // radio arriba
-            1.9,                // radio abajo
-            LINE_HEIGHT + 0.01, // altura
-            64,                 // cantidad de lados
-            Color::DARKSLATEGRAY,
-        );
+impl Default for Stadium {
+    fn default() -> Self {
+        Stadium::new()
     }
+}
 
-    fn draw_hoops(&self, drawer: &mut RaylibMode3D<'_, RaylibDrawHandle<'_>>) {
-        drawer.draw_model_ex(
-            &self.models["blue_hoop"],
-            Vector3::new(-0.35, -4.0, HALF_STADIUM_LENGTH + 1.0),
-            Vector3::new(0.0, 1.0, 1.0),
-            180.0,
-            Vector3::new(0.018, 0.018, 0.018),
-            Color::WHITESMOKE,
-        );
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HALF_STADIUM_LENGTH, HALF_STADIUM_WIDTH, ROBOT_RADIUS};
+
+    #[test]
+    fn same_seed_plays_the_same_match() {
+        let mut first = Stadium::with_seed(7);
+        let mut second = Stadium::with_seed(7);
+
+        first.run(30.0);
+        second.run(30.0);
+
+        assert_eq!(first.shots().len(), second.shots().len());
+        assert_eq!(first.score().red, second.score().red);
+        assert_eq!(first.score().blue, second.score().blue);
+
+        for (a, b) in first.robots().iter().zip(second.robots()) {
+            assert_eq!(a.position, b.position);
+        }
+    }
+
+    #[test]
+    fn score_counts_every_made_shot() {
+        for seed in 0..20 {
+            let mut stadium = Stadium::with_seed(seed);
+            let score = stadium.run(60.0);
+            let made = stadium.shots().iter().filter(|shot| shot.made).count();
+
+            assert_eq!((score.red + score.blue) as usize, made);
+        }
+    }
+
+    #[test]
+    fn ball_changes_hands() {
+        let mut stadium = Stadium::with_seed(11);
+        stadium.run(300.0);
+
+        let stats = stadium.stats();
+        assert!(!stadium.shots().is_empty());
+        assert!(stats.passes > 0);
+        assert!(stats.steals > 0);
+        assert!(stats.rebounds > 0);
+    }
+
+    #[test]
+    fn carrier_keeps_the_ball_at_its_side() {
+        let mut stadium = Stadium::with_seed(5);
+
+        for _ in 0..3600 {
+            stadium.update(FIXED_DT);
+
+            if let Some(carrier) = stadium.possession() {
+                let robot = &stadium.robots()[carrier];
+                let ball = stadium.ball().position;
+                assert_eq!(stadium.ball().state, BallState::Held);
+
+                // A `ROBOT_RADIUS` del centro, en la dirección del rumbo
+                let (dx, dz) = (ball.x - robot.position.x, ball.z - robot.position.z);
+                assert!((dx - ROBOT_RADIUS * robot.angle.sin()).abs() < 1e-4);
+                assert!((dz - ROBOT_RADIUS * robot.angle.cos()).abs() < 1e-4);
+            } else {
+                assert_ne!(stadium.ball().state, BallState::Held);
+            }
+        }
+    }
+
+    #[test]
+    fn shots_leave_from_the_carried_ball() {
+        let mut stadium = Stadium::with_seed(3);
+        let mut shots = 0;
+
+        for _ in 0..3600 {
+            let held = stadium.possession().is_some();
+            let before = stadium.ball().position;
+            stadium.update(FIXED_DT);
+
+            // En el paso del disparo la pelota sigue su vuelo desde donde estaba
+            let ball = stadium.ball();
+            if held && ball.state == BallState::Flying {
+                let expected = before + ball.velocity * FIXED_DT;
+                assert!(ball.position.distance_to(expected) < 0.01);
+                shots += 1;
+            }
+        }
+
+        assert!(shots > 0);
+    }
+
+    #[test]
+    fn state_machine_beats_random_walk() {
+        let (mut fsm, mut random) = (0, 0);
+
+        for seed in 0..3 {
+            let mut stadium = Stadium::with_seed(seed);
+            stadium.set_behavior("red_robot", Behavior::StateMachine);
+            stadium.set_behavior("blue_robot", Behavior::RandomWalk);
+
+            let score = stadium.run(60.0);
+            fsm += score.red;
+            random += score.blue;
+        }
 
-        drawer.draw_model_ex(
-            &self.models["red_hoop"],
-            Vector3::new(0.35, -4.0, -HALF_STADIUM_LENGTH - 1.0),
-            Vector3::new(1.0, 0.0, 0.0),
-            270.0,
-            Vector3::new(0.018, 0.018, 0.018),
-            Color::WHITESMOKE,
+        assert!(
+            fsm > random,
+            "máquina de estados {fsm}, caminata al azar {random}"
         );
     }
+
+    /// Gira en el lugar y nunca suelta la pelota.
+    #[derive(Debug)]
+    struct Spin;
+
+    impl RobotController for Spin {
+        fn command(&mut self, _: &Observation, _: &mut StdRng) -> Command {
+            Command {
+                linear: 0.0,
+                angular: 1.0,
+                action: Action::Keep,
+            }
+        }
+    }
+
+    #[test]
+    fn each_team_follows_its_own_controller() {
+        let mut stadium = Stadium::with_seed(2);
+        stadium.set_controller("red_robot", || Box::new(Spin));
+
+        let before: Vec<Robot> = stadium.robots().to_vec();
+        stadium.run(1.0);
+
+        for (start, end) in before.iter().zip(stadium.robots()) {
+            let turned = end.angle - start.angle;
+
+            match end.color {
+                // Las ruedas tardan un poco en acelerar hasta el giro pedido
+                "red_robot" => assert!((0.95..=1.0).contains(&turned), "giró {turned}"),
+                _ => assert!(start.position != end.position),
+            }
+        }
+
+        let red_shots = stadium
+            .shots()
+            .iter()
+            .filter(|shot| shot.color == "red_robot");
+        assert_eq!(red_shots.count(), 0);
+    }
+
+    #[test]
+    fn robots_stay_inside_the_stadium() {
+        let mut stadium = Stadium::with_seed(3);
+
+        for _ in 0..3600 {
+            stadium.update(FIXED_DT);
+
+            for robot in stadium.robots() {
+                assert!(robot.position.x.abs() <= HALF_STADIUM_WIDTH - ROBOT_RADIUS);
+                assert!(robot.position.z.abs() <= HALF_STADIUM_LENGTH - ROBOT_RADIUS);
+            }
+        }
+    }
 }
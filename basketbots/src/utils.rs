@@ -1,30 +1,18 @@
-use rand::{SeedableRng, rngs::StdRng};
-use raylib::prelude::*;
+use rand::{Rng, SeedableRng, rngs::StdRng};
 
-use std::collections::HashMap;
-
-pub fn random() -> StdRng {
-    StdRng::from_entropy()
-}
-
-pub fn load_models(rlib: &mut RaylibHandle, thread: &RaylibThread) -> HashMap<&'static str, Model> {
-    let mut models = HashMap::new();
-    let model_srcs = HashMap::from([
-        ("red_hoop", "./assets/hoop/red.obj"),
-        ("blue_hoop", "./assets/hoop/blue.obj"),
-        ("ball", "./assets/ball.glb"),
-    ]);
-
-    for (key, value) in model_srcs {
-        models.insert(key, rlib.load_model(thread, value).unwrap());
+/// Generador con semilla fija si se entrega una, o desde la entropía del
+/// sistema si no.
+pub fn rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
     }
+}
 
-    let mut mut_color = |k: &str, c: Color| {
-        models.get_mut(k).unwrap().materials_mut()[0].maps_mut()[0].color = c.into();
-    };
-
-    mut_color("red_hoop", Color::RED);
-    mut_color("blue_hoop", Color::BLUE);
+/// Muestra de una normal estándar (Box-Muller).
+pub fn gaussian(rng: &mut StdRng) -> f32 {
+    let u1: f32 = rng.gen_range(f32::EPSILON..1.0);
+    let u2: f32 = rng.r#gen();
 
-    models
+    (-2.0 * u1.ln()).sqrt() * (2.0 * crate::PI * u2).cos()
 }
@@ -0,0 +1,99 @@
+use std::{io, path::Path, time::Instant};
+
+use utils::{
+    Env,
+    benchmark::{BenchmarkResult, map_files},
+    mapgen::{GeneratedMap, KeyFormat, parse_keys},
+};
+
+use crate::{SUCCESS_PROBABILITIES, env::MdpEnv, map::Map, mdp::Mdp};
+
+/// Factor de descuento con el que se resuelve cada mapa del benchmark.
+pub const BENCHMARK_DISCOUNT_FACTOR: f32 = 0.94;
+/// Límite de pasos de cada episodio de evaluación de la política.
+pub const BENCHMARK_MAX_STEPS: usize = 200;
+
+/// Resuelve con Value Iteration cada mapa `.map` de `dir` con el modelo de
+/// transición de cada probabilidad de éxito y evalúa la política con esa misma
+/// probabilidad. Los mapas que no son de `N_ROWS × N_COLS` se saltan con un
+/// aviso.
+pub fn run(dir: &Path) -> io::Result<Vec<BenchmarkResult>> {
+    let mut results = Vec::new();
+
+    for path in map_files(dir)? {
+        let name = path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let keys = parse_keys(&std::fs::read_to_string(&path)?);
+
+        let map = match GeneratedMap::from_keys(&keys)
+            .and_then(|map| Map::from_keys(&map.to_keys(KeyFormat::MarkovDp)))
+        {
+            Ok(map) => map,
+            Err(error) => {
+                println!("⚠️  Saltando {}: {}", path.display(), error);
+                continue;
+            }
+        };
+
+        for (success_prob, &prob) in SUCCESS_PROBABILITIES.iter().enumerate() {
+            let start_time = Instant::now();
+            let transition_matrix = Mdp::build_transition_matrix_with(
+                &map,
+                [prob, (1.0 - prob) / 2.0, (1.0 - prob) / 2.0],
+            );
+            let mut mdp = Mdp::new_with_transition_matrix(map.clone(), transition_matrix);
+            let sweeps = mdp.value_iteration(BENCHMARK_DISCOUNT_FACTOR);
+            let solve_time = start_time.elapsed();
+
+            let (success_rate, final_return) = evaluate(&map, &mdp.get_max_policy(), success_prob);
+
+            results.push(BenchmarkResult {
+                map: name.clone(),
+                solver: "Value Iteration".to_string(),
+                success_prob: prob,
+                solve_time,
+                sweeps: Some(sweeps),
+                episodes_to_target: None,
+                success_rate,
+                final_return,
+            });
+        }
+    }
+
+    Ok(results)
+}
+
+/// Sigue `policy` una vez desde cada celda inicial posible y devuelve la tasa
+/// de éxito y la recompensa total media.
+fn evaluate(map: &Map, policy: &[usize], success_prob: usize) -> (f64, f64) {
+    let starts = map.get_start_positions();
+    let mut env = MdpEnv::new(map.clone(), success_prob, BENCHMARK_MAX_STEPS);
+    env.reset(Some(0));
+
+    let mut successes = 0;
+    let mut total_reward = 0.0_f64;
+
+    for &start in &starts {
+        let mut obs = env.reset_to(start);
+
+        loop {
+            let (next_obs, reward, terminated, truncated, _) = env.step(policy[obs]);
+            total_reward += reward as f64;
+            obs = next_obs;
+
+            if terminated {
+                successes += 1;
+            }
+
+            if terminated || truncated {
+                break;
+            }
+        }
+    }
+
+    let n = starts.len().max(1) as f64;
+    (successes as f64 / n, total_reward / n)
+}
@@ -1,8 +1,19 @@
 use std::{thread, time::Duration};
 
 use raylib::prelude::*;
+use utils::Env;
 
-use crate::{DISCOUNT_FACTORS, map::Map, mdp::Mdp, robot::Robot};
+use crate::{
+    DISCOUNT_FACTORS,
+    dynamic::DynamicWorld,
+    env::MdpEnv,
+    map::Map,
+    mdp::Mdp,
+    pomdp::{Localization, draw_belief, qmdp_action, read_sensors},
+    robot::Robot,
+};
+
+const SIMULATION_STEPS: usize = 1000;
 
 pub struct Core {
     pub map: Map,
@@ -10,18 +21,21 @@ pub struct Core {
     pub robot: Robot,
     pub simulation_steps: u32,
     pub rewards: Vec<f32>,
+    /// Con `Some` el robot no conoce su celda: decide con QMDP sobre la creencia.
+    pub localization: Option<Localization>,
+    /// Con `Some` el mapa cambia en cada paso (obstáculos, puertas y peligros).
+    pub dynamic: Option<DynamicWorld>,
 }
 
 impl Core {
-    pub fn new(discount_factor_id: usize, success_prob: usize) -> Self {
-        let map = Map::new();
+    pub fn new(map: Map, discount_factor_id: usize, success_prob: usize) -> Self {
         let mut mdp = Mdp::new(map.clone());
 
         let discount_factors = DISCOUNT_FACTORS.to_vec();
 
         mdp.value_iteration(discount_factors[discount_factor_id]);
 
-        let initial_position = map.get_random_valid_position();
+        let initial_position = map.get_random_valid_position(&mut rand::rng());
         let robot = Robot::new(initial_position, success_prob);
 
         Self {
@@ -30,17 +44,22 @@ impl Core {
             robot,
             simulation_steps: 0,
             rewards: vec![],
+            localization: None,
+            dynamic: None,
         }
     }
 
     pub fn reset_robot(&mut self) {
-        let new_position = self.map.get_random_valid_position();
+        let new_position = self.map.get_random_valid_position(&mut rand::rng());
         self.robot.set_position(new_position);
+
+        if let Some(localization) = self.localization.as_mut() {
+            localization.reset(&self.map);
+        }
     }
 
-    pub fn run_simulation() -> Vec<Vec<Vec<f32>>> {
-        let map = Map::new();
-        let transition_matrix = Mdp::build_transition_matrix_static(&map);
+    pub fn run_simulation(map: &Map) -> Vec<Vec<Vec<f32>>> {
+        let transition_matrix = Mdp::build_transition_matrix_static(map);
 
         let mut results = vec![vec![vec![]; 4]; 4];
         let discount_factors = DISCOUNT_FACTORS.to_vec();
@@ -52,29 +71,27 @@ impl Core {
             mdps.push(mdp);
         }
 
-        for success_prob in 0..4 {
-            for discount_factor in 0..4 {
-                let initial_position = map.get_random_valid_position();
-                let mut robot = Robot::new(initial_position, success_prob);
+        for (success_prob, row) in results.iter_mut().enumerate() {
+            for (mdp, result) in mdps.iter_mut().zip(row.iter_mut()) {
+                let policy = mdp.get_max_policy();
+                let mut env = MdpEnv::new(map.clone(), success_prob, SIMULATION_STEPS);
 
-                let mut simulation_steps = 0;
+                let mut obs = env.reset(None);
                 let mut rewards = vec![];
 
-                while simulation_steps < 1000 {
-                    robot.update(&mdps[discount_factor].get_max_policy(), &map);
-
-                    simulation_steps += 1;
-                    let robot_pos = robot.get_matricial_position();
+                // Al llegar a la meta el robot reaparece en otra celda al azar
+                while rewards.len() < SIMULATION_STEPS {
+                    let (next_obs, reward, terminated, truncated, _) = env.step(policy[obs]);
+                    rewards.push(reward);
 
-                    rewards.push(map.states[robot_pos[0]][robot_pos[1]].reward);
-
-                    if robot.get_position() == map.get_goal_position() {
-                        let new_position = map.get_random_valid_position();
-                        robot.set_position(new_position);
-                    }
+                    obs = if terminated || truncated {
+                        env.reset(None)
+                    } else {
+                        next_obs
+                    };
                 }
 
-                results[success_prob][discount_factor] = rewards;
+                *result = rewards;
             }
         }
 
@@ -87,11 +104,32 @@ impl Core {
     }
 
     pub fn simulate(&mut self, drawer: Option<&mut RaylibMode2D<'_, RaylibDrawHandle<'_>>>) {
-        self.robot.update(&self.mdp.get_max_policy(), &self.map);
+        if let Some(world) = self.dynamic.as_mut() {
+            let robot_cell = self.robot.get_matricial_position();
+
+            if world.tick(&mut self.map, &mut self.mdp, robot_cell)
+                && let Some(localization) = self.localization.as_mut()
+            {
+                localization.set_map(&self.map);
+            }
+        }
 
-        if drawer.is_some() {
+        match self.localization.as_mut() {
+            Some(localization) => {
+                let rng = &mut rand::rng();
+                let action = qmdp_action(&localization.belief, &self.mdp.q_values);
+                self.robot.apply_action(action, &self.map, rng);
+
+                let [row, col] = self.robot.get_matricial_position();
+                let reading = read_sensors(&localization.sensor, &self.map, row, col, rng);
+                localization.update(&self.map, action, &reading);
+            }
+            None => self.robot.update(&self.mdp.get_max_policy(), &self.map),
+        }
+
+        if let Some(drawer) = drawer {
             thread::sleep(Duration::from_millis(500));
-            self.draw(drawer.unwrap());
+            self.draw(drawer);
         }
 
         self.simulation_steps += 1;
@@ -104,6 +142,11 @@ impl Core {
 
     pub fn draw(&self, drawer: &mut RaylibMode2D<'_, RaylibDrawHandle<'_>>) {
         self.map.draw(drawer);
+
+        if let Some(localization) = &self.localization {
+            draw_belief(&localization.belief, drawer);
+        }
+
         self.robot.draw(drawer);
     }
 }
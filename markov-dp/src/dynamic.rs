@@ -0,0 +1,213 @@
+use utils::{
+    Env,
+    dynamic::{DynamicElement, DynamicMap},
+};
+
+use crate::{env::MdpEnv, map::Map, mdp::Mdp};
+
+/// Pasos de la comparación entre replanificar y mantener la política inicial.
+pub const DYNAMIC_STEPS: usize = 2000;
+
+/// Escenario de bodega sobre el mapa original: una grúa horquilla recorre la
+/// fila 1, dos puertas rodean la meta y una celda junto a la meta se vuelve
+/// peligrosa por momentos.
+pub fn warehouse() -> DynamicMap {
+    DynamicMap {
+        elements: vec![
+            DynamicElement::Patrol {
+                path: (1..=6).map(|col| (1, col)).collect(),
+                period: 3,
+            },
+            DynamicElement::Door {
+                cell: (3, 3),
+                open: 12,
+                closed: 12,
+                offset: 0,
+            },
+            DynamicElement::Door {
+                cell: (4, 4),
+                open: 10,
+                closed: 15,
+                offset: 6,
+            },
+            DynamicElement::Hazard {
+                cell: (3, 5),
+                rewards: vec![-0.1, -2.0],
+                period: 20,
+            },
+        ],
+    }
+}
+
+/// Mapa que cambia con el tiempo. `base` es el mapa sin elementos dinámicos;
+/// en cada paso se le aplica `schedule` y, si `replan` está activo y algo
+/// cambió, se vuelve a resolver el MDP.
+#[derive(Debug, Clone)]
+pub struct DynamicWorld {
+    pub base: Map,
+    pub schedule: DynamicMap,
+    pub replan: bool,
+    pub discount_factor: f32,
+    pub time: usize,
+    pub replans: usize,
+}
+
+impl DynamicWorld {
+    pub fn new(base: Map, schedule: DynamicMap, replan: bool, discount_factor: f32) -> Self {
+        Self {
+            base,
+            schedule,
+            replan,
+            discount_factor,
+            time: 0,
+            replans: 0,
+        }
+    }
+
+    /// Avanza el reloj un paso y actualiza `map` (y `mdp` si corresponde).
+    /// Devuelve si el mapa cambió.
+    pub fn tick(&mut self, map: &mut Map, mdp: &mut Mdp, robot_cell: [usize; 2]) -> bool {
+        self.time += 1;
+
+        let previous = map.clone();
+        map.apply_snapshot(&self.base, &self.schedule.at(self.time), robot_cell);
+
+        let changed = *map != previous;
+
+        if changed && self.replan {
+            mdp.replan(map, self.discount_factor);
+            self.replans += 1;
+        }
+
+        changed
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DynamicEvaluation {
+    pub total_reward: f64,
+    pub goals: usize,
+    pub bumps: usize,
+    pub replans: usize,
+}
+
+/// Simula `steps` pasos en el mapa dinámico siguiendo la política del MDP,
+/// con o sin replanificar. Al llegar a la meta el robot reaparece al azar,
+/// igual que en `Core::run_simulation`.
+pub fn evaluate(
+    base: &Map,
+    schedule: &DynamicMap,
+    replan: bool,
+    discount_factor: f32,
+    success_prob: usize,
+    steps: usize,
+) -> DynamicEvaluation {
+    let mut mdp = Mdp::new(base.clone());
+    mdp.value_iteration(discount_factor);
+    let mut policy = mdp.get_max_policy();
+
+    let mut world = DynamicWorld::new(base.clone(), schedule.clone(), replan, discount_factor);
+    let mut env = MdpEnv::new(base.clone(), success_prob, steps);
+    let mut obs = env.reset(Some(0));
+    let mut evaluation = DynamicEvaluation::default();
+
+    for _ in 0..steps {
+        let robot_cell = env.robot.get_matricial_position();
+
+        if world.tick(&mut env.map, &mut mdp, robot_cell) && replan {
+            policy = mdp.get_max_policy();
+        }
+
+        let (next_obs, reward, terminated, truncated, info) = env.step(policy[obs]);
+        evaluation.total_reward += reward as f64;
+        evaluation.bumps += info.bumped as usize;
+
+        if terminated {
+            evaluation.goals += 1;
+        }
+
+        obs = if terminated || truncated {
+            env.reset(None)
+        } else {
+            next_obs
+        };
+    }
+
+    evaluation.replans = world.replans;
+    evaluation
+}
+
+#[cfg(test)]
+mod tests {
+    use utils::dynamic::Blocker;
+
+    use super::*;
+    use crate::{DISCOUNT_FACTORS, map::StatusType};
+
+    #[test]
+    fn patrol_goes_back_and_forth() {
+        let schedule = DynamicMap {
+            elements: vec![DynamicElement::Patrol {
+                path: vec![(0, 0), (0, 1), (0, 2)],
+                period: 1,
+            }],
+        };
+
+        let cols: Vec<usize> = (0..6)
+            .map(|time| schedule.at(time).blocked[0].0.1)
+            .collect();
+        assert_eq!(cols, [0, 1, 2, 1, 0, 1]);
+        assert_eq!(schedule.at(0).blocked[0].1, Blocker::Obstacle);
+    }
+
+    #[test]
+    fn robot_cell_and_goal_are_never_blocked() {
+        let base = Map::new();
+        let mut map = base.clone();
+        let schedule = DynamicMap {
+            elements: vec![
+                DynamicElement::Door {
+                    cell: (3, 3),
+                    open: 0,
+                    closed: 1,
+                    offset: 0,
+                },
+                DynamicElement::Door {
+                    cell: (3, 4),
+                    open: 0,
+                    closed: 1,
+                    offset: 0,
+                },
+            ],
+        };
+
+        map.apply_snapshot(&base, &schedule.at(0), [3, 3]);
+        assert_eq!(map.states[3][3].r#type, StatusType::Normal);
+        assert_eq!(map.states[3][4].r#type, StatusType::Goal);
+
+        map.apply_snapshot(&base, &schedule.at(0), [0, 0]);
+        assert_eq!(map.states[3][3].r#type, StatusType::Wall);
+    }
+
+    #[test]
+    fn replanning_matches_solving_from_scratch() {
+        let base = Map::new();
+        let gamma = DISCOUNT_FACTORS[0];
+        let mut map = base.clone();
+        map.apply_snapshot(&base, &warehouse().at(15), [5, 7]);
+
+        let mut replanned = Mdp::new(base);
+        replanned.value_iteration(gamma);
+        replanned.replan(&map, gamma);
+
+        let mut fresh = Mdp::new(map);
+        fresh.value_iteration(gamma);
+
+        // Las celdas aisladas por los bloqueos empatan en todas las acciones,
+        // así que se comparan los valores y no la acción elegida
+        for (replanned, fresh) in replanned.q_values.iter().zip(&fresh.q_values) {
+            let best = |q: &Vec<f32>| q.iter().copied().fold(f32::MIN, f32::max);
+            assert!((best(replanned) - best(fresh)).abs() < 1e-2);
+        }
+    }
+}
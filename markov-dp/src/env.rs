@@ -0,0 +1,108 @@
+use rand::{SeedableRng, rngs::StdRng};
+use raylib::prelude::Vector2;
+use utils::{Discrete, Env, Step, StepInfo};
+
+use crate::{N_STATES, map::Map, robot::Robot};
+
+/// El mapa del MDP visto como entorno: la observación es el índice de la celda
+/// del robot y la recompensa la de la celda a la que llega.
+pub struct MdpEnv {
+    pub map: Map,
+    pub robot: Robot,
+    pub max_steps: usize,
+    steps: usize,
+    rng: StdRng,
+}
+
+impl MdpEnv {
+    pub fn new(map: Map, success_prob: usize, max_steps: usize) -> Self {
+        let mut rng = StdRng::from_os_rng();
+        let robot = Robot::new(map.get_random_valid_position(&mut rng), success_prob);
+
+        Self {
+            map,
+            robot,
+            max_steps,
+            steps: 0,
+            rng,
+        }
+    }
+
+    /// Empieza un episodio con el robot en `position` en vez de una celda al azar.
+    pub fn reset_to(&mut self, position: Vector2) -> usize {
+        self.robot.set_position(position);
+        self.steps = 0;
+
+        self.robot.get_normalized_position()
+    }
+}
+
+impl Env for MdpEnv {
+    type Obs = usize;
+
+    fn reset(&mut self, seed: Option<u64>) -> usize {
+        if let Some(seed) = seed {
+            self.rng = StdRng::seed_from_u64(seed);
+        }
+
+        let position = self.map.get_random_valid_position(&mut self.rng);
+        self.robot.set_position(position);
+        self.steps = 0;
+
+        self.robot.get_normalized_position()
+    }
+
+    fn step(&mut self, action: usize) -> Step<usize> {
+        let moved = self.robot.apply_action(action, &self.map, &mut self.rng);
+        self.steps += 1;
+
+        let [row, col] = self.robot.get_matricial_position();
+        let reward = self.map.states[row][col].reward;
+        let terminated = self.robot.get_position() == self.map.get_goal_position();
+        let truncated = !terminated && self.steps >= self.max_steps;
+
+        let info = StepInfo {
+            steps: self.steps,
+            bumped: !moved,
+        };
+
+        (
+            self.robot.get_normalized_position(),
+            reward,
+            terminated,
+            truncated,
+            info,
+        )
+    }
+
+    fn action_space(&self) -> Discrete {
+        Discrete { n: 4 }
+    }
+
+    fn observation_space(&self) -> Discrete {
+        Discrete { n: N_STATES }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SUCCESS_PROBABILITIES;
+
+    #[test]
+    fn robot_never_ends_on_a_wall() {
+        for success_prob in 0..SUCCESS_PROBABILITIES.len() {
+            let mut env = MdpEnv::new(Map::new(), success_prob, 100);
+            env.reset(Some(success_prob as u64));
+
+            for step in 0..2000 {
+                let (_, _, terminated, truncated, _) = env.step(step % 4);
+                assert!(env.map.is_valid_position(env.robot.get_position()));
+
+                if terminated || truncated {
+                    env.reset(None);
+                }
+            }
+        }
+    }
+}
@@ -1,10 +1,19 @@
+mod benchmark;
 mod core;
+mod dynamic;
+mod env;
 mod graphics;
 mod map;
 mod mdp;
+mod pomdp;
 mod robot;
 
-use crate::core::Core;
+use crate::{
+    core::Core,
+    dynamic::DynamicWorld,
+    map::Map,
+    pomdp::{Controller, Localization, MAX_ERROR_RATE, WallSensor},
+};
 use raylib::prelude::*;
 
 pub const N_ROWS: usize = 6;
@@ -16,13 +25,78 @@ pub const TILE_SIZE: f32 = 100.0;
 pub const DISCOUNT_FACTORS: [f32; 4] = [0.86, 0.90, 0.94, 0.98];
 pub const SUCCESS_PROBABILITIES: [f32; 4] = [0.5, 0.7, 0.8, 0.9];
 
-fn main() {
+pub const BENCHMARKS_DIR: &str = "benchmarks";
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("🎯 MDP Robotics - Simulación");
     println!("=======================================\n");
 
+    // Con --benchmark=<carpeta> solo se resuelven los mapas de la carpeta y se escribe el reporte
+    if let Some(dir) =
+        std::env::args().find_map(|arg| arg.strip_prefix("--benchmark=").map(str::to_string))
+    {
+        println!("📊 Benchmark de Value Iteration sobre los mapas de {}", dir);
+        let results = benchmark::run(std::path::Path::new(&dir))?;
+
+        for result in &results {
+            println!(
+                "   {} (P = {}): {} barridos en {:?}, éxito {:.2}, retorno {:.3}",
+                result.map,
+                result.success_prob,
+                result.sweeps.unwrap_or_default(),
+                result.solve_time,
+                result.success_rate,
+                result.final_return
+            );
+        }
+
+        utils::benchmark::write_report(BENCHMARKS_DIR, "markov-dp", &results)?;
+        println!(
+            "✅ Reporte escrito en {}/markov-dp.{{md,csv}}",
+            BENCHMARKS_DIR
+        );
+
+        return Ok(());
+    }
+
+    // Con --map=<archivo> se usa un mapa generado con `utils::mapgen`
+    let map = match std::env::args().find_map(|arg| arg.strip_prefix("--map=").map(str::to_string))
+    {
+        Some(path) => {
+            // Las celdas de `dynamic::warehouse()` son las del mapa original
+            if std::env::args().any(|arg| arg == "--dynamic") {
+                return Err(format!("--dynamic no se puede combinar con --map={path}").into());
+            }
+
+            println!("🗺️  Cargando mapa desde {}", path);
+            let keys = utils::mapgen::parse_keys(&std::fs::read_to_string(&path)?);
+            Map::from_keys(&keys).map_err(|error| format!("Mapa inválido {path}: {error}"))?
+        }
+        None => Map::new(),
+    };
+
+    // Con --pomdp[=<error>] el robot no conoce su celda: se localiza con sensores de muros
+    let sensor = match std::env::args().find_map(|arg| match arg.as_str() {
+        "--pomdp" => Some(pomdp::SENSOR_ERROR_RATE.to_string()),
+        _ => arg.strip_prefix("--pomdp=").map(str::to_string),
+    }) {
+        Some(value) => match value.parse::<f32>() {
+            Ok(error_rate) if (0.0..=MAX_ERROR_RATE).contains(&error_rate) => {
+                Some(WallSensor { error_rate })
+            }
+            _ => {
+                return Err(format!(
+                    "Error de sensores inválido: {value} (debe estar entre 0.0 y {MAX_ERROR_RATE})"
+                )
+                .into());
+            }
+        },
+        None => None,
+    };
+
     println!("🚀 Ejecutando simulación...");
     let start_time = std::time::Instant::now();
-    let results = Core::run_simulation();
+    let results = Core::run_simulation(&map);
     let duration = start_time.elapsed();
     println!("⏱️  Simulación completada en: {:?}\n", duration);
 
@@ -32,7 +106,76 @@ fn main() {
     println!("✅ Gráficos generados en la carpeta analytics/");
     println!("\n🎮 Iniciando visualización interactiva...");
 
-    let mut visual_core = Core::new(0, 3);
+    let mut visual_core = Core::new(map, 0, 3);
+
+    // Con --dynamic el mapa cambia con el tiempo; con --replan además se recalcula la política
+    if std::env::args().any(|arg| arg == "--dynamic") {
+        let replan = std::env::args().any(|arg| arg == "--replan");
+        let schedule = dynamic::warehouse();
+        println!("🚜 Mapa dinámico: grúa horquilla, puertas y peligro variable");
+
+        for replanning in [false, true] {
+            let evaluation = dynamic::evaluate(
+                &visual_core.map,
+                &schedule,
+                replanning,
+                DISCOUNT_FACTORS[0],
+                3,
+                dynamic::DYNAMIC_STEPS,
+            );
+            println!(
+                "   {}: recompensa {:.1}, {} metas, {} choques, {} replanificaciones en {} pasos",
+                if replanning {
+                    "Replanificando"
+                } else {
+                    "Política fija"
+                },
+                evaluation.total_reward,
+                evaluation.goals,
+                evaluation.bumps,
+                evaluation.replans,
+                dynamic::DYNAMIC_STEPS
+            );
+        }
+
+        visual_core.dynamic = Some(DynamicWorld::new(
+            visual_core.map.clone(),
+            schedule,
+            replan,
+            DISCOUNT_FACTORS[0],
+        ));
+    }
+
+    if let Some(sensor) = sensor {
+        println!(
+            "🛰️  Modo POMDP: sensores de muros con {:.0}% de error",
+            sensor.error_rate * 100.0
+        );
+
+        for (name, controller) in [
+            ("MDP (celda conocida)", Controller::FullyObservable),
+            ("QMDP (creencia)", Controller::Qmdp),
+        ] {
+            let evaluation = pomdp::evaluate(
+                &visual_core.map,
+                &mut visual_core.mdp,
+                3,
+                sensor,
+                controller,
+                pomdp::EVALUATION_EPISODES,
+                pomdp::EVALUATION_MAX_STEPS,
+            );
+            println!(
+                "   {}: éxito {:.2}, {:.1} pasos por episodio, localización correcta {:.0}% de los pasos",
+                name,
+                evaluation.success_rate,
+                evaluation.mean_steps,
+                evaluation.localization_accuracy * 100.0
+            );
+        }
+
+        visual_core.localization = Some(Localization::new(&visual_core.map, sensor, 3));
+    }
 
     let (mut rlib, thread) = raylib::init()
         .size(800, 600)
@@ -58,4 +201,6 @@ fn main() {
         let mut drawer2d = drawer.begin_mode2D(camera);
         visual_core.simulate(Some(&mut drawer2d));
     }
+
+    Ok(())
 }
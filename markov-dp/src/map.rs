@@ -1,9 +1,11 @@
 use crate::{N_COLS, N_ROWS, TILE_SIZE};
+use rand::Rng;
 use raylib::prelude::*;
+use utils::dynamic::{self, Blocker, DynamicCell, Snapshot};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct State {
-    pub key: &'static str,
+    pub key: String,
     pub r#type: StatusType,
     pub reward: f32,
     pub position: Vector2,
@@ -18,7 +20,7 @@ pub enum StatusType {
     Goal,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Map {
     pub states: Vec<Vec<State>>,
 }
@@ -26,7 +28,7 @@ pub struct Map {
 impl Map {
     pub fn new() -> Self {
         #[rustfmt::skip]
-        let raw_map = vec![
+        let raw_map = [
             [ "S0",  "S1",  "P1",  "O1",  "S3",  "O2",  "S4",  "S5"  ],
             [ "O3",  "S6",  "S7",  "S8",  "S9",  "S10", "S11", "O4"  ],
             [ "S12", "P2",  "S14", "O5",  "S15", "P3",  "S17", "S18" ],
@@ -48,6 +50,37 @@ impl Map {
         Self { states: map }
     }
 
+    /// Mapa leído de un archivo o generado con `utils::mapgen`, con las mismas
+    /// claves que `raw_map`. Debe medir `N_ROWS × N_COLS` y tener una sola meta.
+    pub fn from_keys(keys: &[Vec<String>]) -> Result<Self, String> {
+        if keys.len() != N_ROWS || keys.iter().any(|row| row.len() != N_COLS) {
+            return Err(format!("El mapa debe ser de {N_ROWS}x{N_COLS}"));
+        }
+
+        let mut map = Vec::new();
+        let mut goals = 0;
+
+        for (i, row) in keys.iter().enumerate() {
+            let mut map_row = Vec::new();
+            for (j, state_key) in row.iter().enumerate() {
+                match state_key.chars().next() {
+                    Some('M') => goals += 1,
+                    Some('S' | 'P' | 'O') => {}
+                    _ => return Err(format!("Clave de estado inválida: {state_key}")),
+                }
+
+                map_row.push(Self::create_state(state_key, i, j));
+            }
+            map.push(map_row);
+        }
+
+        if goals != 1 {
+            return Err(format!("El mapa debe tener una sola meta, tiene {goals}"));
+        }
+
+        Ok(Self { states: map })
+    }
+
     pub fn draw(&self, drawer: &mut RaylibMode2D<'_, RaylibDrawHandle<'_>>) {
         for row in self.states.iter() {
             for state in row.iter() {
@@ -56,7 +89,7 @@ impl Map {
         }
     }
 
-    fn create_state(key: &'static str, i: usize, j: usize) -> State {
+    fn create_state(key: &str, i: usize, j: usize) -> State {
         let (r#type, reward, color) = match key.chars().next().unwrap() {
             'M' => (StatusType::Goal, 10.0, Color::GREEN),
             'P' => (StatusType::Danger, -0.5, Color::RED),
@@ -66,7 +99,7 @@ impl Map {
         };
 
         State {
-            key,
+            key: key.to_string(),
             r#type,
             reward,
             color,
@@ -77,8 +110,14 @@ impl Map {
         }
     }
 
+    /// Centro de la celda meta, que es donde queda el robot al llegar.
     pub fn get_goal_position(&self) -> Vector2 {
-        Vector2 { x: 450.0, y: 350.0 }
+        self.states
+            .iter()
+            .flatten()
+            .find(|state| state.r#type == StatusType::Goal)
+            .map(|state| state.position + Vector2::new(TILE_SIZE / 2.0, TILE_SIZE / 2.0))
+            .unwrap_or_default()
     }
 
     pub fn is_valid_position(&self, position: Vector2) -> bool {
@@ -92,10 +131,48 @@ impl Map {
         self.states[grid_y][grid_x].r#type != StatusType::Wall
     }
 
-    pub fn get_random_valid_position(&self) -> Vector2 {
+    /// Copia `base` y le aplica los elementos dinámicos de `snapshot`. La celda
+    /// `keep_free` (la del robot) y la meta nunca se bloquean: el obstáculo o la
+    /// puerta esperan a que el robot salga.
+    pub fn apply_snapshot(&mut self, base: &Map, snapshot: &Snapshot, keep_free: [usize; 2]) {
+        dynamic::apply_snapshot(
+            &mut self.states,
+            &base.states,
+            snapshot,
+            (keep_free[0], keep_free[1]),
+        );
+    }
+
+    /// Si hay muro (o borde del mapa) al Norte, Sur, Este y Oeste de la celda.
+    pub fn walls_around(&self, row: usize, col: usize) -> [bool; 4] {
+        let blocked = |di: isize, dj: isize| {
+            let ni = row as isize + di;
+            let nj = col as isize + dj;
+
+            ni < 0
+                || nj < 0
+                || ni >= N_ROWS as isize
+                || nj >= N_COLS as isize
+                || self.states[ni as usize][nj as usize].r#type == StatusType::Wall
+        };
+
+        [blocked(-1, 0), blocked(1, 0), blocked(0, 1), blocked(0, -1)]
+    }
+
+    /// Centros de todas las celdas donde puede empezar el robot (ni muro ni meta).
+    pub fn get_start_positions(&self) -> Vec<Vector2> {
+        self.states
+            .iter()
+            .flatten()
+            .filter(|state| !matches!(state.r#type, StatusType::Wall | StatusType::Goal))
+            .map(|state| state.position + Vector2::new(TILE_SIZE / 2.0, TILE_SIZE / 2.0))
+            .collect()
+    }
+
+    pub fn get_random_valid_position(&self, rng: &mut impl Rng) -> Vector2 {
         loop {
-            let grid_x = rand::random_range(0..N_COLS);
-            let grid_y = rand::random_range(0..N_ROWS);
+            let grid_x = rng.random_range(0..N_COLS);
+            let grid_y = rng.random_range(0..N_ROWS);
 
             let position = Vector2::new(
                 grid_x as f32 * TILE_SIZE + TILE_SIZE / 2.0,
@@ -109,6 +186,39 @@ impl Map {
     }
 }
 
+/// Un bloqueo se dibuja dorado (obstáculo) o café (puerta) y una recompensa
+/// peor que la del mapa base convierte la celda en peligro.
+impl DynamicCell for State {
+    fn is_wall(&self) -> bool {
+        self.r#type == StatusType::Wall
+    }
+
+    fn is_goal(&self) -> bool {
+        self.r#type == StatusType::Goal
+    }
+
+    fn reward(&self) -> f32 {
+        self.reward
+    }
+
+    fn block(&mut self, blocker: Blocker) {
+        self.r#type = StatusType::Wall;
+        self.color = match blocker {
+            Blocker::Obstacle => Color::GOLD,
+            Blocker::Door => Color::BROWN,
+        };
+    }
+
+    fn set_reward(&mut self, reward: f32, hazard: bool) {
+        self.reward = reward;
+        (self.r#type, self.color) = if hazard {
+            (StatusType::Danger, Color::RED)
+        } else {
+            (StatusType::Normal, Color::WHITESMOKE)
+        };
+    }
+}
+
 impl State {
     pub fn draw(&self, drawer: &mut RaylibMode2D<'_, RaylibDrawHandle<'_>>) {
         let position = self.position;
@@ -130,9 +240,9 @@ impl State {
             };
 
             drawer.draw_text(
-                self.key,
-                (position.x + TILE_SIZE as f32 / 2.0) as i32 - 12,
-                (position.y + TILE_SIZE as f32 / 2.0) as i32 - 12,
+                &self.key,
+                (position.x + TILE_SIZE / 2.0) as i32 - 12,
+                (position.y + TILE_SIZE / 2.0) as i32 - 12,
                 30,
                 text_color,
             );
@@ -147,3 +257,51 @@ impl State {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use utils::mapgen::{Cell, KeyFormat, Layout, MapGenerator, parse_keys};
+
+    use super::*;
+
+    #[test]
+    fn generated_maps_load_from_text() {
+        let layouts = [
+            "dfs",
+            "prim",
+            "obstacles:0",
+            "obstacles:0.4",
+            "obstacles:1",
+            "rooms:2",
+        ];
+
+        for (layout, seed) in layouts
+            .iter()
+            .flat_map(|layout| (0..5).map(move |s| (layout, s)))
+        {
+            let generated = MapGenerator {
+                danger_patches: 2,
+                ..MapGenerator::new(N_ROWS, N_COLS, Layout::from_name(layout).unwrap(), seed)
+            }
+            .generate();
+            let keys = parse_keys(&generated.to_text(KeyFormat::MarkovDp));
+            let map = Map::from_keys(&keys).unwrap();
+
+            for (row, cells) in generated.cells.iter().enumerate() {
+                for (col, &cell) in cells.iter().enumerate() {
+                    let expected = match cell {
+                        Cell::Free => StatusType::Normal,
+                        Cell::Danger => StatusType::Danger,
+                        Cell::Wall => StatusType::Wall,
+                        Cell::Goal => StatusType::Goal,
+                    };
+
+                    assert_eq!(
+                        map.states[row][col].r#type, expected,
+                        "{layout} with seed {seed} at ({row}, {col})"
+                    );
+                }
+            }
+        }
+    }
+}
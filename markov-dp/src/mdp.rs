@@ -3,6 +3,11 @@ use crate::{
     map::{Map, StatusType},
 };
 
+/// Máximo de barridos de `value_iteration`.
+pub const MAX_SWEEPS: usize = 1000;
+/// Cambio máximo de Q(s,a) en un barrido por debajo del cual se considera convergido.
+pub const VALUE_ITERATION_TOLERANCE: f32 = 1e-4;
+
 #[derive(Debug, Clone)]
 pub struct Mdp {
     pub map: Map,
@@ -32,6 +37,13 @@ impl Mdp {
     }
 
     pub fn build_transition_matrix_static(map: &Map) -> Vec<Vec<Vec<f32>>> {
+        Self::build_transition_matrix_with(map, PROBABILITIES)
+    }
+
+    /// Igual que `build_transition_matrix_static` pero con otras probabilidades
+    /// `[principal, izquierda, derecha]`, por ejemplo las del movimiento real
+    /// del robot para una `SUCCESS_PROBABILITIES` dada.
+    pub fn build_transition_matrix_with(map: &Map, probabilities: [f32; 3]) -> Vec<Vec<Vec<f32>>> {
         // matrices[action][from][to]
         let mut matrices = vec![
             vec![vec![0.0; N_STATES]; N_STATES], // North
@@ -71,12 +83,12 @@ impl Mdp {
                             let next_idx = ni * N_COLS + nj;
 
                             if next_status.r#type == StatusType::Wall {
-                                stay_prob += PROBABILITIES[k];
+                                stay_prob += probabilities[k];
                             } else {
-                                matrices[action][idx][next_idx] += PROBABILITIES[k];
+                                matrices[action][idx][next_idx] += probabilities[k];
                             }
                         } else {
-                            stay_prob += PROBABILITIES[k];
+                            stay_prob += probabilities[k];
                         }
                     }
 
@@ -92,12 +104,21 @@ impl Mdp {
         self.transition_matrix = Self::build_transition_matrix_static(&self.map);
     }
 
-    pub fn value_iteration(&mut self, discount_factor: f32) {
-        let mut q = vec![vec![0.0_f32; 4]; N_STATES];
+    /// Itera hasta que ningún Q(s,a) cambie más de `VALUE_ITERATION_TOLERANCE`
+    /// en un barrido, con un máximo de `MAX_SWEEPS`. Devuelve los barridos hechos.
+    ///
+    /// Parte de los `q_values` actuales (ceros en un `Mdp` nuevo), así que
+    /// después de `replan` suele necesitar menos barridos que desde cero.
+    pub fn value_iteration(&mut self, discount_factor: f32) -> usize {
+        let mut q = self.q_values.clone();
 
         let t = self.transition_matrix.clone();
+        let mut sweeps = 0;
+
+        while sweeps < MAX_SWEEPS {
+            let mut max_change = 0_f32;
+            sweeps += 1;
 
-        for _ in 0..1000 {
             for s in 0..N_STATES {
                 for a in 0..4 {
                     let mut sum_sp = 0_f32;
@@ -108,12 +129,26 @@ impl Mdp {
                                     * q[s_].clone().into_iter().reduce(f32::max).unwrap_or(0.))
                     }
 
+                    max_change = max_change.max((sum_sp - q[s][a]).abs());
                     q[s][a] = sum_sp;
                 }
             }
+
+            if max_change < VALUE_ITERATION_TOLERANCE {
+                break;
+            }
         }
 
-        self.q_values = q
+        self.q_values = q;
+
+        sweeps
+    }
+
+    /// Vuelve a resolver el MDP después de un cambio en el mapa.
+    pub fn replan(&mut self, map: &Map, discount_factor: f32) -> usize {
+        self.map.clone_from(map);
+        self.build_transition_matrix();
+        self.value_iteration(discount_factor)
     }
 
     pub fn get_max_policy(&mut self) -> Vec<usize> {
@@ -130,3 +165,184 @@ impl Mdp {
         max_policy
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use utils::mapgen::{KeyFormat, Layout, MapGenerator};
+
+    use super::*;
+    use crate::DISCOUNT_FACTORS;
+
+    /// Política de `Map::new()` para cada γ de `DISCOUNT_FACTORS`
+    /// (0 Norte, 1 Sur, 2 Este, 3 Oeste; los muros quedan en 0).
+    #[rustfmt::skip]
+    const GOLDEN_POLICIES: [[usize; N_STATES]; 4] = [
+        [
+            2, 1, 1, 0, 1, 0, 1, 3,
+            0, 2, 1, 2, 1, 3, 1, 0,
+            2, 2, 1, 0, 1, 3, 3, 3,
+            2, 2, 2, 2, 3, 3, 3, 0,
+            0, 0, 0, 0, 0, 3, 3, 3,
+            0, 0, 2, 0, 0, 0, 3, 3,
+        ],
+        [
+            2, 1, 1, 0, 1, 0, 1, 3,
+            0, 1, 1, 2, 1, 3, 1, 0,
+            2, 2, 1, 0, 1, 3, 3, 3,
+            2, 2, 2, 2, 3, 3, 3, 0,
+            0, 0, 0, 0, 0, 3, 3, 3,
+            0, 0, 2, 0, 0, 0, 3, 3,
+        ],
+        [
+            2, 1, 1, 0, 1, 0, 1, 3,
+            0, 1, 1, 2, 1, 3, 1, 0,
+            2, 2, 1, 0, 1, 3, 3, 3,
+            2, 2, 2, 2, 3, 3, 3, 0,
+            0, 0, 0, 0, 0, 3, 3, 3,
+            0, 0, 2, 0, 0, 0, 3, 3,
+        ],
+        [
+            2, 1, 1, 0, 1, 0, 1, 3,
+            0, 1, 1, 2, 1, 1, 1, 0,
+            2, 2, 1, 0, 1, 3, 3, 3,
+            2, 2, 2, 2, 3, 3, 3, 0,
+            0, 0, 0, 0, 0, 3, 3, 3,
+            0, 0, 2, 0, 0, 0, 3, 3,
+        ],
+    ];
+
+    /// El mapa original y algunos generados, para no depender de uno solo.
+    fn test_maps() -> Vec<Map> {
+        let layouts = ["obstacles:0.3", "rooms:2", "prim", "dfs"];
+        let mut maps = vec![Map::new()];
+
+        for (seed, layout) in layouts.iter().enumerate() {
+            let generator = MapGenerator {
+                danger_patches: 2,
+                ..MapGenerator::new(
+                    N_ROWS,
+                    N_COLS,
+                    Layout::from_name(layout).unwrap(),
+                    seed as u64,
+                )
+            };
+            let keys = generator.generate().to_keys(KeyFormat::MarkovDp);
+            maps.push(Map::from_keys(&keys).unwrap());
+        }
+
+        maps
+    }
+
+    /// Mapa de puros muros salvo las celdas indicadas.
+    fn map_with(cells: &[((usize, usize), &str)]) -> Map {
+        let mut keys = vec![vec!["O".to_string(); N_COLS]; N_ROWS];
+
+        for &((row, col), key) in cells {
+            keys[row][col] = key.to_string();
+        }
+
+        Map::from_keys(&keys).unwrap()
+    }
+
+    fn state_value(mdp: &Mdp, row: usize, col: usize) -> f32 {
+        mdp.q_values[row * N_COLS + col]
+            .iter()
+            .copied()
+            .fold(f32::MIN, f32::max)
+    }
+
+    #[test]
+    fn transition_rows_sum_to_one() {
+        for map in test_maps() {
+            let matrices = Mdp::build_transition_matrix_static(&map);
+
+            for (action, matrix) in matrices.iter().enumerate() {
+                for (from, row) in matrix.iter().enumerate() {
+                    let sum: f32 = row.iter().sum();
+                    let is_wall =
+                        map.states[from / N_COLS][from % N_COLS].r#type == StatusType::Wall;
+
+                    // Desde un muro no se sale: su fila queda vacía
+                    let expected = if is_wall { 0.0 } else { 1.0 };
+                    assert!(
+                        (sum - expected).abs() < 1e-5,
+                        "action {action}, state {from}: row sums to {sum}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn walls_are_never_entered() {
+        for map in test_maps() {
+            let matrices = Mdp::build_transition_matrix_static(&map);
+
+            for (to, state) in map.states.iter().flatten().enumerate() {
+                if state.r#type != StatusType::Wall {
+                    continue;
+                }
+
+                for matrix in &matrices {
+                    assert!(matrix.iter().all(|row| row[to] == 0.0), "wall {to} entered");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn value_iteration_matches_hand_solved_values() {
+        // Un pasillo S-M en la esquina y dos celdas encerradas, todo lo demás muro
+        let map = map_with(&[
+            ((0, 0), "S0"),
+            ((0, 1), "M"),
+            ((5, 0), "P0"),
+            ((5, 7), "S1"),
+        ]);
+        let gamma = 0.9;
+
+        let mut mdp = Mdp::new(map);
+        let sweeps = mdp.value_iteration(gamma);
+        assert!(sweeps < MAX_SWEEPS, "did not converge");
+
+        // Una celda encerrada recibe su propia recompensa para siempre: r / (1 - γ)
+        let goal = 10.0 / (1.0 - gamma);
+        let danger = -0.5 / (1.0 - gamma);
+        let enclosed = -0.1 / (1.0 - gamma);
+
+        // Desde S0 lo mejor es ir al Este: 0.8 llega a la meta y 0.2 choca y se queda
+        // V = 0.8 (10 + γ V_M) + 0.2 (-0.1 + γ V)
+        let corridor = (0.8 * (10.0 + gamma * goal) - 0.2 * 0.1) / (1.0 - 0.2 * gamma);
+
+        let expected = [
+            ((0, 1), goal),
+            ((5, 0), danger),
+            ((5, 7), enclosed),
+            ((0, 0), corridor),
+        ];
+
+        for ((row, col), value) in expected {
+            let actual = state_value(&mdp, row, col);
+            assert!(
+                (actual - value).abs() < 1e-2,
+                "V({row}, {col}) = {actual}, expected {value}"
+            );
+        }
+
+        assert_eq!(mdp.get_max_policy()[0], 2);
+    }
+
+    #[test]
+    fn max_policy_matches_golden_for_each_discount_factor() {
+        for (&gamma, golden) in DISCOUNT_FACTORS.iter().zip(&GOLDEN_POLICIES) {
+            let mut mdp = Mdp::new(Map::new());
+            mdp.value_iteration(gamma);
+
+            assert_eq!(
+                &mdp.get_max_policy(),
+                golden,
+                "policy changed for γ = {gamma}"
+            );
+        }
+    }
+}
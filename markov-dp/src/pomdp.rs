@@ -0,0 +1,390 @@
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use raylib::prelude::*;
+use utils::{Discrete, Env, Step};
+
+pub use utils::pomdp::{Belief, MAX_ERROR_RATE, WallSensor, qmdp_action};
+
+use crate::{
+    N_COLS, SUCCESS_PROBABILITIES, TILE_SIZE,
+    env::MdpEnv,
+    map::{Map, StatusType},
+    mdp::Mdp,
+};
+
+/// Error de los sensores de muros por defecto en el modo `--pomdp`.
+pub const SENSOR_ERROR_RATE: f32 = 0.1;
+/// Episodios y límite de pasos de la comparación entre MDP y QMDP.
+pub const EVALUATION_EPISODES: usize = 500;
+pub const EVALUATION_MAX_STEPS: usize = 200;
+
+/// Lectura ruidosa de los sensores de muros (ver `Map::walls_around`) en la celda.
+pub fn read_sensors(
+    sensor: &WallSensor,
+    map: &Map,
+    row: usize,
+    col: usize,
+    rng: &mut impl Rng,
+) -> [bool; 4] {
+    sensor.read(map.walls_around(row, col), rng)
+}
+
+/// Misma probabilidad en todas las celdas que no son muro.
+pub fn uniform_belief(map: &Map) -> Belief {
+    let free: Vec<bool> = map
+        .states
+        .iter()
+        .flatten()
+        .map(|state| state.r#type != StatusType::Wall)
+        .collect();
+
+    Belief::uniform(&free)
+}
+
+/// Corrige la creencia con la lectura `reading`. Si la lectura es imposible
+/// para toda celda con probabilidad, la creencia vuelve a ser uniforme.
+pub fn correct_belief(belief: &mut Belief, sensor: &WallSensor, map: &Map, reading: &[bool; 4]) {
+    let consistent =
+        belief.correct(|s| sensor.likelihood(&map.walls_around(s / N_COLS, s % N_COLS), reading));
+
+    if !consistent {
+        *belief = uniform_belief(map);
+    }
+}
+
+/// Sombrea cada celda según su probabilidad y marca la más probable.
+pub fn draw_belief(belief: &Belief, drawer: &mut RaylibMode2D<'_, RaylibDrawHandle<'_>>) {
+    let max = belief
+        .probs
+        .iter()
+        .copied()
+        .fold(0.0, f32::max)
+        .max(f32::EPSILON);
+
+    for (s, &prob) in belief.probs.iter().enumerate() {
+        if prob < 0.005 {
+            continue;
+        }
+
+        let x = (s % N_COLS) as f32 * TILE_SIZE;
+        let y = (s / N_COLS) as f32 * TILE_SIZE;
+
+        drawer.draw_rectangle(
+            x as i32,
+            y as i32,
+            TILE_SIZE as i32,
+            TILE_SIZE as i32,
+            Color::ORANGE.fade(0.7 * prob / max),
+        );
+        drawer.draw_text(
+            &format!("{:.0}%", prob * 100.0),
+            x as i32 + 5,
+            y as i32 + 5,
+            18,
+            Color::BLACK,
+        );
+    }
+
+    let best = belief.most_likely();
+    drawer.draw_rectangle_lines(
+        ((best % N_COLS) as f32 * TILE_SIZE) as i32,
+        ((best / N_COLS) as f32 * TILE_SIZE) as i32,
+        TILE_SIZE as i32,
+        TILE_SIZE as i32,
+        Color::ORANGE,
+    );
+}
+
+/// Modelo de movimiento real del robot para `SUCCESS_PROBABILITIES[success_prob]`:
+/// avanza con esa probabilidad y si falla se desvía a un lado o al otro por igual.
+pub fn motion_model(map: &Map, success_prob: usize) -> Vec<Vec<Vec<f32>>> {
+    let p = SUCCESS_PROBABILITIES[success_prob];
+    Mdp::build_transition_matrix_with(map, [p, (1.0 - p) / 2.0, (1.0 - p) / 2.0])
+}
+
+/// Lo que necesita el robot para moverse sin conocer su celda: creencia,
+/// sensores y modelo de movimiento.
+#[derive(Debug, Clone)]
+pub struct Localization {
+    pub belief: Belief,
+    pub sensor: WallSensor,
+    success_prob: usize,
+    motion: Vec<Vec<Vec<f32>>>,
+}
+
+impl Localization {
+    pub fn new(map: &Map, sensor: WallSensor, success_prob: usize) -> Self {
+        Self {
+            belief: uniform_belief(map),
+            sensor,
+            success_prob,
+            motion: motion_model(map, success_prob),
+        }
+    }
+
+    /// Rehace el modelo de movimiento cuando cambia el mapa (por ejemplo con
+    /// `--dynamic`), para que la predicción no atraviese los muros nuevos.
+    pub fn set_map(&mut self, map: &Map) {
+        self.motion = motion_model(map, self.success_prob);
+    }
+
+    /// Un paso del filtro de Bayes después de ejecutar `action` y leer `reading`.
+    pub fn update(&mut self, map: &Map, action: usize, reading: &[bool; 4]) {
+        let motion = &self.motion[action];
+        self.belief.predict(|s| {
+            motion[s]
+                .iter()
+                .enumerate()
+                .filter(|&(_, &p)| p > 0.0)
+                .map(|(s_, &p)| (s_, p))
+                .collect()
+        });
+        correct_belief(&mut self.belief, &self.sensor, map, reading);
+    }
+
+    /// El robot fue movido a otra celda sin saber cuál.
+    pub fn reset(&mut self, map: &Map) {
+        self.belief = uniform_belief(map);
+    }
+}
+
+/// `MdpEnv` donde la observación ya no es la celda sino la lectura de los
+/// sensores de muros.
+pub struct PomdpEnv {
+    pub env: MdpEnv,
+    pub sensor: WallSensor,
+    rng: StdRng,
+}
+
+impl PomdpEnv {
+    pub fn new(env: MdpEnv, sensor: WallSensor) -> Self {
+        Self {
+            env,
+            sensor,
+            rng: StdRng::from_os_rng(),
+        }
+    }
+
+    fn read(&mut self) -> [bool; 4] {
+        let [row, col] = self.env.robot.get_matricial_position();
+        read_sensors(&self.sensor, &self.env.map, row, col, &mut self.rng)
+    }
+}
+
+impl Env for PomdpEnv {
+    type Obs = [bool; 4];
+
+    fn reset(&mut self, seed: Option<u64>) -> [bool; 4] {
+        if let Some(seed) = seed {
+            self.rng = StdRng::seed_from_u64(seed);
+        }
+
+        self.env.reset(seed);
+        self.read()
+    }
+
+    fn step(&mut self, action: usize) -> Step<[bool; 4]> {
+        let (_, reward, terminated, truncated, info) = self.env.step(action);
+        (self.read(), reward, terminated, truncated, info)
+    }
+
+    fn action_space(&self) -> Discrete {
+        Discrete { n: 4 }
+    }
+
+    /// Las 16 combinaciones posibles de los cuatro sensores.
+    fn observation_space(&self) -> Discrete {
+        Discrete { n: 16 }
+    }
+}
+
+/// Quién decide la acción en `evaluate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Controller {
+    /// La política del MDP con la celda real (referencia con observabilidad total).
+    FullyObservable,
+    /// QMDP sobre la creencia del filtro de Bayes.
+    Qmdp,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PomdpEvaluation {
+    pub success_rate: f64,
+    pub mean_steps: f64,
+    /// Fracción de pasos en que la celda más probable era la real.
+    pub localization_accuracy: f64,
+}
+
+/// Juega `episodes` episodios desde celdas al azar y mide qué tan seguido se
+/// llega a la meta. La creencia se mantiene también con `FullyObservable`,
+/// para comparar la precisión de la localización con la misma semilla.
+pub fn evaluate(
+    map: &Map,
+    mdp: &mut Mdp,
+    success_prob: usize,
+    sensor: WallSensor,
+    controller: Controller,
+    episodes: usize,
+    max_steps: usize,
+) -> PomdpEvaluation {
+    let policy = mdp.get_max_policy();
+    let mut env = PomdpEnv::new(MdpEnv::new(map.clone(), success_prob, max_steps), sensor);
+    let mut localization = Localization::new(map, sensor, success_prob);
+
+    let mut successes = 0;
+    let mut total_steps = 0;
+    let mut localized = 0;
+
+    for episode in 0..episodes {
+        // Solo el primer episodio fija la semilla; los demás siguen la misma secuencia
+        let reading = env.reset((episode == 0).then_some(0));
+        localization.reset(map);
+        correct_belief(&mut localization.belief, &sensor, map, &reading);
+
+        loop {
+            let action = match controller {
+                Controller::FullyObservable => policy[env.env.robot.get_normalized_position()],
+                Controller::Qmdp => qmdp_action(&localization.belief, &mdp.q_values),
+            };
+
+            let (reading, _, terminated, truncated, _) = env.step(action);
+            localization.update(map, action, &reading);
+
+            total_steps += 1;
+            if localization.belief.most_likely() == env.env.robot.get_normalized_position() {
+                localized += 1;
+            }
+
+            if terminated {
+                successes += 1;
+            }
+
+            if terminated || truncated {
+                break;
+            }
+        }
+    }
+
+    PomdpEvaluation {
+        success_rate: successes as f64 / episodes.max(1) as f64,
+        mean_steps: total_steps as f64 / episodes.max(1) as f64,
+        localization_accuracy: localized as f64 / total_steps.max(1) as f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use utils::dynamic::{DynamicElement, DynamicMap};
+
+    use super::*;
+    use crate::{DISCOUNT_FACTORS, N_STATES};
+
+    #[test]
+    fn belief_stays_normalized() {
+        let map = Map::new();
+        let sensor = WallSensor { error_rate: 0.2 };
+        let mut localization = Localization::new(&map, sensor, 1);
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for step in 0..500 {
+            let reading = read_sensors(&sensor, &map, 3, 2, &mut rng);
+            localization.update(&map, step % 4, &reading);
+
+            let total: f32 = localization.belief.probs.iter().sum();
+            assert!((total - 1.0).abs() < 1e-4, "belief sums to {total}");
+        }
+    }
+
+    #[test]
+    fn exact_sensors_keep_only_consistent_cells() {
+        let map = Map::new();
+        let sensor = WallSensor { error_rate: 0.0 };
+        let reading = map.walls_around(3, 2);
+
+        let mut belief = uniform_belief(&map);
+        correct_belief(&mut belief, &sensor, &map, &reading);
+
+        for (s, &prob) in belief.probs.iter().enumerate() {
+            let (row, col) = (s / N_COLS, s % N_COLS);
+            let consistent = map.states[row][col].r#type != StatusType::Wall
+                && map.walls_around(row, col) == reading;
+
+            assert_eq!(prob > 0.0, consistent, "cell ({row}, {col})");
+        }
+    }
+
+    #[test]
+    fn qmdp_picks_the_best_expected_action_over_two_cells() {
+        let map = Map::new();
+        let mut mdp = Mdp::new(map.clone());
+        mdp.value_iteration(DISCOUNT_FACTORS[0]);
+        let policy = mdp.get_max_policy();
+
+        let cells: Vec<usize> = map
+            .states
+            .iter()
+            .flatten()
+            .enumerate()
+            .filter(|(_, state)| state.r#type == StatusType::Normal)
+            .map(|(s, _)| s)
+            .collect();
+        let mut differs_from_a_cell = false;
+
+        for (i, &first) in cells.iter().enumerate() {
+            for &second in &cells[i + 1..] {
+                let mut belief = Belief {
+                    probs: vec![0.0; N_STATES],
+                };
+                belief.probs[first] = 0.3;
+                belief.probs[second] = 0.7;
+
+                let expected = |action: usize| {
+                    0.3 * mdp.q_values[first][action] + 0.7 * mdp.q_values[second][action]
+                };
+                let action = qmdp_action(&belief, &mdp.q_values);
+
+                for other in 0..4 {
+                    assert!(
+                        expected(action) >= expected(other),
+                        "cells {first} and {second}: {action} is worse than {other}"
+                    );
+                }
+
+                differs_from_a_cell |= action != policy[first] || action != policy[second];
+            }
+        }
+
+        assert!(differs_from_a_cell);
+    }
+
+    #[test]
+    fn prediction_follows_the_map_changes() {
+        let base = Map::new();
+        let mut map = base.clone();
+        // Con 50% de error las lecturas no dan información y solo cuenta la predicción
+        let mut localization = Localization::new(&map, WallSensor { error_rate: 0.5 }, 3);
+
+        // Se cierra una puerta en (3, 3), al lado de la celda del robot
+        let door = DynamicMap {
+            elements: vec![DynamicElement::Door {
+                cell: (3, 3),
+                open: 0,
+                closed: 1,
+                offset: 0,
+            }],
+        };
+        map.apply_snapshot(&base, &door.at(0), [0, 0]);
+        localization.set_map(&map);
+
+        for action in 0..4 {
+            localization.belief.probs = vec![0.0; N_STATES];
+            localization.belief.probs[3 * N_COLS + 2] = 1.0;
+            localization.update(&map, action, &[false; 4]);
+
+            assert_eq!(
+                localization.belief.probs[3 * N_COLS + 3],
+                0.0,
+                "action {action} moves into the closed door"
+            );
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use rand::Rng;
 use raylib::prelude::*;
 
 use crate::{N_COLS, N_ROWS, SUCCESS_PROBABILITIES, TILE_SIZE, map::Map};
@@ -42,7 +43,7 @@ impl Robot {
         [norm_y, norm_x]
     }
 
-    fn calc_next_action(&self, next_action: usize) -> (f32, f32) {
+    fn calc_next_action(&self, next_action: usize, rng: &mut impl Rng) -> (f32, f32) {
         let north = (0.0, -TILE_SIZE);
         let south = (0.0, TILE_SIZE);
         let east = (TILE_SIZE, 0.0);
@@ -56,29 +57,35 @@ impl Robot {
         ];
 
         let possible_actions = combinations[next_action];
-        let success_prob = SUCCESS_PROBABILITIES[self.success_prob] as f32;
+        let success_prob = SUCCESS_PROBABILITIES[self.success_prob];
 
-        let choice = rand::random::<f32>();
+        let choice = rng.random::<f32>();
 
         if choice <= success_prob {
             possible_actions[0]
         } else {
-            match rand::random_bool(0.5) {
+            match rng.random_bool(0.5) {
                 true => possible_actions[1],
                 false => possible_actions[2],
             }
         }
     }
 
-    pub fn update(&mut self, policy: &Vec<usize>, map: &Map) {
+    pub fn update(&mut self, policy: &[usize], map: &Map) {
         let current_index = self.get_normalized_position();
 
         if current_index >= policy.len() {
             return;
         }
 
-        let next_action = policy[current_index];
-        let diff = self.calc_next_action(next_action);
+        self.apply_action(policy[current_index], map, &mut rand::rng());
+    }
+
+    /// Intenta ejecutar `action` (puede desviarse hacia los lados según la
+    /// probabilidad de éxito). Devuelve `false` si el movimiento chocó con un
+    /// muro o el borde del mapa y el robot se quedó en su lugar.
+    pub fn apply_action(&mut self, action: usize, map: &Map, rng: &mut impl Rng) -> bool {
+        let diff = self.calc_next_action(action, rng);
 
         let current_pos = self.get_position();
         let new_pos = Vector2 {
@@ -98,8 +105,11 @@ impl Robot {
             && map.is_valid_position(new_pos)
         {
             self.set_position(new_pos);
+            return true;
         }
+
         // Si el movimiento es inválido, el robot se queda en su posición actual
+        false
     }
 
     pub fn draw(&self, drawer: &mut RaylibMode2D<'_, RaylibDrawHandle<'_>>) {
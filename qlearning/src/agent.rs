@@ -0,0 +1,346 @@
+use std::io;
+
+use rand::{random, random_range};
+use utils::{Env, argmax_f32};
+
+use crate::{
+    DISCOUNT_FACTOR, EPISODES, EPSILON_DECAY, LEARNING_RATE,
+    environment::Environment,
+    evaluation::{EVALUATION_INTERVAL, Evaluation, evaluate},
+    logger::{EpisodeLog, TrainingLogger},
+    observer::{TrainingObserver, TrainingProgress},
+    optimal::{EpisodeMetrics, Optimum},
+    planning::{Planning, ReplayBuffer, TabularModel},
+};
+
+/// Agente de Q-Learning tabular. Guarda solo lo que aprende (Q-table y
+/// epsilon); el mundo vive en `Environment` y se usa a través de `Env`.
+#[derive(Debug, Clone)]
+pub struct QLearningAgent {
+    pub q_table: Vec<Vec<f32>>,
+    pub epsilon: f64,
+    pub planning: Planning,
+    replay: ReplayBuffer,
+    model: TabularModel,
+}
+
+/// Transición `(s, a, r, s')` observada en el entorno.
+#[derive(Debug, Clone, Copy)]
+pub struct Experience {
+    pub obs: usize,
+    pub action: usize,
+    pub reward: f32,
+    pub next_obs: usize,
+    pub terminated: bool,
+}
+
+/// Lo registrado durante `QLearningAgent::train`: una entrada por episodio y
+/// una evaluación greedy `(episodio, evaluación)` cada `EVALUATION_INTERVAL`.
+#[derive(Debug, Clone, Default)]
+pub struct TrainingHistory {
+    pub rewards: Vec<f64>,
+    pub steps: Vec<usize>,
+    pub metrics: Vec<EpisodeMetrics>,
+    pub evaluations: Vec<(usize, Evaluation)>,
+}
+
+/// Resumen de un episodio jugado por el agente.
+#[derive(Debug, Clone, Copy)]
+pub struct EpisodeStats {
+    pub start: usize,
+    pub steps: usize,
+    pub total_reward: f64,
+    pub discounted_return: f64,
+    pub td_error_mean: f64,
+    pub td_error_max: f64,
+}
+
+impl QLearningAgent {
+    pub fn new(n_states: usize, n_actions: usize) -> Self {
+        Self {
+            q_table: vec![vec![0.0; n_actions]; n_states],
+            epsilon: 0.1_f64,
+            planning: Planning::None,
+            replay: ReplayBuffer::default(),
+            model: TabularModel::default(),
+        }
+    }
+
+    /// Agente que además de aprender de cada paso real hace `planning`.
+    pub fn with_planning(n_states: usize, n_actions: usize, planning: Planning) -> Self {
+        let replay = match planning {
+            Planning::Replay { capacity, .. } => ReplayBuffer::new(capacity),
+            _ => ReplayBuffer::default(),
+        };
+
+        Self {
+            planning,
+            replay,
+            ..Self::new(n_states, n_actions)
+        }
+    }
+
+    /// Crea un agente con el tamaño de los espacios de `env`.
+    pub fn for_env<E: Env>(env: &E) -> Self {
+        Self::new(env.observation_space().n, env.action_space().n)
+    }
+
+    pub fn choose_action(&self, obs: usize) -> usize {
+        if random::<f64>() > self.epsilon {
+            return self.best_action(obs);
+        }
+
+        random_range(0..self.q_table[obs].len())
+    }
+
+    pub fn best_action(&self, obs: usize) -> usize {
+        argmax_f32(&self.q_table[obs])
+    }
+
+    /// Actualiza la Q-table usando la ecuación de Bellman para Q-Learning.
+    ///
+    /// Esta función implementa el núcleo del algoritmo Q-Learning, que permite al robot
+    /// aprender la política óptima a través de la experiencia. La actualización sigue
+    /// la ecuación: Q(s,a) = Q(s,a) + α[r + γ*max_a'Q(s',a') - Q(s,a)]
+    ///
+    /// LEARNING_RATE: qué tan rápido aprende el robot.
+    /// a más alto, más rápido se adapta a nuevas experiencias.
+    ///
+    /// DISCOUNT_FACTOR: Factor de descuento que determina la importancia
+    /// de las recompensas futuras vs inmediatas.
+    /// a mayor valor, el robot valora más las recompensas futuras
+    ///
+    /// r (reward): Recompensa inmediata obtenida en la transición hacia next_obs.
+    /// Guía al robot sobre qué tan bueno o malo es estar en ese estado.
+    ///
+    /// max_future_q: El máximo valor Q esperado desde el siguiente estado,
+    /// representando la mejor acción posible que el robot puede tomar desde ahí.
+    /// Esto permite que el robot considere las consecuencias futuras de sus acciones.
+    /// Si `terminated` es verdadero (se llegó a la meta) no hay futuro y vale 0.
+    ///
+    /// Devuelve el error TD, r + γ*max_a'Q(s',a') - Q(s,a), antes de la actualización.
+    pub fn update(
+        &mut self,
+        obs: usize,
+        action: usize,
+        reward: f32,
+        next_obs: usize,
+        terminated: bool,
+    ) -> f32 {
+        let max_future_q = if terminated {
+            0.0
+        } else {
+            self.q_table[next_obs][self.best_action(next_obs)]
+        };
+        let old_q = self.q_table[obs][action];
+
+        // Formula: Q_nuevo = (1-α)*Q_viejo + α*(recompensa + γ*mejor_Q_futuro)
+        // El balance entre estos términos permite al robot aprender gradualmente
+        // sin olvidar completamente lo que ya sabía
+        let target = reward + DISCOUNT_FACTOR * max_future_q;
+        self.q_table[obs][action] = (1.0 - LEARNING_RATE) * old_q + LEARNING_RATE * target;
+
+        target - old_q
+    }
+
+    /// Actualizaciones extra después de aprender de `experience` según
+    /// `self.planning`: se repiten transiciones del buffer o se simulan con el
+    /// modelo aprendido, usando la misma regla que `update`.
+    fn plan(&mut self, experience: Experience) {
+        let updates = match self.planning {
+            Planning::None => 0,
+            Planning::Replay { updates, .. } => {
+                self.replay.push(experience);
+                updates
+            }
+            Planning::DynaQ { steps } => {
+                self.model.observe(experience);
+                steps
+            }
+        };
+
+        for _ in 0..updates {
+            let sampled = match self.planning {
+                Planning::Replay { .. } => self.replay.sample(),
+                _ => self.model.sample(),
+            };
+
+            if let Some(e) = sampled {
+                self.update(e.obs, e.action, e.reward, e.next_obs, e.terminated);
+            }
+        }
+    }
+
+    /// Juega un episodio completo con la política epsilon-greedy, aprendiendo en
+    /// cada paso. `discount_factor` solo se usa para el retorno descontado.
+    pub fn run_episode<E: Env<Obs = usize>>(
+        &mut self,
+        env: &mut E,
+        discount_factor: f32,
+    ) -> EpisodeStats {
+        let start = env.reset(None);
+        let mut obs = start;
+
+        let mut steps = 0;
+        let mut total_reward = 0.0_f64;
+        let mut discounted_return = 0.0_f64;
+        let mut discount = 1.0_f64;
+        let mut td_error_sum = 0.0_f64;
+        let mut td_error_max = 0.0_f64;
+
+        loop {
+            let action = self.choose_action(obs);
+            let (next_obs, reward, terminated, truncated, _) = env.step(action);
+
+            let td_error = self.update(obs, action, reward, next_obs, terminated).abs() as f64;
+            td_error_sum += td_error;
+            td_error_max = td_error_max.max(td_error);
+
+            self.plan(Experience {
+                obs,
+                action,
+                reward,
+                next_obs,
+                terminated,
+            });
+
+            obs = next_obs;
+            total_reward += reward as f64;
+            discounted_return += discount * reward as f64;
+            discount *= discount_factor as f64;
+            steps += 1;
+
+            if terminated || truncated {
+                break;
+            }
+        }
+
+        EpisodeStats {
+            start,
+            steps,
+            total_reward,
+            discounted_return,
+            td_error_mean: td_error_sum / steps as f64,
+            td_error_max,
+        }
+    }
+
+    /// Entrena la Q-table durante `EPISODES` episodios sin dibujar nada.
+    ///
+    /// Cada `EVALUATION_INTERVAL` episodios se evalúa la política greedy (ε = 0)
+    /// desde todas las celdas normales, siempre las mismas, sin importar la
+    /// distribución de inicio del entrenamiento. La evaluación corre sobre una
+    /// copia de `env`, así que no cambia lo que aprende el agente.
+    ///
+    /// Los `observers` reciben el progreso cada `interval()` episodios; un visor
+    /// puede suscribirse para mostrar el entrenamiento en vivo.
+    ///
+    /// Sin `optimum` no se calculan las métricas de optimalidad; es el caso de
+    /// los mapas dinámicos, donde el óptimo del mapa estático no sirve.
+    pub fn train(
+        &mut self,
+        env: &mut Environment,
+        optimum: Option<&Optimum>,
+        logger: &mut TrainingLogger,
+        observers: &mut [&mut dyn TrainingObserver],
+    ) -> io::Result<TrainingHistory> {
+        let mut history = TrainingHistory::default();
+        let evaluation_cells = env.normal_cells();
+        // El retorno descontado se compara con V*, así que usa el mismo factor
+        let discount_factor = optimum.map_or(DISCOUNT_FACTOR, |optimum| optimum.discount_factor);
+
+        for episode in 1..=EPISODES {
+            let stats = self.run_episode(env, discount_factor);
+            let episode_metrics = optimum.map(|optimum| {
+                optimum.metrics(env, &self.q_table, stats.start, stats.discounted_return)
+            });
+
+            logger.log_episode(&EpisodeLog {
+                episode,
+                steps: stats.steps,
+                total_reward: stats.total_reward,
+                epsilon: self.epsilon,
+                td_error_mean: stats.td_error_mean,
+                td_error_max: stats.td_error_max,
+                metrics: episode_metrics,
+            })?;
+            logger.snapshot_policy(episode, &self.q_table)?;
+
+            history.metrics.extend(episode_metrics);
+            history.rewards.push(stats.total_reward);
+            history.steps.push(stats.steps);
+
+            self.epsilon *= EPSILON_DECAY;
+
+            if episode.is_multiple_of(EVALUATION_INTERVAL) {
+                let evaluation = evaluate(env, &evaluation_cells, |obs| self.best_action(obs));
+                logger.log_evaluation(episode, &evaluation)?;
+                history.evaluations.push((episode, evaluation));
+            }
+
+            let progress = TrainingProgress {
+                episode,
+                total_episodes: EPISODES,
+                env,
+                agent: self,
+                history: &history,
+            };
+
+            for observer in observers.iter_mut() {
+                if episode.is_multiple_of(observer.interval().max(1)) || episode == EPISODES {
+                    observer.on_episode(&progress);
+                }
+            }
+
+            if observers.iter().any(|observer| observer.should_stop()) {
+                break;
+            }
+        }
+
+        logger.flush()?;
+
+        Ok(history)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logger::{LogFormat, LoggerConfig};
+
+    /// Entrena sin exploración: `choose_action` usa el rng global, así que con
+    /// ε = 0 todo el azar sale del rng del entorno.
+    fn greedy_agent(env: &Environment) -> QLearningAgent {
+        let mut agent = QLearningAgent::for_env(env);
+        agent.epsilon = 0.0;
+        agent
+    }
+
+    #[test]
+    fn evaluation_does_not_change_training() {
+        let config = LoggerConfig {
+            run_dir: std::env::temp_dir().join(format!("qlearning-agent-{}", std::process::id())),
+            format: LogFormat::Csv,
+            policy_interval: 0,
+        };
+        let mut logger = TrainingLogger::create(&config, "train").unwrap();
+
+        let mut env = Environment::new(0.7);
+        env.reset(Some(11));
+        let mut evaluated = greedy_agent(&env);
+        let history = evaluated
+            .train(&mut env, None, &mut logger, &mut [])
+            .unwrap();
+        assert!(!history.evaluations.is_empty());
+
+        let mut env = Environment::new(0.7);
+        env.reset(Some(11));
+        let mut plain = greedy_agent(&env);
+        for _ in 0..EPISODES {
+            plain.run_episode(&mut env, DISCOUNT_FACTOR);
+        }
+
+        assert_eq!(evaluated.q_table, plain.q_table);
+        std::fs::remove_dir_all(&config.run_dir).ok();
+    }
+}
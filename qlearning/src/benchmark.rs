@@ -0,0 +1,98 @@
+use std::{
+    io,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use utils::{
+    Env,
+    benchmark::{BenchmarkResult, map_files},
+    mapgen::{GeneratedMap, KeyFormat, parse_keys},
+};
+
+use crate::{
+    DISCOUNT_FACTOR, EPSILON_DECAY, SUCCESS_PROBABILITIES,
+    agent::QLearningAgent,
+    dynamics::Dynamics,
+    environment::Environment,
+    evaluation::{EVALUATION_INTERVAL, Evaluation, evaluate},
+};
+
+/// Episodios de entrenamiento por mapa y probabilidad de éxito.
+pub const BENCHMARK_EPISODES: usize = 3000;
+/// Tasa de éxito de la política greedy a partir de la cual se cuenta el mapa como aprendido.
+pub const TARGET_SUCCESS_RATE: f64 = 0.9;
+/// Semilla del entorno, para que todas las corridas vean los mismos inicios.
+pub const BENCHMARK_SEED: u64 = 0;
+
+/// Entrena un agente de Q-Learning en cada mapa `.map` de `dir` con cada
+/// probabilidad de éxito. La política greedy se evalúa cada
+/// `EVALUATION_INTERVAL` episodios desde todas las celdas normales; el retorno
+/// final es el de la última evaluación. Los mapas más grandes que
+/// `N_ROWS × N_COLS` se saltan con un aviso; los más chicos se rellenan con
+/// muros, así que el corpus de markov-dp (`maps/shared`) también sirve.
+pub fn run(dir: &Path, dynamics: &Dynamics) -> io::Result<Vec<BenchmarkResult>> {
+    let mut results = Vec::new();
+
+    for path in map_files(dir)? {
+        let name = path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let keys = match GeneratedMap::from_keys(&parse_keys(&std::fs::read_to_string(&path)?)) {
+            Ok(map) => map.to_keys(KeyFormat::QLearning),
+            Err(error) => {
+                println!("Skipping {}: {}", path.display(), error);
+                continue;
+            }
+        };
+
+        for &prob in &SUCCESS_PROBABILITIES {
+            let mut env = match Environment::from_keys(&keys, prob, dynamics.clone()) {
+                Ok(env) => env,
+                Err(error) => {
+                    println!("Skipping {}: {}", path.display(), error);
+                    break;
+                }
+            };
+            env.reset(Some(BENCHMARK_SEED));
+
+            let evaluation_cells = env.normal_cells();
+            let mut agent = QLearningAgent::for_env(&env);
+            let mut episodes_to_target = None;
+            let mut last = Evaluation::default();
+
+            // Solo se mide el entrenamiento, no las evaluaciones
+            let mut solve_time = Duration::ZERO;
+
+            for episode in 1..=BENCHMARK_EPISODES {
+                let start_time = Instant::now();
+                agent.run_episode(&mut env, DISCOUNT_FACTOR);
+                agent.epsilon *= EPSILON_DECAY;
+                solve_time += start_time.elapsed();
+
+                if episode.is_multiple_of(EVALUATION_INTERVAL) {
+                    last = evaluate(&env, &evaluation_cells, |obs| agent.best_action(obs));
+
+                    if episodes_to_target.is_none() && last.success_rate >= TARGET_SUCCESS_RATE {
+                        episodes_to_target = Some(episode);
+                    }
+                }
+            }
+
+            results.push(BenchmarkResult {
+                map: name.clone(),
+                solver: "Q-Learning".to_string(),
+                success_prob: prob,
+                solve_time,
+                sweeps: None,
+                episodes_to_target,
+                success_rate: last.success_rate,
+                final_return: last.mean_return,
+            });
+        }
+    }
+
+    Ok(results)
+}
@@ -0,0 +1,139 @@
+use crate::{
+    DISCOUNT_FACTOR, EPSILON_DECAY, N_STATES,
+    agent::{EpisodeStats, QLearningAgent},
+    dqn::{DqnAgent, DqnConfig, Encoding, EpsilonSchedule},
+    environment::Environment,
+    optimal::Optimum,
+    planning::Planning,
+};
+
+pub const DQN_EPISODES: usize = 300;
+pub const PLANNING_EPISODES: usize = 300;
+/// Acuerdo con la política óptima a partir del cual se considera que el agente aprendió.
+pub const AGREEMENT_TARGET: f64 = 0.9;
+
+/// Recompensa y pasos por episodio de un agente, para graficar varias curvas juntas.
+#[derive(Debug, Clone)]
+pub struct TrainingCurve {
+    pub agent: String,
+    pub rewards: Vec<f64>,
+    pub steps: Vec<usize>,
+}
+
+impl TrainingCurve {
+    fn new(agent: &str) -> Self {
+        Self {
+            agent: agent.to_string(),
+            rewards: Vec::new(),
+            steps: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, stats: EpisodeStats) {
+        self.rewards.push(stats.total_reward);
+        self.steps.push(stats.steps);
+    }
+}
+
+/// Pasos reales en el entorno que necesitó un agente para alcanzar `AGREEMENT_TARGET`.
+#[derive(Debug, Clone)]
+pub struct SampleEfficiency {
+    pub agent: String,
+    pub steps_to_target: Option<usize>,
+    pub total_steps: usize,
+}
+
+fn dqn_config(encoding: Encoding) -> DqnConfig {
+    DqnConfig {
+        encoding,
+        hidden: vec![64, 64],
+        learning_rate: 1e-3,
+        discount_factor: DISCOUNT_FACTOR,
+        replay_capacity: 10_000,
+        batch_size: 32,
+        target_update: 500,
+        epsilon: EpsilonSchedule {
+            start: 1.0,
+            end: 0.05,
+            decay_steps: 20_000,
+        },
+    }
+}
+
+/// Entrena la Q-table y dos DQN (entrada one-hot y coordenadas) en el mismo
+/// entorno `env` durante `DQN_EPISODES` episodios.
+pub fn dqn_vs_tabular(mut env: Environment) -> Vec<TrainingCurve> {
+    let mut tabular = QLearningAgent::for_env(&env);
+    let mut dqn_one_hot = DqnAgent::new(dqn_config(Encoding::OneHot), 4);
+    let mut dqn_coordinates = DqnAgent::new(dqn_config(Encoding::Coordinates), 4);
+
+    let mut curves = [
+        TrainingCurve::new("Tabular Q-Learning"),
+        TrainingCurve::new("DQN (one-hot)"),
+        TrainingCurve::new("DQN (coordenadas)"),
+    ];
+
+    for _ in 0..DQN_EPISODES {
+        curves[0].push(tabular.run_episode(&mut env, DISCOUNT_FACTOR));
+        curves[1].push(dqn_one_hot.run_episode(&mut env));
+        curves[2].push(dqn_coordinates.run_episode(&mut env));
+
+        tabular.epsilon *= EPSILON_DECAY;
+    }
+
+    curves.to_vec()
+}
+
+/// Compara Q-Learning sin planificación, con experience replay y con Dyna-Q en
+/// el entorno `env`. La eficiencia se mide en pasos reales: cuántas
+/// interacciones con el entorno hacen falta para que la política greedy
+/// coincida con la óptima en `AGREEMENT_TARGET` de los estados.
+pub fn planning_vs_qlearning(mut env: Environment) -> (Vec<TrainingCurve>, Vec<SampleEfficiency>) {
+    let optimum = Optimum::value_iteration(&env, DISCOUNT_FACTOR);
+
+    let variants = [
+        ("Q-Learning", Planning::None),
+        (
+            "Replay (10 por paso)",
+            Planning::Replay {
+                capacity: 10_000,
+                updates: 10,
+            },
+        ),
+        ("Dyna-Q (k = 5)", Planning::DynaQ { steps: 5 }),
+        ("Dyna-Q (k = 50)", Planning::DynaQ { steps: 50 }),
+    ];
+
+    let mut curves = Vec::new();
+    let mut efficiency = Vec::new();
+
+    for (name, planning) in variants {
+        let mut agent = QLearningAgent::with_planning(N_STATES, 4, planning);
+        let mut curve = TrainingCurve::new(name);
+        let mut total_steps = 0;
+        let mut steps_to_target = None;
+
+        for _ in 0..PLANNING_EPISODES {
+            let stats = agent.run_episode(&mut env, DISCOUNT_FACTOR);
+            total_steps += stats.steps;
+
+            let metrics =
+                optimum.metrics(&env, &agent.q_table, stats.start, stats.discounted_return);
+            if steps_to_target.is_none() && metrics.policy_agreement >= AGREEMENT_TARGET {
+                steps_to_target = Some(total_steps);
+            }
+
+            curve.push(stats);
+            agent.epsilon *= EPSILON_DECAY;
+        }
+
+        curves.push(curve);
+        efficiency.push(SampleEfficiency {
+            agent: name.to_string(),
+            steps_to_target,
+            total_steps,
+        });
+    }
+
+    (curves, efficiency)
+}
@@ -0,0 +1,269 @@
+use raylib::prelude::*;
+use utils::{Raylib, argmax_f32};
+
+use crate::{
+    N_COLS, TILE_SIZE,
+    agent::QLearningAgent,
+    environment::Environment,
+    observer::{TrainingObserver, TrainingProgress},
+    state::StatusType,
+};
+
+pub const PANEL_WIDTH: usize = 420;
+
+const HISTORY: usize = 500;
+const ROLLING_WINDOW: usize = 50;
+const CHART_HEIGHT: f32 = 250.0;
+const PANEL_MARGIN: f32 = 20.0;
+
+/// Vista en vivo del entrenamiento: Q-values por celda (un triángulo por acción
+/// sombreado según su valor), flechas de la política greedy y curvas móviles de
+/// recompensa y pasos. Se suscribe a `QLearningAgent::train` como observador.
+pub struct Dashboard<'a> {
+    rlib: &'a mut RaylibHandle,
+    thread: &'a RaylibThread,
+    interval: usize,
+    success_prob: f32,
+}
+
+impl<'a> Dashboard<'a> {
+    pub fn new(
+        rlib: &'a mut RaylibHandle,
+        thread: &'a RaylibThread,
+        interval: usize,
+        success_prob: f32,
+    ) -> Self {
+        Self {
+            rlib,
+            thread,
+            interval,
+            success_prob,
+        }
+    }
+
+    fn draw_q_values(env: &Environment, agent: &QLearningAgent) {
+        let valid_q = env
+            .map
+            .iter()
+            .flatten()
+            .filter(|state| state.r#type == StatusType::Normal)
+            .flat_map(|state| agent.q_table[state.index].iter().copied());
+
+        let (min_q, max_q) = valid_q.fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), q| {
+            (lo.min(q), hi.max(q))
+        });
+
+        for state in env.map.iter().flatten() {
+            if state.r#type != StatusType::Normal {
+                state.draw();
+                continue;
+            }
+
+            let q_values = &agent.q_table[state.index];
+
+            for (action, &q) in q_values.iter().enumerate() {
+                let t = if max_q > min_q {
+                    (q - min_q) / (max_q - min_q)
+                } else {
+                    0.5
+                };
+
+                let [a, b] = Self::tile_edge(state.position, action);
+                Raylib::draw_triangle(a, b, Self::tile_center(state.position), value_color(t));
+            }
+
+            Raylib::draw_rectangle_lines(
+                state.position,
+                Vector2::new(TILE_SIZE, TILE_SIZE),
+                Color::BLACK,
+            );
+
+            Self::draw_arrow(state.position, argmax_f32(q_values));
+        }
+
+        Raylib::draw_robot(env.agent_position, 20.0);
+    }
+
+    /// Vértices del borde de la celda hacia el que mueve cada acción
+    /// (0: arriba, 1: abajo, 2: izquierda, 3: derecha), igual que `Environment::neighbor`.
+    fn tile_edge(position: Vector2, action: usize) -> [Vector2; 2] {
+        let Vector2 { x, y } = position;
+        let (top_left, top_right) = (Vector2::new(x, y), Vector2::new(x + TILE_SIZE, y));
+        let (bottom_left, bottom_right) = (
+            Vector2::new(x, y + TILE_SIZE),
+            Vector2::new(x + TILE_SIZE, y + TILE_SIZE),
+        );
+
+        match action {
+            0 => [top_left, top_right],
+            1 => [bottom_left, bottom_right],
+            2 => [top_left, bottom_left],
+            3 => [top_right, bottom_right],
+            _ => panic!("Invalid action"),
+        }
+    }
+
+    fn tile_center(position: Vector2) -> Vector2 {
+        Vector2::new(position.x + TILE_SIZE / 2.0, position.y + TILE_SIZE / 2.0)
+    }
+
+    fn draw_arrow(position: Vector2, action: usize) {
+        let center = Self::tile_center(position);
+        let direction = match action {
+            0 => Vector2::new(0.0, -1.0),
+            1 => Vector2::new(0.0, 1.0),
+            2 => Vector2::new(-1.0, 0.0),
+            3 => Vector2::new(1.0, 0.0),
+            _ => panic!("Invalid action"),
+        };
+
+        let normal = Vector2::new(-direction.y, direction.x);
+        let tip = center + direction * (TILE_SIZE * 0.3);
+        let base = center + direction * (TILE_SIZE * 0.1);
+
+        Raylib::draw_line(
+            center - direction * (TILE_SIZE * 0.2),
+            base,
+            3.0,
+            Color::BLACK,
+        );
+        Raylib::draw_triangle(
+            tip,
+            base + normal * (TILE_SIZE * 0.1),
+            base - normal * (TILE_SIZE * 0.1),
+            Color::BLACK,
+        );
+    }
+
+    fn draw_panel(progress: &TrainingProgress<'_>, success_prob: f32) {
+        let x = N_COLS as f32 * TILE_SIZE + PANEL_MARGIN;
+        let width = PANEL_WIDTH as f32 - 2.0 * PANEL_MARGIN;
+        let history = progress.history;
+        let agreement = history.metrics.last().map_or("n/a".to_string(), |m| {
+            format!("{:.1}%", m.policy_agreement * 100.0)
+        });
+        let evaluation = history
+            .evaluations
+            .last()
+            .map(|(_, evaluation)| *evaluation)
+            .unwrap_or_default();
+
+        let lines = [
+            format!("P = {:.1}", success_prob),
+            format!("Episode {}/{}", progress.episode, progress.total_episodes),
+            format!("Epsilon: {:.4}", progress.agent.epsilon),
+            format!("Policy agreement: {}", agreement),
+            format!(
+                "Greedy: {:.1}% success, {:.1} steps",
+                evaluation.success_rate * 100.0,
+                evaluation.mean_steps
+            ),
+        ];
+
+        for (i, line) in lines.iter().enumerate() {
+            Raylib::draw_text(
+                line,
+                Vector2::new(x, PANEL_MARGIN + i as f32 * 24.0),
+                Color::BLACK,
+                20,
+            );
+        }
+
+        let steps: Vec<f64> = history.steps.iter().map(|&s| s as f64).collect();
+        let charts = [
+            (
+                "Reward (moving average)",
+                history.rewards.as_slice(),
+                Color::BLUE,
+            ),
+            ("Steps (moving average)", steps.as_slice(), Color::RED),
+        ];
+
+        for (i, (title, data, color)) in charts.into_iter().enumerate() {
+            let y = 150.0 + i as f32 * (CHART_HEIGHT + 2.0 * PANEL_MARGIN + 20.0);
+            draw_rolling_chart(
+                title,
+                data,
+                Vector2::new(x, y),
+                Vector2::new(width, CHART_HEIGHT),
+                color,
+            );
+        }
+    }
+}
+
+impl TrainingObserver for Dashboard<'_> {
+    fn interval(&self) -> usize {
+        self.interval
+    }
+
+    fn on_episode(&mut self, progress: &TrainingProgress<'_>) {
+        if self.rlib.window_should_close() {
+            return;
+        }
+
+        let mut d = self.rlib.begin_drawing(self.thread);
+        d.clear_background(Color::WHITE);
+
+        Self::draw_q_values(progress.env, progress.agent);
+        Self::draw_panel(progress, self.success_prob);
+    }
+
+    fn should_stop(&self) -> bool {
+        self.rlib.window_should_close()
+    }
+}
+
+/// Rojo para los valores más bajos, verde para los más altos.
+fn value_color(t: f32) -> Color {
+    let t = t.clamp(0.0, 1.0);
+    let mix = |low: f32, high: f32| (low + (high - low) * t) as u8;
+
+    Color::new(mix(200.0, 40.0), mix(40.0, 180.0), mix(40.0, 60.0), 255)
+}
+
+/// Dibuja la media móvil de los últimos `HISTORY` episodios de `data`.
+fn draw_rolling_chart(title: &str, data: &[f64], origin: Vector2, size: Vector2, color: Color) {
+    let from = data.len().saturating_sub(HISTORY);
+    let rolling: Vec<f64> = (from..data.len())
+        .map(|i| {
+            let window = &data[(i + 1).saturating_sub(ROLLING_WINDOW)..=i];
+            window.iter().sum::<f64>() / window.len() as f64
+        })
+        .collect();
+
+    Raylib::draw_text(title, origin, Color::BLACK, 18);
+
+    let plot_origin = Vector2::new(origin.x, origin.y + 24.0);
+    Raylib::draw_rectangle(plot_origin, size, Color::WHITESMOKE);
+    Raylib::draw_rectangle_lines(plot_origin, size, Color::GRAY);
+
+    if rolling.len() < 2 {
+        return;
+    }
+
+    let min = rolling.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = rolling.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let range = (max - min).max(f64::EPSILON);
+
+    let to_screen = |i: usize, value: f64| {
+        Vector2::new(
+            plot_origin.x + size.x * i as f32 / (HISTORY - 1) as f32,
+            plot_origin.y + size.y * (1.0 - ((value - min) / range) as f32),
+        )
+    };
+
+    for (i, pair) in rolling.windows(2).enumerate() {
+        Raylib::draw_line(to_screen(i, pair[0]), to_screen(i + 1, pair[1]), 2.0, color);
+    }
+
+    let labels = [(max, plot_origin.y), (min, plot_origin.y + size.y - 14.0)];
+    for (value, y) in labels {
+        Raylib::draw_text(
+            &format!("{value:.2}"),
+            Vector2::new(plot_origin.x + 4.0, y),
+            Color::DARKGRAY,
+            14,
+        );
+    }
+}
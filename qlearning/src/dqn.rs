@@ -0,0 +1,198 @@
+use rand::{random, random_range};
+use utils::{Env, argmax_f32};
+
+use crate::{
+    N_COLS, N_ROWS, N_STATES,
+    agent::{EpisodeStats, Experience},
+    mlp::Mlp,
+    planning::ReplayBuffer,
+};
+
+/// Cómo se le presenta la observación (índice de celda) a la red.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// Vector de `N_STATES` con un 1 en la celda actual.
+    OneHot,
+    /// Fila y columna normalizadas a `[0, 1]`.
+    Coordinates,
+}
+
+/// Epsilon que baja linealmente de `start` a `end` en `decay_steps` pasos.
+#[derive(Debug, Clone, Copy)]
+pub struct EpsilonSchedule {
+    pub start: f64,
+    pub end: f64,
+    pub decay_steps: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct DqnConfig {
+    pub encoding: Encoding,
+    pub hidden: Vec<usize>,
+    pub learning_rate: f32,
+    pub discount_factor: f32,
+    pub replay_capacity: usize,
+    pub batch_size: usize,
+    /// Cada cuántos pasos se copia la red en línea a la red objetivo.
+    pub target_update: usize,
+    pub epsilon: EpsilonSchedule,
+}
+
+/// Deep Q-Network: Q(s,·) lo aproxima un `Mlp`, se entrena con minibatches
+/// muestreados de un buffer de experiencias y los objetivos se calculan con
+/// una copia de la red que solo se actualiza cada `target_update` pasos.
+#[derive(Debug, Clone)]
+pub struct DqnAgent {
+    pub config: DqnConfig,
+    online: Mlp,
+    target: Mlp,
+    replay: ReplayBuffer,
+    steps: usize,
+}
+
+impl Encoding {
+    pub fn size(&self) -> usize {
+        match self {
+            Self::OneHot => N_STATES,
+            Self::Coordinates => 2,
+        }
+    }
+
+    pub fn encode(&self, obs: usize) -> Vec<f32> {
+        match self {
+            Self::OneHot => {
+                let mut input = vec![0.0; N_STATES];
+                input[obs] = 1.0;
+                input
+            }
+            Self::Coordinates => vec![
+                (obs / N_COLS) as f32 / (N_ROWS - 1) as f32,
+                (obs % N_COLS) as f32 / (N_COLS - 1) as f32,
+            ],
+        }
+    }
+}
+
+impl EpsilonSchedule {
+    pub fn value(&self, step: usize) -> f64 {
+        let t = (step as f64 / self.decay_steps.max(1) as f64).min(1.0);
+        self.start + (self.end - self.start) * t
+    }
+}
+
+impl DqnAgent {
+    pub fn new(config: DqnConfig, n_actions: usize) -> Self {
+        let mut sizes = vec![config.encoding.size()];
+        sizes.extend(&config.hidden);
+        sizes.push(n_actions);
+
+        let online = Mlp::new(&sizes, config.learning_rate);
+
+        Self {
+            target: online.clone(),
+            online,
+            replay: ReplayBuffer::new(config.replay_capacity),
+            steps: 0,
+            config,
+        }
+    }
+
+    pub fn epsilon(&self) -> f64 {
+        self.config.epsilon.value(self.steps)
+    }
+
+    pub fn q_values(&self, obs: usize) -> Vec<f32> {
+        self.online.predict(&self.config.encoding.encode(obs))
+    }
+
+    pub fn best_action(&self, obs: usize) -> usize {
+        argmax_f32(&self.q_values(obs))
+    }
+
+    pub fn choose_action(&self, obs: usize) -> usize {
+        if random::<f64>() > self.epsilon() {
+            return self.best_action(obs);
+        }
+
+        random_range(0..4)
+    }
+
+    /// Entrena la red en línea con un minibatch al azar del buffer. Los
+    /// objetivos r + γ·max_a' Q_target(s',a') usan la red objetivo.
+    fn learn(&mut self) -> f32 {
+        let encoding = self.config.encoding;
+        let batch: Vec<(Vec<f32>, usize, f32)> = (0..self.config.batch_size)
+            .filter_map(|_| self.replay.sample())
+            .map(|e| {
+                let future = if e.terminated {
+                    0.0
+                } else {
+                    let next_q = self.target.predict(&encoding.encode(e.next_obs));
+                    next_q.into_iter().fold(f32::NEG_INFINITY, f32::max)
+                };
+
+                let target = e.reward + self.config.discount_factor * future;
+                (encoding.encode(e.obs), e.action, target)
+            })
+            .collect();
+
+        self.online.train(&batch)
+    }
+
+    /// Juega un episodio con la política epsilon-greedy; en cada paso guarda la
+    /// transición y, una vez que el buffer tiene un minibatch, entrena la red.
+    pub fn run_episode<E: Env<Obs = usize>>(&mut self, env: &mut E) -> EpisodeStats {
+        let start = env.reset(None);
+        let mut obs = start;
+
+        let mut steps = 0;
+        let mut total_reward = 0.0_f64;
+        let mut discounted_return = 0.0_f64;
+        let mut discount = 1.0_f64;
+        let mut td_error_sum = 0.0_f64;
+        let mut td_error_max = 0.0_f64;
+
+        loop {
+            let action = self.choose_action(obs);
+            let (next_obs, reward, terminated, truncated, _) = env.step(action);
+
+            self.replay.push(Experience {
+                obs,
+                action,
+                reward,
+                next_obs,
+                terminated,
+            });
+            self.steps += 1;
+
+            if self.replay.len() >= self.config.batch_size {
+                let td_error = self.learn() as f64;
+                td_error_sum += td_error;
+                td_error_max = td_error_max.max(td_error);
+            }
+
+            if self.steps.is_multiple_of(self.config.target_update) {
+                self.target = self.online.clone();
+            }
+
+            obs = next_obs;
+            total_reward += reward as f64;
+            discounted_return += discount * reward as f64;
+            discount *= self.config.discount_factor as f64;
+            steps += 1;
+
+            if terminated || truncated {
+                break;
+            }
+        }
+
+        EpisodeStats {
+            start,
+            steps,
+            total_reward,
+            discounted_return,
+            td_error_mean: td_error_sum / steps as f64,
+            td_error_max,
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use crate::{
+    N_COLS, TILE_SIZE,
+    state::{State, StatusType},
+};
+
+/// Modelo de transición del entorno: qué ocurre cuando la acción elegida no se
+/// ejecuta con éxito (probabilidad `1 - success_prob`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Dynamics {
+    /// Si la acción falla el agente no se mueve.
+    StayOnFailure,
+    /// Si la acción falla el agente se desliza hacia uno de los dos lados,
+    /// cada uno con la mitad de la probabilidad de fallo (como en markov-dp).
+    LateralSlip,
+    /// Si la acción falla se ejecuta una de las cuatro acciones al azar.
+    UniformRandom,
+    /// Como `StayOnFailure`, pero después del movimiento el viento puede empujar
+    /// al agente una celda más según la columna en la que quedó.
+    Wind(WindField),
+}
+
+/// Campo de viento por columnas: `strength[col]` es la probabilidad de que el
+/// agente sea empujado una celda en `direction` al terminar en esa columna.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindField {
+    pub direction: usize,
+    pub strength: Vec<f32>,
+}
+
+impl Dynamics {
+    /// Lee el modelo por nombre: `stay`, `slip`, `uniform` o `wind`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "stay" => Some(Self::StayOnFailure),
+            "slip" => Some(Self::LateralSlip),
+            "uniform" => Some(Self::UniformRandom),
+            "wind" => Some(Self::Wind(WindField::default())),
+            _ => None,
+        }
+    }
+
+    /// Distribución de las acciones que efectivamente se ejecutan al elegir
+    /// `action`. `None` significa que el agente no se mueve.
+    pub fn action_outcomes(&self, action: usize, success_prob: f32) -> Vec<(f32, Option<usize>)> {
+        let failure_prob = 1.0 - success_prob;
+
+        match self {
+            Self::StayOnFailure | Self::Wind(_) => {
+                vec![(success_prob, Some(action)), (failure_prob, None)]
+            }
+            Self::LateralSlip => {
+                let [left, right] = lateral_actions(action);
+
+                vec![
+                    (success_prob, Some(action)),
+                    (failure_prob / 2.0, Some(left)),
+                    (failure_prob / 2.0, Some(right)),
+                ]
+            }
+            Self::UniformRandom => {
+                let mut outcomes = vec![(success_prob, Some(action))];
+                outcomes.extend((0..4).map(|a| (failure_prob / 4.0, Some(a))));
+                outcomes
+            }
+        }
+    }
+
+    /// Empuje adicional `(probabilidad, acción)` al terminar el movimiento en
+    /// `state`. En la meta el episodio termina, así que el viento no la mueve.
+    pub fn drift(&self, state: &State) -> Option<(f32, usize)> {
+        match self {
+            _ if state.r#type == StatusType::Goal => None,
+            Self::Wind(field) => {
+                let col = (state.position.x / TILE_SIZE) as usize;
+                let strength = field.strength.get(col).copied().unwrap_or(0.0);
+
+                (strength > 0.0).then_some((strength, field.direction))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Default for WindField {
+    /// Viento hacia el norte, más fuerte en las columnas centrales del mapa.
+    fn default() -> Self {
+        let strength = (0..N_COLS)
+            .map(|col| match col {
+                6..=8 => 0.3,
+                3..=5 | 9..=11 => 0.15,
+                _ => 0.0,
+            })
+            .collect();
+
+        Self {
+            direction: 0,
+            strength,
+        }
+    }
+}
+
+/// Acciones perpendiculares a `action` (0: arriba, 1: abajo, 2: izquierda, 3: derecha).
+fn lateral_actions(action: usize) -> [usize; 2] {
+    match action {
+        0 | 1 => [2, 3],
+        2 | 3 => [0, 1],
+        _ => panic!("Invalid action"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SUCCESS_PROBABILITIES, environment::Environment};
+
+    #[test]
+    fn action_outcomes_sum_to_one() {
+        let models = ["stay", "slip", "uniform", "wind"].map(Dynamics::from_name);
+
+        for dynamics in models.into_iter().flatten() {
+            for &success_prob in &SUCCESS_PROBABILITIES {
+                for action in 0..4 {
+                    let outcomes = dynamics.action_outcomes(action, success_prob);
+                    let sum: f32 = outcomes.iter().map(|(prob, _)| prob).sum();
+
+                    assert!(
+                        (sum - 1.0).abs() < 1e-6,
+                        "{dynamics:?}, action {action}: outcomes sum to {sum}"
+                    );
+                    assert!(outcomes.iter().all(|&(prob, _)| prob >= 0.0));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn transitions_sum_to_one_in_every_state() {
+        let models = ["stay", "slip", "uniform", "wind"].map(Dynamics::from_name);
+
+        for dynamics in models.into_iter().flatten() {
+            for &success_prob in &SUCCESS_PROBABILITIES {
+                let env = Environment::with_dynamics(success_prob, dynamics.clone());
+
+                for state in env.map.iter().flatten() {
+                    if state.r#type == StatusType::Wall {
+                        continue;
+                    }
+
+                    for action in 0..4 {
+                        let sum: f32 = env.transitions(state, action).iter().map(|t| t.prob).sum();
+
+                        assert!(
+                            (sum - 1.0).abs() < 1e-5,
+                            "{dynamics:?}, state {}, action {action}: sums to {sum}",
+                            state.index
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn wind_does_not_blow_the_agent_off_the_goal() {
+        let env = Environment::with_dynamics(1.0, Dynamics::Wind(WindField::default()));
+        let goal = env.goal().index;
+        let left = env.get_state(goal / N_COLS, goal % N_COLS - 1);
+
+        // Desde la izquierda de la meta, moverse al Este siempre termina en ella
+        let transitions = env.transitions(left, 3);
+        let reached: f32 = transitions
+            .iter()
+            .filter(|t| t.next_state.r#type == StatusType::Goal)
+            .map(|t| t.prob)
+            .sum();
+
+        assert!(
+            (reached - 1.0).abs() < 1e-6,
+            "reached the goal with {reached}"
+        );
+    }
+
+    #[test]
+    fn slips_are_perpendicular_to_the_chosen_action() {
+        for action in 0..4 {
+            let outcomes = Dynamics::LateralSlip.action_outcomes(action, 0.8);
+
+            assert_eq!(outcomes[0], (0.8, Some(action)));
+            for &(_, executed) in &outcomes[1..] {
+                let executed = executed.unwrap();
+                assert_ne!(executed / 2, action / 2, "slipped along the chosen axis");
+            }
+        }
+    }
+}
@@ -1,29 +1,84 @@
-use std::{
-    fs::File,
-    io::{BufWriter, Write},
-};
+use std::collections::VecDeque;
 
 use crate::{
-    DISCOUNT_FACTOR, EPISODES, EPSILON_DECAY, LEARNING_RATE, MAX_STEPS, N_COLS, N_ROWS, N_STATES,
-    RAW_MAP, TILE_SIZE,
+    BUMP_PENALTY, FAR_START_DISTANCE, MAX_STEPS, N_COLS, N_ROWS, N_STATES, RAW_MAP, TILE_SIZE,
+    dynamics::Dynamics,
     state::{State, StatusType},
 };
 
-use rand::{random, random_range};
+use rand::{Rng, SeedableRng, rngs::StdRng};
 use raylib::prelude::*;
-use utils::{Raylib, argmax_f32, num_to_direction};
+use utils::{
+    Discrete, Env, Raylib, Step, StepInfo,
+    dynamic::{self, DynamicMap},
+};
 
+/// Estado del mundo: mapa, posición del agente y dinámica de transición.
+/// Los agentes (ver `agent.rs`) interactúan con él a través del trait `Env`.
 #[derive(Debug, Clone)]
 pub struct Environment {
     pub map: Vec<Vec<State>>,
     pub agent_position: Vector2,
-    pub q_table: Vec<Vec<f32>>,
     pub success_prob: f32,
-    pub epsilon: f64,
+    pub dynamics: Dynamics,
+    pub bump_penalty: f32,
+    pub max_steps: usize,
+    pub start: StartDistribution,
+    /// Obstáculos, puertas y peligros que cambian con el tiempo. El reloj
+    /// (`time`) avanza en cada paso y no se reinicia entre episodios.
+    pub schedule: Option<DynamicMap>,
+    pub time: usize,
+    base_map: Vec<Vec<State>>,
+    steps: usize,
+    rng: StdRng,
+}
+
+/// Resultado posible de ejecutar una acción: con probabilidad `prob` el agente
+/// termina en `next_state` y recibe `reward`.
+#[derive(Debug, Clone)]
+pub struct Transition<'a> {
+    pub prob: f32,
+    pub next_state: &'a State,
+    pub reward: f32,
+    pub bumped: bool,
+}
+
+/// Distribución de la celda inicial de cada episodio.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StartDistribution {
+    /// Siempre la misma celda (índice de estado).
+    Fixed(usize),
+    /// Cualquier celda normal con la misma probabilidad.
+    Uniform,
+    /// Celdas normales a al menos `min_distance` pasos de la meta por el
+    /// camino más corto.
+    FarFromGoal { min_distance: usize },
+    /// Una de las celdas dadas, al azar.
+    Cells(Vec<usize>),
+}
+
+impl StartDistribution {
+    /// Lee la distribución por nombre: `uniform`, `far` o `fixed:<índice>`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "uniform" => Some(Self::Uniform),
+            "far" => Some(Self::FarFromGoal {
+                min_distance: FAR_START_DISTANCE,
+            }),
+            _ => name
+                .strip_prefix("fixed:")
+                .and_then(|index| index.parse().ok())
+                .map(Self::Fixed),
+        }
+    }
 }
 
 impl Environment {
     pub fn new(success_prob: f32) -> Self {
+        Self::with_dynamics(success_prob, Dynamics::StayOnFailure)
+    }
+
+    pub fn with_dynamics(success_prob: f32, dynamics: Dynamics) -> Self {
         let mut map = Vec::new();
 
         for (i, row) in RAW_MAP.iter().enumerate() {
@@ -36,44 +91,92 @@ impl Environment {
             map.push(map_row);
         }
 
-        Self {
-            map,
-            agent_position: Vector2::default(),
-            q_table: vec![vec![0.0; 4]; N_STATES],
-            success_prob,
-            epsilon: 0.1_f64,
-        }
+        Self::from_map(map, success_prob, dynamics)
     }
 
-    pub fn reset_agent(&mut self) -> State {
-        let state = self.get_random_state();
-        self.agent_position = state.position;
+    /// Entorno sobre un mapa leído de un archivo o generado con
+    /// `utils::mapgen`, en el mismo formato de claves que `RAW_MAP`. El mapa
+    /// debe tener una sola meta y medir a lo sumo `N_ROWS × N_COLS`; uno más
+    /// chico (como los de markov-dp) queda en la esquina superior izquierda y
+    /// el resto se rellena con muros.
+    pub fn from_keys(
+        keys: &[Vec<String>],
+        success_prob: f32,
+        dynamics: Dynamics,
+    ) -> Result<Self, String> {
+        let width = keys.first().map_or(0, Vec::len);
+
+        if keys.is_empty()
+            || keys.len() > N_ROWS
+            || width > N_COLS
+            || keys.iter().any(|row| row.len() != width)
+        {
+            return Err(format!(
+                "The map must be rectangular and at most {N_ROWS}x{N_COLS}"
+            ));
+        }
 
-        state
-    }
+        let mut map = Vec::new();
+        let mut goals = 0;
+        let padding = "W".to_string();
+
+        for i in 0..N_ROWS {
+            let mut map_row = Vec::new();
+
+            for j in 0..N_COLS {
+                let state_key = keys.get(i).and_then(|row| row.get(j)).unwrap_or(&padding);
+
+                match state_key.chars().next() {
+                    Some('G') => goals += 1,
+                    Some('S' | 'W') => {}
+                    _ => return Err(format!("Invalid state key: {state_key}")),
+                }
 
-    pub fn choose_action(&self, state: &State) -> usize {
-        if random::<f64>() > self.epsilon {
-            return self.get_best_action(state);
+                map_row.push(State::new(state_key, i, j));
+            }
+
+            map.push(map_row);
         }
 
-        random_range::<usize, _>(0..4)
+        if goals != 1 {
+            return Err(format!("The map must have exactly one goal, found {goals}"));
+        }
+
+        Ok(Self::from_map(map, success_prob, dynamics))
     }
 
-    pub fn get_best_action(&self, state: &State) -> usize {
-        let state_rewards = &self.q_table[state.index];
-        argmax_f32(state_rewards)
+    fn from_map(map: Vec<Vec<State>>, success_prob: f32, dynamics: Dynamics) -> Self {
+        Self {
+            base_map: map.clone(),
+            map,
+            agent_position: Vector2::default(),
+            success_prob,
+            dynamics,
+            bump_penalty: BUMP_PENALTY,
+            max_steps: MAX_STEPS,
+            start: StartDistribution::Uniform,
+            schedule: None,
+            time: 0,
+            steps: 0,
+            rng: StdRng::from_os_rng(),
+        }
     }
 
     pub fn get_state(&self, i: usize, j: usize) -> &State {
         &self.map[i][j]
     }
 
-    pub fn step(&mut self, current_state: &State, action: usize) -> State {
-        let mut row = (self.agent_position.y / TILE_SIZE) as isize;
-        let mut col = (self.agent_position.x / TILE_SIZE) as isize;
+    pub fn current_state(&self) -> &State {
+        self.get_state_at(self.agent_position)
+    }
+
+    /// Celda vecina a `state` en la dirección de `action`, o `None` si el
+    /// movimiento choca con un muro o sale del mapa.
+    pub fn neighbor(&self, state: &State, action: usize) -> Option<&State> {
+        let mut row = (state.position.y / TILE_SIZE) as isize;
+        let mut col = (state.position.x / TILE_SIZE) as isize;
 
-        let mut r#move = |action: usize| match action {
+        match action {
             0 => row -= 1,
             1 => row += 1,
             2 => col -= 1,
@@ -81,138 +184,176 @@ impl Environment {
             _ => panic!("Invalid action"),
         };
 
-        if random::<f32>() <= self.success_prob {
-            r#move(action);
-        }
-
         if row < 0 || col < 0 || row >= N_ROWS as isize || col >= N_COLS as isize {
-            return current_state.clone();
+            return None;
         }
 
-        if self.get_state(row as usize, col as usize).r#type == StatusType::Wall {
-            return current_state.clone();
-        }
+        let target = self.get_state(row as usize, col as usize);
 
-        self.agent_position = Vector2 {
-            x: col as f32 * TILE_SIZE,
-            y: row as f32 * TILE_SIZE,
-        };
-
-        self.get_state(row as usize, col as usize).clone()
+        (target.r#type != StatusType::Wall).then_some(target)
     }
 
-    /// Actualiza la Q-table usando la ecuación de Bellman para Q-Learning.
+    /// Distribución de transiciones al elegir `action` en `state` según el
+    /// modelo de `self.dynamics`. `Env::step` muestrea de esta misma distribución,
+    /// y `Optimum` la usa como modelo exacto.
     ///
-    /// Esta función implementa el núcleo del algoritmo Q-Learning, que permite al robot
-    /// aprender la política óptima a través de la experiencia. La actualización sigue
-    /// la ecuación: Q(s,a) = Q(s,a) + α[r + γ*max_a'Q(s',a') - Q(s,a)]
+    /// La recompensa es la de la celda donde termina el agente; si el movimiento
+    /// choca con un muro o el borde se suma además `bump_penalty`.
+    pub fn transitions(&self, state: &State, action: usize) -> Vec<Transition<'_>> {
+        let current = self.get_state_at(state.position);
+        let mut transitions = Vec::new();
+
+        for (prob, executed) in self.dynamics.action_outcomes(action, self.success_prob) {
+            let (landed, bumped) = match executed {
+                Some(executed) => match self.neighbor(current, executed) {
+                    Some(next) => (next, false),
+                    None => (current, true),
+                },
+                None => (current, false),
+            };
+
+            let penalty = if bumped { self.bump_penalty } else { 0.0 };
+
+            let mut outcomes = vec![(prob, landed)];
+            if let Some((strength, direction)) = self.dynamics.drift(landed) {
+                let pushed = self.neighbor(landed, direction).unwrap_or(landed);
+                outcomes = vec![(prob * (1.0 - strength), landed), (prob * strength, pushed)];
+            }
 
-    /// LEARNING_RATE: qué tan rápido aprende el robot.
-    /// a más alto, más rápido se adapta a nuevas experiencias.
-    ///
-    /// DISCOUNT_FACTOR: Factor de descuento que determina la importancia
-    /// de las recompensas futuras vs inmediatas.
-    /// a mayor valor, el robot valora más las recompensas futuras
-    ///
-    /// r (reward): Recompensa inmediata obtenida al llegar al next_state.
-    /// Guía al robot sobre qué tan bueno o malo es estar en ese estado.
-    ///
-    /// max_future_q: El máximo valor Q esperado desde el siguiente estado,
-    /// representando la mejor acción posible que el robot puede tomar desde ahí.
-    /// Esto permite que el robot considere las consecuencias futuras de sus acciones.
-    pub fn update_q_table(&mut self, current_state: &State, action: usize, next_state: &State) {
-        let max_future_q_index = argmax_f32(&self.q_table[next_state.index]);
-        let max_future_q = self.q_table[next_state.index][max_future_q_index];
-        let old_q = self.q_table[current_state.index][action];
+            transitions.extend(outcomes.into_iter().map(|(prob, next_state)| Transition {
+                prob,
+                next_state,
+                reward: next_state.reward + penalty,
+                bumped,
+            }));
+        }
 
-        // Formula: Q_nuevo = (1-α)*Q_viejo + α*(recompensa + γ*mejor_Q_futuro)
-        // El balance entre estos términos permite al robot aprender gradualmente
-        // sin olvidar completamente lo que ya sabía
-        self.q_table[current_state.index][action] = (1.0 - LEARNING_RATE) * old_q
-            + LEARNING_RATE * (next_state.reward + DISCOUNT_FACTOR * max_future_q);
+        transitions
     }
 
-    pub fn run(&mut self) -> (Vec<f64>, Vec<usize>) {
-        let mut rewards = Vec::new();
-        let mut policies = Vec::new();
-        let mut steps_per_episode = Vec::new();
+    fn get_state_at(&self, position: Vector2) -> &State {
+        let row = (position.y / TILE_SIZE) as usize;
+        let col = (position.x / TILE_SIZE) as usize;
 
-        for episode in 0..EPISODES {
-            let mut current_state = self.reset_agent();
+        self.get_state(row, col)
+    }
 
-            let mut steps = 0;
-            let mut total_reward = 0.0_f64;
+    /// La meta del mapa. `from_keys` y `RAW_MAP` garantizan que hay una sola.
+    pub fn goal(&self) -> &State {
+        self.map
+            .iter()
+            .flatten()
+            .find(|state| state.r#type == StatusType::Goal)
+            .expect("The map has no goal")
+    }
 
-            for _ in 0..MAX_STEPS {
-                let action = self.choose_action(&current_state);
-                let next_state = self.step(&current_state, action);
+    /// Celdas normales (no muro ni meta), en orden de índice.
+    pub fn normal_cells(&self) -> Vec<usize> {
+        self.map
+            .iter()
+            .flatten()
+            .filter(|state| state.r#type == StatusType::Normal)
+            .map(|state| state.index)
+            .collect()
+    }
+
+    /// Largo del camino más corto (en movimientos exitosos) desde cada celda a
+    /// la meta, o `None` si no se puede llegar.
+    pub fn distances_to_goal(&self) -> Vec<Option<usize>> {
+        let mut distances = vec![None; N_STATES];
+        let mut queue = VecDeque::new();
 
-                self.update_q_table(&current_state, action, &next_state);
+        for state in self.map.iter().flatten() {
+            if state.r#type == StatusType::Goal {
+                distances[state.index] = Some(0);
+                queue.push_back(state);
+            }
+        }
 
-                current_state = next_state.clone();
-                total_reward += next_state.reward as f64;
-                steps += 1;
+        // Los movimientos son simétricos, así que se puede recorrer desde la meta
+        while let Some(state) = queue.pop_front() {
+            let distance = distances[state.index].unwrap_or_default();
 
-                if current_state.r#type == StatusType::Goal {
-                    break;
+            for action in 0..4 {
+                if let Some(next) = self.neighbor(state, action)
+                    && distances[next.index].is_none()
+                {
+                    distances[next.index] = Some(distance + 1);
+                    queue.push_back(next);
                 }
             }
+        }
 
-            let policy: Vec<usize> = self
-                .q_table
-                .iter()
-                .map(|actions| argmax_f32(actions))
-                .collect();
+        distances
+    }
 
-            policies.push(policy);
-            rewards.push(total_reward);
-            steps_per_episode.push(steps);
+    /// Celdas posibles de inicio según `self.start`.
+    pub fn start_cells(&self) -> Vec<usize> {
+        match &self.start {
+            StartDistribution::Fixed(index) => vec![*index],
+            StartDistribution::Uniform => self.normal_cells(),
+            StartDistribution::FarFromGoal { min_distance } => {
+                let distances = self.distances_to_goal();
+
+                self.normal_cells()
+                    .into_iter()
+                    .filter(|&index| distances[index].is_some_and(|d| d >= *min_distance))
+                    .collect()
+            }
+            StartDistribution::Cells(cells) => cells.clone(),
+        }
+    }
 
-            self.epsilon *= EPSILON_DECAY;
+    /// Comprueba que `self.start` deje al menos una celda de inicio en este
+    /// mapa y que todas sean celdas normales.
+    pub fn check_start(&self) -> Result<(), String> {
+        let cells = self.start_cells();
+        let normal = self.normal_cells();
 
-            println!(
-                "Episode {}: Steps: {}, Total Reward: {}, Epsilon: {}",
-                episode + 1,
-                steps,
-                total_reward,
-                self.epsilon
-            );
+        if cells.is_empty() {
+            return Err(format!("No start cells for {:?} on this map", self.start));
+        }
 
-            self.draw();
+        match cells.iter().find(|index| !normal.contains(index)) {
+            Some(index) => Err(format!("Start cell {index} is not a normal cell")),
+            None => Ok(()),
         }
+    }
 
-        let mut file = BufWriter::new(File::create("results.txt").unwrap());
-        for (i, (policy, reward)) in policies.iter().zip(rewards.iter()).enumerate() {
-            let policy_str: String = policy
-                .iter()
-                .map(|a| format!("{}", num_to_direction(*a)))
-                .collect::<Vec<_>>()
-                .join(",");
+    /// Celda inicial al azar según `self.start`. Si no hay ninguna entra en
+    /// pánico: ver `check_start`.
+    pub fn get_random_state(&mut self) -> State {
+        let cells = self.start_cells();
+        let index = cells[self.rng.random_range(0..cells.len())];
 
-            writeln!(
-                file,
-                "Episode {}: Policy: [{}], Reward: {}",
-                i + 1,
-                policy_str,
-                reward
-            )
-            .unwrap();
-        }
+        self.get_state(index / N_COLS, index % N_COLS).clone()
+    }
+
+    /// Reinicia el episodio con el agente en la celda `index`.
+    pub fn reset_to(&mut self, index: usize) -> usize {
+        self.agent_position = self.get_state(index / N_COLS, index % N_COLS).position;
+        self.steps = 0;
 
-        (rewards, steps_per_episode)
+        index
     }
 
-    pub fn get_random_state(&self) -> State {
-        loop {
-            let i = random_range(0..N_ROWS);
-            let j = random_range(0..N_COLS);
+    /// Avanza el reloj y rehace `map` a partir del mapa base con los elementos
+    /// de `schedule` en ese instante. La celda del agente y la meta nunca se
+    /// bloquean.
+    fn tick(&mut self) {
+        let Some(schedule) = &self.schedule else {
+            return;
+        };
 
-            let state = self.map[i][j].clone();
+        self.time += 1;
+        let agent = self.current_state().index;
 
-            if state.r#type != StatusType::Wall {
-                return state;
-            }
-        }
+        dynamic::apply_snapshot(
+            &mut self.map,
+            &self.base_map,
+            &schedule.at(self.time),
+            (agent / N_COLS, agent % N_COLS),
+        );
     }
 
     pub fn draw(&self) {
@@ -225,3 +366,247 @@ impl Environment {
         Raylib::draw_robot(self.agent_position, 20.0);
     }
 }
+
+impl Env for Environment {
+    type Obs = usize;
+
+    fn reset(&mut self, seed: Option<u64>) -> usize {
+        if let Some(seed) = seed {
+            self.rng = StdRng::seed_from_u64(seed);
+        }
+
+        let index = self.get_random_state().index;
+        self.reset_to(index)
+    }
+
+    fn step(&mut self, action: usize) -> Step<usize> {
+        self.tick();
+
+        let mut roll = self.rng.random::<f32>();
+        let transitions = self.transitions(self.current_state(), action);
+
+        let chosen = transitions
+            .iter()
+            .find(|transition| {
+                roll -= transition.prob;
+                roll < 0.0
+            })
+            .unwrap_or(&transitions[transitions.len() - 1]);
+
+        let (next_state, reward, bumped) = (chosen.next_state, chosen.reward, chosen.bumped);
+        let (index, position) = (next_state.index, next_state.position);
+        let terminated = next_state.r#type == StatusType::Goal;
+
+        self.agent_position = position;
+        self.steps += 1;
+
+        let truncated = !terminated && self.steps >= self.max_steps;
+        let info = StepInfo {
+            steps: self.steps,
+            bumped,
+        };
+
+        (index, reward, terminated, truncated, info)
+    }
+
+    fn action_space(&self) -> Discrete {
+        Discrete { n: 4 }
+    }
+
+    fn observation_space(&self) -> Discrete {
+        Discrete { n: N_STATES }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use utils::mapgen::{Cell, KeyFormat, Layout, MapGenerator, parse_keys};
+
+    use super::*;
+    use crate::SUCCESS_PROBABILITIES;
+
+    /// Mapa de puros muros salvo las celdas indicadas.
+    pub(crate) fn env_with(cells: &[((usize, usize), &str)], success_prob: f32) -> Environment {
+        let mut keys = vec![vec!["W".to_string(); N_COLS]; N_ROWS];
+
+        for &((row, col), key) in cells {
+            keys[row][col] = key.to_string();
+        }
+
+        Environment::from_keys(&keys, success_prob, Dynamics::StayOnFailure).unwrap()
+    }
+
+    #[test]
+    fn distances_follow_the_shortest_path() {
+        // Un pasillo en L hasta la meta y una celda encerrada
+        let env = env_with(
+            &[
+                ((0, 0), "S0"),
+                ((0, 1), "S1"),
+                ((1, 1), "S2"),
+                ((2, 1), "G"),
+                ((5, 5), "S3"),
+            ],
+            1.0,
+        );
+        let distances = env.distances_to_goal();
+
+        assert_eq!(distances[0], Some(3));
+        assert_eq!(distances[1], Some(2));
+        assert_eq!(distances[N_COLS + 1], Some(1));
+        assert_eq!(distances[2 * N_COLS + 1], Some(0));
+        assert_eq!(distances[5 * N_COLS + 5], None);
+        assert_eq!(distances[2], None, "walls are unreachable");
+    }
+
+    #[test]
+    fn distances_on_the_default_map() {
+        let env = Environment::new(1.0);
+        let distances = env.distances_to_goal();
+        let goal = env.goal().index;
+
+        assert_eq!(distances[goal], Some(0));
+        assert_eq!(distances[goal - 1], Some(1));
+        assert_eq!(distances[goal + N_COLS], None, "wall below the goal");
+
+        // Todas las celdas normales llegan a la meta
+        for index in env.normal_cells() {
+            assert!(distances[index].is_some(), "cell {index} is unreachable");
+        }
+    }
+
+    #[test]
+    fn generated_maps_load_from_text() {
+        let layouts = [
+            "dfs",
+            "prim",
+            "obstacles:0",
+            "obstacles:0.4",
+            "obstacles:1",
+            "rooms:3",
+        ];
+
+        for (layout, seed) in layouts
+            .iter()
+            .flat_map(|layout| (0..5).map(move |s| (layout, s)))
+        {
+            let generated = MapGenerator {
+                danger_patches: 2,
+                ..MapGenerator::new(N_ROWS, N_COLS, Layout::from_name(layout).unwrap(), seed)
+            }
+            .generate();
+            let keys = parse_keys(&generated.to_text(KeyFormat::QLearning));
+            let env = Environment::from_keys(&keys, 1.0, Dynamics::StayOnFailure).unwrap();
+            let distances = env.distances_to_goal();
+
+            let (row, col) = generated.goal;
+            assert_eq!(env.goal().index, row * N_COLS + col);
+
+            for state in env.map.iter().flatten() {
+                let cell = generated.cells[state.index / N_COLS][state.index % N_COLS];
+                assert_eq!(
+                    state.r#type == StatusType::Wall,
+                    cell == Cell::Wall,
+                    "{layout} with seed {seed}, cell {}",
+                    state.index
+                );
+                assert_eq!(distances[state.index].is_some(), cell != Cell::Wall);
+            }
+        }
+    }
+
+    #[test]
+    fn smaller_maps_are_padded_with_walls() {
+        let keys = parse_keys("S0 S1 G\nS2 W0 S3\n");
+        let env = Environment::from_keys(&keys, 1.0, Dynamics::StayOnFailure).unwrap();
+
+        assert_eq!(env.normal_cells(), vec![0, 1, N_COLS, N_COLS + 2]);
+        assert_eq!(env.goal().index, 2);
+        assert_eq!(env.distances_to_goal()[N_COLS], Some(3));
+        assert!(env.get_state(0, 3).r#type == StatusType::Wall);
+
+        let too_wide = vec![vec!["S".to_string(); N_COLS + 1]; 2];
+        assert!(Environment::from_keys(&too_wide, 1.0, Dynamics::StayOnFailure).is_err());
+    }
+
+    #[test]
+    fn start_distributions_parse_by_name() {
+        assert_eq!(
+            StartDistribution::from_name("uniform"),
+            Some(StartDistribution::Uniform)
+        );
+        assert_eq!(
+            StartDistribution::from_name("far"),
+            Some(StartDistribution::FarFromGoal {
+                min_distance: FAR_START_DISTANCE
+            })
+        );
+        assert_eq!(
+            StartDistribution::from_name("fixed:12"),
+            Some(StartDistribution::Fixed(12))
+        );
+        assert_eq!(StartDistribution::from_name("fixed:x"), None);
+        assert_eq!(StartDistribution::from_name("center"), None);
+    }
+
+    #[test]
+    fn episodes_start_only_in_the_start_cells() {
+        let mut env = Environment::new(1.0);
+        let distances = env.distances_to_goal();
+
+        env.start = StartDistribution::FarFromGoal { min_distance: 10 };
+        let far = env.start_cells();
+        assert!(!far.is_empty());
+        assert!(far.iter().all(|&index| distances[index] >= Some(10)));
+
+        env.start = StartDistribution::Cells(vec![0, 14]);
+        assert_eq!(env.start_cells(), vec![0, 14]);
+
+        env.reset(Some(0));
+        for _ in 0..100 {
+            let index = env.reset(None);
+            assert!(index == 0 || index == 14, "started at {index}");
+        }
+
+        env.start = StartDistribution::Uniform;
+        let normal = env.normal_cells();
+        assert_eq!(env.start_cells(), normal);
+        for _ in 0..100 {
+            assert!(normal.contains(&env.reset(None)));
+        }
+    }
+
+    #[test]
+    fn empty_or_invalid_start_sets_are_rejected() {
+        let mut env = env_with(&[((0, 0), "S0"), ((0, 1), "S1"), ((0, 2), "G")], 1.0);
+        assert!(env.check_start().is_ok());
+
+        env.start = StartDistribution::FarFromGoal {
+            min_distance: FAR_START_DISTANCE,
+        };
+        assert!(env.check_start().is_err());
+
+        env.start = StartDistribution::Cells(vec![]);
+        assert!(env.check_start().is_err());
+
+        env.start = StartDistribution::Cells(vec![0, 2]);
+        assert!(env.check_start().is_err(), "the goal is not a start cell");
+
+        env.start = StartDistribution::Fixed(3);
+        assert!(env.check_start().is_err(), "cell 3 is a wall");
+
+        env.start = StartDistribution::Fixed(1);
+        assert!(env.check_start().is_ok());
+    }
+
+    #[test]
+    fn reset_with_a_seed_is_reproducible() {
+        let mut env = Environment::new(SUCCESS_PROBABILITIES[0]);
+        let mut other = Environment::new(SUCCESS_PROBABILITIES[0]);
+
+        assert_eq!(env.reset(Some(7)), other.reset(Some(7)));
+        for _ in 0..50 {
+            assert_eq!(env.step(3).0, other.step(3).0);
+        }
+    }
+}
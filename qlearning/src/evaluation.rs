@@ -0,0 +1,57 @@
+use utils::Env;
+
+use crate::environment::Environment;
+
+/// Cada cuántos episodios de entrenamiento se evalúa la política greedy.
+pub const EVALUATION_INTERVAL: usize = 100;
+/// Límite de pasos de cada episodio de evaluación.
+pub const EVALUATION_MAX_STEPS: usize = 200;
+
+/// Desempeño de la política greedy (ε = 0) desde un conjunto de celdas.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Evaluation {
+    pub success_rate: f64,
+    /// Pasos medios por episodio; los que no llegan cuentan `EVALUATION_MAX_STEPS`.
+    pub mean_steps: f64,
+    /// Recompensa total media por episodio, sin descontar.
+    pub mean_return: f64,
+}
+
+/// Ejecuta la política `policy` una vez desde cada celda de `cells`, cortando
+/// a los `EVALUATION_MAX_STEPS` pasos. No aprende nada: sirve para que las
+/// curvas midan la calidad de la política y no el ruido de la exploración.
+///
+/// Corre sobre una copia de `env`: evaluar no consume el rng del entrenamiento
+/// ni avanza el mapa dinámico.
+pub fn evaluate(env: &Environment, cells: &[usize], policy: impl Fn(usize) -> usize) -> Evaluation {
+    let mut env = env.clone();
+    let mut successes = 0;
+    let mut total_steps = 0;
+    let mut total_reward = 0.0_f64;
+
+    for &cell in cells {
+        let mut obs = env.reset_to(cell);
+
+        for step in 1..=EVALUATION_MAX_STEPS {
+            let (next_obs, reward, terminated, _, _) = env.step(policy(obs));
+            obs = next_obs;
+            total_reward += reward as f64;
+
+            if terminated {
+                successes += 1;
+                total_steps += step;
+                break;
+            }
+
+            if step == EVALUATION_MAX_STEPS {
+                total_steps += step;
+            }
+        }
+    }
+
+    Evaluation {
+        success_rate: successes as f64 / cells.len().max(1) as f64,
+        mean_steps: total_steps as f64 / cells.len().max(1) as f64,
+        mean_return: total_reward / cells.len().max(1) as f64,
+    }
+}
@@ -0,0 +1,99 @@
+use crate::{N_COLS, N_ROWS, N_STATES};
+
+/// Familia de características sobre la posición `(fila, columna)` de una celda.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Feature {
+    /// Un indicador por celda; equivale a la Q-table y no generaliza.
+    OneHot,
+    /// `tilings` rejillas de `width × width` celdas, cada una desplazada
+    /// `width / tilings` respecto de la anterior. Celdas cercanas comparten tiles.
+    TileCoding { tilings: usize, width: usize },
+    /// Desplazamiento con signo hasta la meta en `(row, col)` (normalizado),
+    /// distancia Manhattan normalizada y un término de sesgo.
+    GoalDistance { row: usize, col: usize },
+}
+
+/// Convierte una observación (índice de celda) en un vector de características
+/// disperso `(índice, valor)`, concatenando las familias configuradas.
+#[derive(Debug, Clone)]
+pub struct FeatureExtractor {
+    features: Vec<Feature>,
+    len: usize,
+}
+
+impl Feature {
+    fn len(&self) -> usize {
+        match *self {
+            Self::OneHot => N_STATES,
+            Self::TileCoding { tilings, width } => {
+                tilings * tiles_per_axis(N_ROWS, width) * tiles_per_axis(N_COLS, width)
+            }
+            Self::GoalDistance { .. } => 4,
+        }
+    }
+
+    fn extract(&self, row: usize, col: usize, offset: usize, out: &mut Vec<(usize, f32)>) {
+        match *self {
+            Self::OneHot => out.push((offset + row * N_COLS + col, 1.0)),
+            Self::TileCoding { tilings, width } => {
+                let (tiles_rows, tiles_cols) =
+                    (tiles_per_axis(N_ROWS, width), tiles_per_axis(N_COLS, width));
+
+                for tiling in 0..tilings {
+                    // Cada rejilla se corre `tiling * width / tilings` celdas en diagonal
+                    let shift = tiling * width / tilings;
+                    let tile_row = (row + shift) / width;
+                    let tile_col = (col + shift) / width;
+
+                    let index = (tiling * tiles_rows + tile_row) * tiles_cols + tile_col;
+                    out.push((offset + index, 1.0));
+                }
+            }
+            Self::GoalDistance {
+                row: goal_row,
+                col: goal_col,
+            } => {
+                let d_row = (goal_row as f32 - row as f32) / N_ROWS as f32;
+                let d_col = (goal_col as f32 - col as f32) / N_COLS as f32;
+
+                out.extend([
+                    (offset, d_row),
+                    (offset + 1, d_col),
+                    (offset + 2, d_row.abs() + d_col.abs()),
+                    (offset + 3, 1.0),
+                ]);
+            }
+        }
+    }
+}
+
+impl FeatureExtractor {
+    pub fn new(features: Vec<Feature>) -> Self {
+        let len = features.iter().map(Feature::len).sum();
+
+        Self { features, len }
+    }
+
+    /// Número total de características (tamaño de cada vector de pesos).
+    pub fn dimension(&self) -> usize {
+        self.len
+    }
+
+    pub fn extract(&self, obs: usize) -> Vec<(usize, f32)> {
+        let (row, col) = (obs / N_COLS, obs % N_COLS);
+        let mut out = Vec::new();
+        let mut offset = 0;
+
+        for feature in &self.features {
+            feature.extract(row, col, offset, &mut out);
+            offset += feature.len();
+        }
+
+        out
+    }
+}
+
+/// Tiles necesarios para cubrir `cells` celdas con un desplazamiento de hasta `width - 1`.
+fn tiles_per_axis(cells: usize, width: usize) -> usize {
+    cells.div_ceil(width) + 1
+}
@@ -0,0 +1,113 @@
+use crate::{
+    DISCOUNT_FACTOR, EPSILON_DECAY, N_COLS,
+    agent::QLearningAgent,
+    environment::{Environment, StartDistribution},
+    evaluation::{EVALUATION_INTERVAL, Evaluation, evaluate},
+    features::Feature,
+    linear::{LinearAgent, TdMethod},
+};
+
+pub const GENERALIZATION_EPISODES: usize = 3000;
+/// Las celdas desde esta columna hacia la derecha nunca se usan como inicio
+/// durante el entrenamiento; solo se evalúan.
+pub const HELD_OUT_FROM_COL: usize = 11;
+const LINEAR_LEARNING_RATE: f32 = 0.1;
+
+/// Curva de evaluación de un agente sobre las celdas no vistas.
+#[derive(Debug, Clone)]
+pub struct GeneralizationCurve {
+    pub agent: String,
+    pub evaluations: Vec<Evaluation>,
+}
+
+/// Celdas de inicio de `env.start` para entrenar y celdas reservadas para
+/// evaluar. Falla si alguno de los dos grupos queda vacío.
+pub fn split_start_cells(env: &Environment) -> Result<(Vec<usize>, Vec<usize>), String> {
+    let (train_cells, held_out): (Vec<_>, Vec<_>) = env
+        .start_cells()
+        .into_iter()
+        .partition(|&index| index % N_COLS < HELD_OUT_FROM_COL);
+
+    if train_cells.is_empty() || held_out.is_empty() {
+        return Err(format!(
+            "The start distribution {:?} must have cells on both sides of column {HELD_OUT_FROM_COL}",
+            env.start
+        ));
+    }
+
+    Ok((train_cells, held_out))
+}
+
+/// Entrena un agente tabular y dos lineales (Q-Learning y SARSA semi-gradiente
+/// con tile coding y distancia a la meta) en el mapa de `env`, empezando solo
+/// en las celdas de inicio de las columnas `< HELD_OUT_FROM_COL`, y cada
+/// `EVALUATION_INTERVAL` episodios evalúa la política greedy desde las demás.
+pub fn run(mut env: Environment) -> Result<Vec<GeneralizationCurve>, String> {
+    let (train_cells, held_out) = split_start_cells(&env)?;
+    env.start = StartDistribution::Cells(train_cells);
+    let goal = env.goal().index;
+
+    let features = || {
+        vec![
+            Feature::TileCoding {
+                tilings: 8,
+                width: 4,
+            },
+            Feature::GoalDistance {
+                row: goal / N_COLS,
+                col: goal % N_COLS,
+            },
+        ]
+    };
+    let linear = |features, method| {
+        LinearAgent::new(features, method, 4, LINEAR_LEARNING_RATE, DISCOUNT_FACTOR)
+    };
+
+    let mut tabular = QLearningAgent::for_env(&env);
+    let mut linear_q = linear(features(), TdMethod::QLearning);
+    let mut linear_sarsa = linear(features(), TdMethod::Sarsa);
+    // Control: con one-hot el agente lineal es equivalente a la Q-table
+    let mut one_hot = linear(vec![Feature::OneHot], TdMethod::QLearning);
+
+    let mut curves: Vec<GeneralizationCurve> = [
+        "Tabular Q-Learning",
+        "Linear Q-Learning",
+        "Linear SARSA",
+        "Linear Q-Learning (one-hot)",
+    ]
+    .iter()
+    .map(|name| GeneralizationCurve {
+        agent: name.to_string(),
+        evaluations: Vec::new(),
+    })
+    .collect();
+
+    for episode in 1..=GENERALIZATION_EPISODES {
+        tabular.run_episode(&mut env, DISCOUNT_FACTOR);
+        linear_q.run_episode(&mut env);
+        linear_sarsa.run_episode(&mut env);
+        one_hot.run_episode(&mut env);
+
+        tabular.epsilon *= EPSILON_DECAY;
+        linear_q.epsilon *= EPSILON_DECAY;
+        linear_sarsa.epsilon *= EPSILON_DECAY;
+        one_hot.epsilon *= EPSILON_DECAY;
+
+        if !episode.is_multiple_of(EVALUATION_INTERVAL) {
+            continue;
+        }
+
+        let evaluations = [
+            evaluate(&env, &held_out, |obs| tabular.best_action(obs)),
+            evaluate(&env, &held_out, |obs| linear_q.best_action(obs)),
+            evaluate(&env, &held_out, |obs| linear_sarsa.best_action(obs)),
+            evaluate(&env, &held_out, |obs| one_hot.best_action(obs)),
+        ];
+
+        for (curve, evaluation) in curves.iter_mut().zip(evaluations) {
+            curve.evaluations.push(evaluation);
+        }
+    }
+
+    Ok(curves)
+}
@@ -1,12 +1,18 @@
-use crate::SUCCESS_PROBABILITIES;
+use crate::{
+    SUCCESS_PROBABILITIES,
+    comparison::TrainingCurve,
+    evaluation::{EVALUATION_INTERVAL, Evaluation},
+    generalization::GeneralizationCurve,
+    optimal::EpisodeMetrics,
+};
 use plotters::prelude::*;
 
-const COLORS: [RGBColor; 3] = [RED, BLUE, GREEN];
+const COLORS: [RGBColor; 4] = [RED, BLUE, GREEN, MAGENTA];
 
 pub fn downsample<T: Copy + Into<f64>>(data: &[T], stride: usize) -> Vec<(usize, f64)> {
     data.iter()
         .enumerate()
-        .filter(|(i, _)| i % stride == 0)
+        .filter(|(i, _)| i.is_multiple_of(stride))
         .map(|(i, &v)| (i, v.into()))
         .collect()
 }
@@ -20,7 +26,9 @@ pub fn plot_rewards_and_steps(
         "Episode",
         "Total Reward",
         "rewards.png",
+        &probability_labels(),
         rewards,
+        50,
     )?;
 
     let steps_f64: Vec<Vec<f64>> = steps
@@ -33,18 +41,185 @@ pub fn plot_rewards_and_steps(
         "Episode",
         "Steps",
         "steps.png",
+        &probability_labels(),
         &steps_f64,
+        50,
     )?;
 
     Ok(())
 }
 
+/// Grafica las métricas respecto del óptimo de programación dinámica, una curva
+/// por probabilidad de éxito, con el mismo formato que las curvas de recompensa.
+pub fn plot_optimality_metrics(
+    metrics: &[Vec<EpisodeMetrics>],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let series = |f: fn(&EpisodeMetrics) -> f64| -> Vec<Vec<f64>> {
+        metrics
+            .iter()
+            .map(|episodes| episodes.iter().map(f).collect())
+            .collect()
+    };
+
+    plot_line_chart(
+        "Acuerdo con la política óptima por episodios",
+        "Episode",
+        "Policy Agreement",
+        "agreement.png",
+        &probability_labels(),
+        &series(|m| m.policy_agreement),
+        50,
+    )?;
+
+    plot_line_chart(
+        "Error ‖Q − Q*‖ por episodios",
+        "Episode",
+        "Max |Q - Q*|",
+        "q_error.png",
+        &probability_labels(),
+        &series(|m| m.q_error),
+        50,
+    )?;
+
+    plot_line_chart(
+        "Regret por episodios",
+        "Episode",
+        "V*(s0) - Discounted Return",
+        "regret.png",
+        &probability_labels(),
+        &series(|m| m.regret),
+        50,
+    )?;
+
+    Ok(())
+}
+
+/// Evaluaciones greedy periódicas del entrenamiento principal, una curva por
+/// probabilidad de éxito.
+pub fn plot_evaluations(
+    evaluations: &[Vec<(usize, Evaluation)>],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let series = |f: fn(&Evaluation) -> f64| -> Vec<Vec<f64>> {
+        evaluations
+            .iter()
+            .map(|runs| runs.iter().map(|(_, evaluation)| f(evaluation)).collect())
+            .collect()
+    };
+
+    plot_line_chart(
+        "Éxito de la política greedy (ε = 0)",
+        &format!("Evaluation (cada {} episodios)", EVALUATION_INTERVAL),
+        "Success Rate",
+        "evaluation_success.png",
+        &probability_labels(),
+        &series(|e| e.success_rate),
+        1,
+    )?;
+
+    plot_line_chart(
+        "Pasos de la política greedy (ε = 0)",
+        &format!("Evaluation (cada {} episodios)", EVALUATION_INTERVAL),
+        "Mean Steps",
+        "evaluation_steps.png",
+        &probability_labels(),
+        &series(|e| e.mean_steps),
+        1,
+    )?;
+
+    Ok(())
+}
+
+/// Curvas de evaluación desde las celdas no vistas, una por agente.
+pub fn plot_generalization(
+    success_prob: f32,
+    curves: &[GeneralizationCurve],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let labels: Vec<String> = curves.iter().map(|curve| curve.agent.clone()).collect();
+    let series = |f: fn(&Evaluation) -> f64| -> Vec<Vec<f64>> {
+        curves
+            .iter()
+            .map(|curve| curve.evaluations.iter().map(f).collect())
+            .collect()
+    };
+
+    plot_line_chart(
+        &format!("Éxito desde celdas no vistas (P = {:.1})", success_prob),
+        &format!("Evaluation (cada {} episodios)", EVALUATION_INTERVAL),
+        "Success Rate",
+        &format!("generalization_success_p{}.png", success_prob),
+        &labels,
+        &series(|e| e.success_rate),
+        1,
+    )?;
+
+    plot_line_chart(
+        &format!("Pasos desde celdas no vistas (P = {:.1})", success_prob),
+        &format!("Evaluation (cada {} episodios)", EVALUATION_INTERVAL),
+        "Mean Steps",
+        &format!("generalization_steps_p{}.png", success_prob),
+        &labels,
+        &series(|e| e.mean_steps),
+        1,
+    )?;
+
+    Ok(())
+}
+
+/// Recompensa y pasos por episodio de varios agentes entrenados con la misma
+/// probabilidad de éxito. Genera `{prefix}_rewards_p*.png` y `{prefix}_steps_p*.png`.
+pub fn plot_training_curves(
+    prefix: &str,
+    success_prob: f32,
+    curves: &[TrainingCurve],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let labels: Vec<String> = curves.iter().map(|curve| curve.agent.clone()).collect();
+    let rewards: Vec<Vec<f64>> = curves.iter().map(|curve| curve.rewards.clone()).collect();
+    let steps: Vec<Vec<f64>> = curves
+        .iter()
+        .map(|curve| curve.steps.iter().map(|&v| v as f64).collect())
+        .collect();
+
+    plot_line_chart(
+        &format!("Recompenza por episodios (P = {:.1})", success_prob),
+        "Episode",
+        "Total Reward",
+        &format!("{}_rewards_p{}.png", prefix, success_prob),
+        &labels,
+        &rewards,
+        5,
+    )?;
+
+    plot_line_chart(
+        &format!(
+            "Pasos hasta la meta por episodios (P = {:.1})",
+            success_prob
+        ),
+        "Episode",
+        "Steps",
+        &format!("{}_steps_p{}.png", prefix, success_prob),
+        &labels,
+        &steps,
+        5,
+    )?;
+
+    Ok(())
+}
+
+fn probability_labels() -> Vec<String> {
+    SUCCESS_PROBABILITIES
+        .iter()
+        .map(|prob| format!("P = {:.1}", prob))
+        .collect()
+}
+
 fn plot_line_chart<T: Copy + Into<f64>>(
     title: &str,
     x_label: &str,
     y_label: &str,
     filename: &str,
+    labels: &[String],
     data_series: &[Vec<T>],
+    stride: usize,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let filename = format!("plots/{}", filename);
     let root = BitMapBackend::new(&filename, (1280, 720)).into_drawing_area();
@@ -76,20 +251,17 @@ fn plot_line_chart<T: Copy + Into<f64>>(
 
     for (i, series) in data_series.iter().enumerate() {
         let color = COLORS[i % COLORS.len()].to_rgba();
-        let label = format!("P = {:.1}", SUCCESS_PROBABILITIES[i]);
+        let label = labels[i].clone();
 
-        let downsampled = downsample(series, 50);
+        let downsampled = downsample(series, stride);
 
         chart
             .draw_series(LineSeries::new(downsampled, &color))?
             .label(label)
-            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &color));
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
     }
 
-    chart
-        .configure_series_labels()
-        .border_style(&BLACK)
-        .draw()?;
+    chart.configure_series_labels().border_style(BLACK).draw()?;
 
     Ok(())
 }
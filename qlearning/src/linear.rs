@@ -0,0 +1,198 @@
+use rand::{random, random_range};
+use utils::{Env, argmax_f32};
+
+use crate::{
+    agent::EpisodeStats,
+    features::{Feature, FeatureExtractor},
+};
+
+/// Regla de actualización TD del agente lineal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TdMethod {
+    /// Off-policy: el objetivo usa la mejor acción en el estado siguiente.
+    QLearning,
+    /// On-policy: el objetivo usa la acción que efectivamente se tomará.
+    Sarsa,
+}
+
+/// Agente con aproximación lineal Q(s,a) = w_a · φ(s). A diferencia de la
+/// Q-table, celdas con características parecidas comparten pesos, por lo que
+/// el agente puede actuar razonablemente en celdas donde nunca empezó.
+#[derive(Debug, Clone)]
+pub struct LinearAgent {
+    pub weights: Vec<Vec<f32>>,
+    pub features: FeatureExtractor,
+    pub method: TdMethod,
+    pub learning_rate: f32,
+    pub discount_factor: f32,
+    pub epsilon: f64,
+}
+
+impl LinearAgent {
+    pub fn new(
+        features: Vec<Feature>,
+        method: TdMethod,
+        n_actions: usize,
+        learning_rate: f32,
+        discount_factor: f32,
+    ) -> Self {
+        let features = FeatureExtractor::new(features);
+
+        Self {
+            weights: vec![vec![0.0; features.dimension()]; n_actions],
+            features,
+            method,
+            learning_rate,
+            discount_factor,
+            epsilon: 0.1_f64,
+        }
+    }
+
+    pub fn q_values(&self, obs: usize) -> Vec<f32> {
+        let phi = self.features.extract(obs);
+
+        self.weights
+            .iter()
+            .map(|w| phi.iter().map(|&(i, x)| w[i] * x).sum())
+            .collect()
+    }
+
+    pub fn best_action(&self, obs: usize) -> usize {
+        argmax_f32(&self.q_values(obs))
+    }
+
+    pub fn choose_action(&self, obs: usize) -> usize {
+        if random::<f64>() > self.epsilon {
+            return self.best_action(obs);
+        }
+
+        random_range(0..self.weights.len())
+    }
+
+    /// Actualización semi-gradiente: w_a += α · δ · φ(s), con
+    /// δ = r + γ·Q(s',a') − Q(s,a). `next_q` es Q(s',a') según el método
+    /// (0 si el episodio terminó). El paso se divide por ‖φ(s)‖₁ para que no
+    /// dependa de cuántas características estén activas.
+    ///
+    /// Devuelve el error TD antes de la actualización.
+    fn update(&mut self, obs: usize, action: usize, reward: f32, next_q: f32) -> f32 {
+        let phi = self.features.extract(obs);
+        let q = phi
+            .iter()
+            .map(|&(i, x)| self.weights[action][i] * x)
+            .sum::<f32>();
+        let td_error = reward + self.discount_factor * next_q - q;
+
+        let norm = phi.iter().map(|(_, x)| x.abs()).sum::<f32>().max(1.0);
+        let step = self.learning_rate / norm;
+
+        for (i, x) in phi {
+            self.weights[action][i] += step * td_error * x;
+        }
+
+        td_error
+    }
+
+    /// Juega un episodio completo con la política epsilon-greedy, aprendiendo
+    /// en cada paso con `self.method`.
+    pub fn run_episode<E: Env<Obs = usize>>(&mut self, env: &mut E) -> EpisodeStats {
+        let start = env.reset(None);
+        let mut obs = start;
+        let mut action = self.choose_action(obs);
+
+        let mut steps = 0;
+        let mut total_reward = 0.0_f64;
+        let mut discounted_return = 0.0_f64;
+        let mut discount = 1.0_f64;
+        let mut td_error_sum = 0.0_f64;
+        let mut td_error_max = 0.0_f64;
+
+        loop {
+            let (next_obs, reward, terminated, truncated, _) = env.step(action);
+            let next_action = self.choose_action(next_obs);
+
+            let next_q = if terminated {
+                0.0
+            } else {
+                let next_q_values = self.q_values(next_obs);
+
+                match self.method {
+                    TdMethod::QLearning => next_q_values[argmax_f32(&next_q_values)],
+                    TdMethod::Sarsa => next_q_values[next_action],
+                }
+            };
+
+            let td_error = self.update(obs, action, reward, next_q).abs() as f64;
+            td_error_sum += td_error;
+            td_error_max = td_error_max.max(td_error);
+
+            obs = next_obs;
+            action = next_action;
+            total_reward += reward as f64;
+            discounted_return += discount * reward as f64;
+            discount *= self.discount_factor as f64;
+            steps += 1;
+
+            if terminated || truncated {
+                break;
+            }
+        }
+
+        EpisodeStats {
+            start,
+            steps,
+            total_reward,
+            discounted_return,
+            td_error_mean: td_error_sum / steps as f64,
+            td_error_max,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::N_COLS;
+
+    #[test]
+    fn one_hot_update_converges_to_the_target() {
+        let mut agent = LinearAgent::new(vec![Feature::OneHot], TdMethod::QLearning, 4, 0.5, 0.9);
+
+        for _ in 0..50 {
+            agent.update(7, 2, 1.0, 0.0);
+        }
+
+        assert!((agent.q_values(7)[2] - 1.0).abs() < 1e-4);
+        assert_eq!(agent.best_action(7), 2);
+        // Sin características compartidas las demás celdas no cambian
+        assert_eq!(agent.q_values(8), vec![0.0; 4]);
+    }
+
+    #[test]
+    fn td_error_uses_the_discounted_next_value() {
+        let mut agent = LinearAgent::new(vec![Feature::OneHot], TdMethod::Sarsa, 4, 0.1, 0.9);
+
+        let td_error = agent.update(0, 1, -0.1, 2.0);
+        assert!((td_error - (-0.1 + 0.9 * 2.0)).abs() < 1e-6);
+        assert!((agent.q_values(0)[1] - 0.1 * td_error).abs() < 1e-6);
+    }
+
+    #[test]
+    fn tile_coding_generalizes_to_neighbors() {
+        let features = vec![Feature::TileCoding {
+            tilings: 4,
+            width: 4,
+        }];
+        let mut agent = LinearAgent::new(features, TdMethod::QLearning, 4, 0.5, 0.9);
+
+        for _ in 0..20 {
+            agent.update(5 * N_COLS + 5, 0, 1.0, 0.0);
+        }
+
+        let neighbor = agent.q_values(5 * N_COLS + 6)[0];
+        let far = agent.q_values(0)[0];
+
+        assert!(neighbor > 0.5, "neighbor only reached {neighbor}");
+        assert_eq!(far, 0.0);
+    }
+}
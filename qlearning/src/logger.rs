@@ -0,0 +1,350 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use utils::{argmax_f32, num_to_direction};
+
+use crate::{evaluation::Evaluation, optimal::EpisodeMetrics};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Csv,
+    JsonLines,
+}
+
+#[derive(Debug, Clone)]
+pub struct LoggerConfig {
+    /// Carpeta de la ejecución, una por cada vez que se corre el binario.
+    pub run_dir: PathBuf,
+    pub format: LogFormat,
+    /// Cada cuántos episodios se guarda la política greedy (0 = nunca).
+    pub policy_interval: usize,
+}
+
+/// Datos de un episodio de entrenamiento tal como se escriben en el log.
+#[derive(Debug, Clone, Copy)]
+pub struct EpisodeLog {
+    pub episode: usize,
+    pub steps: usize,
+    pub total_reward: f64,
+    pub epsilon: f64,
+    pub td_error_mean: f64,
+    pub td_error_max: f64,
+    /// `None` si no hay óptimo con qué comparar (mapa dinámico).
+    pub metrics: Option<EpisodeMetrics>,
+}
+
+/// Escribe un registro estructurado (CSV o JSON Lines) por episodio, una
+/// instantánea de la política cada `policy_interval` episodios y el resultado
+/// de cada evaluación greedy.
+pub struct TrainingLogger {
+    episodes: BufWriter<File>,
+    policies: BufWriter<File>,
+    evaluations: BufWriter<File>,
+    format: LogFormat,
+    policy_interval: usize,
+    started_at: Instant,
+}
+
+impl LoggerConfig {
+    /// Crea la configuración con una carpeta nueva `runs_dir/<unix_timestamp>`.
+    pub fn new_run(runs_dir: impl AsRef<Path>, format: LogFormat, policy_interval: usize) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        Self {
+            run_dir: runs_dir.as_ref().join(timestamp.to_string()),
+            format,
+            policy_interval,
+        }
+    }
+}
+
+impl TrainingLogger {
+    /// Abre los archivos de log de un entrenamiento dentro de `config.run_dir/name`.
+    pub fn create(config: &LoggerConfig, name: &str) -> io::Result<Self> {
+        let dir = config.run_dir.join(name);
+        fs::create_dir_all(&dir)?;
+
+        let extension = match config.format {
+            LogFormat::Csv => "csv",
+            LogFormat::JsonLines => "jsonl",
+        };
+
+        let mut episodes = BufWriter::new(File::create(dir.join(format!("episodes.{extension}")))?);
+        let mut policies = BufWriter::new(File::create(dir.join(format!("policies.{extension}")))?);
+        let mut evaluations =
+            BufWriter::new(File::create(dir.join(format!("evaluations.{extension}")))?);
+
+        if config.format == LogFormat::Csv {
+            writeln!(
+                episodes,
+                "episode,steps,return,epsilon,td_error_mean,td_error_max,\
+                 policy_agreement,q_error,regret,wall_time_s"
+            )?;
+            writeln!(policies, "episode,policy")?;
+            writeln!(evaluations, "episode,success_rate,mean_steps,mean_return")?;
+        }
+
+        Ok(Self {
+            episodes,
+            policies,
+            evaluations,
+            format: config.format,
+            policy_interval: config.policy_interval,
+            started_at: Instant::now(),
+        })
+    }
+
+    pub fn log_episode(&mut self, log: &EpisodeLog) -> io::Result<()> {
+        let wall_time = self.started_at.elapsed().as_secs_f64();
+        let EpisodeLog {
+            episode,
+            steps,
+            total_reward,
+            epsilon,
+            td_error_mean,
+            td_error_max,
+            metrics,
+        } = *log;
+        // Sin métricas la columna queda vacía en CSV y en `null` en JSON
+        let metric = |value: fn(&EpisodeMetrics) -> f64, missing: &str| {
+            metrics.map_or(missing.to_string(), |metrics| value(&metrics).to_string())
+        };
+
+        match self.format {
+            LogFormat::Csv => writeln!(
+                self.episodes,
+                "{},{},{},{},{},{},{},{},{},{:.6}",
+                episode,
+                steps,
+                total_reward,
+                epsilon,
+                td_error_mean,
+                td_error_max,
+                metric(|m| m.policy_agreement, ""),
+                metric(|m| m.q_error, ""),
+                metric(|m| m.regret, ""),
+                wall_time
+            ),
+            LogFormat::JsonLines => writeln!(
+                self.episodes,
+                "{{\"episode\":{},\"steps\":{},\"return\":{},\"epsilon\":{},\
+                 \"td_error_mean\":{},\"td_error_max\":{},\"policy_agreement\":{},\
+                 \"q_error\":{},\"regret\":{},\"wall_time_s\":{:.6}}}",
+                episode,
+                steps,
+                total_reward,
+                epsilon,
+                td_error_mean,
+                td_error_max,
+                metric(|m| m.policy_agreement, "null"),
+                metric(|m| m.q_error, "null"),
+                metric(|m| m.regret, "null"),
+                wall_time
+            ),
+        }
+    }
+
+    /// Guarda la política greedy de `q_table` si `episode` cae en el intervalo
+    /// configurado. Devuelve si se escribió la instantánea.
+    pub fn snapshot_policy(&mut self, episode: usize, q_table: &[Vec<f32>]) -> io::Result<bool> {
+        if self.policy_interval == 0 || !episode.is_multiple_of(self.policy_interval) {
+            return Ok(false);
+        }
+
+        let policy: String = q_table
+            .iter()
+            .map(|actions| num_to_direction(argmax_f32(actions)))
+            .collect();
+
+        match self.format {
+            LogFormat::Csv => writeln!(self.policies, "{episode},{policy}")?,
+            LogFormat::JsonLines => writeln!(
+                self.policies,
+                "{{\"episode\":{episode},\"policy\":\"{policy}\"}}"
+            )?,
+        }
+
+        Ok(true)
+    }
+
+    pub fn log_evaluation(&mut self, episode: usize, evaluation: &Evaluation) -> io::Result<()> {
+        let Evaluation {
+            success_rate,
+            mean_steps,
+            mean_return,
+        } = *evaluation;
+
+        match self.format {
+            LogFormat::Csv => writeln!(
+                self.evaluations,
+                "{episode},{success_rate},{mean_steps},{mean_return}"
+            ),
+            LogFormat::JsonLines => writeln!(
+                self.evaluations,
+                "{{\"episode\":{episode},\"success_rate\":{success_rate},\"mean_steps\":{mean_steps},\"mean_return\":{mean_return}}}"
+            ),
+        }
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.episodes.flush()?;
+        self.policies.flush()?;
+        self.evaluations.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Logger sobre una carpeta temporal propia de cada test.
+    fn logger(name: &str, format: LogFormat, policy_interval: usize) -> (TrainingLogger, PathBuf) {
+        let config = LoggerConfig {
+            run_dir: std::env::temp_dir().join(format!("qlearning-logger-{}", std::process::id())),
+            format,
+            policy_interval,
+        };
+        let logger = TrainingLogger::create(&config, name).unwrap();
+
+        (logger, config.run_dir.join(name))
+    }
+
+    fn episode(episode: usize, metrics: Option<EpisodeMetrics>) -> EpisodeLog {
+        EpisodeLog {
+            episode,
+            steps: 12,
+            total_reward: -3.5,
+            epsilon: 0.1,
+            td_error_mean: 0.25,
+            td_error_max: 1.0,
+            metrics,
+        }
+    }
+
+    const METRICS: EpisodeMetrics = EpisodeMetrics {
+        policy_agreement: 0.5,
+        q_error: 2.0,
+        regret: 0.75,
+    };
+
+    /// Separa un objeto JSON plano en pares (clave, valor), comprobando que
+    /// cada valor es un número, `null` o un string.
+    fn parse_flat_json(line: &str) -> Vec<(String, String)> {
+        let body = line
+            .strip_prefix('{')
+            .and_then(|line| line.strip_suffix('}'))
+            .expect("not a JSON object");
+
+        body.split(',')
+            .map(|pair| {
+                let (key, value) = pair.split_once(':').expect("missing ':'");
+                let key = key
+                    .strip_prefix('"')
+                    .and_then(|key| key.strip_suffix('"'))
+                    .expect("unquoted key");
+                let is_string = value.len() >= 2 && value.starts_with('"') && value.ends_with('"');
+                assert!(
+                    value == "null" || value.parse::<f64>().is_ok() || is_string,
+                    "invalid value {value:?} for {key}"
+                );
+
+                (key.to_string(), value.to_string())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn csv_rows_match_the_header() {
+        let (mut logger, dir) = logger("csv", LogFormat::Csv, 0);
+        logger.log_episode(&episode(1, Some(METRICS))).unwrap();
+        logger.log_episode(&episode(2, None)).unwrap();
+        logger.flush().unwrap();
+
+        let content = fs::read_to_string(dir.join("episodes.csv")).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        let columns = lines[0].split(',').count();
+
+        assert_eq!(columns, 10);
+        assert_eq!(lines.len(), 3);
+
+        for row in &lines[1..] {
+            assert_eq!(row.split(',').count(), columns, "{row}");
+        }
+
+        let with_metrics: Vec<&str> = lines[1].split(',').collect();
+        assert_eq!(&with_metrics[6..9], ["0.5", "2", "0.75"]);
+        let without_metrics: Vec<&str> = lines[2].split(',').collect();
+        assert_eq!(&without_metrics[6..9], ["", "", ""]);
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn json_lines_parse_with_null_metrics() {
+        let (mut logger, dir) = logger("jsonl", LogFormat::JsonLines, 1);
+        logger.log_episode(&episode(1, Some(METRICS))).unwrap();
+        logger.log_episode(&episode(2, None)).unwrap();
+        logger
+            .snapshot_policy(1, &[vec![0.0, 1.0, 0.0, 0.0]])
+            .unwrap();
+        logger.log_evaluation(1, &Evaluation::default()).unwrap();
+        logger.flush().unwrap();
+
+        let episodes = fs::read_to_string(dir.join("episodes.jsonl")).unwrap();
+        let rows: Vec<_> = episodes.lines().map(parse_flat_json).collect();
+        assert_eq!(rows.len(), 2);
+
+        let value = |row: &[(String, String)], key: &str| {
+            row.iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.clone())
+                .unwrap()
+        };
+
+        for key in ["policy_agreement", "q_error", "regret"] {
+            assert_ne!(value(&rows[0], key), "null");
+            assert_eq!(value(&rows[1], key), "null");
+        }
+
+        for file in ["policies.jsonl", "evaluations.jsonl"] {
+            let content = fs::read_to_string(dir.join(file)).unwrap();
+            assert_eq!(content.lines().map(parse_flat_json).count(), 1, "{file}");
+        }
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn policy_snapshots_follow_the_interval() {
+        let q_table = vec![vec![0.0, 0.0, 1.0, 0.0]; 3];
+
+        let (mut every_three, dir) = logger("interval", LogFormat::Csv, 3);
+        let written: Vec<usize> = (1..=10)
+            .filter(|&episode| every_three.snapshot_policy(episode, &q_table).unwrap())
+            .collect();
+        every_three.flush().unwrap();
+
+        assert_eq!(written, [3, 6, 9]);
+        let content = fs::read_to_string(dir.join("policies.csv")).unwrap();
+        let episodes: Vec<&str> = content
+            .lines()
+            .skip(1)
+            .map(|line| line.split(',').next().unwrap())
+            .collect();
+        assert_eq!(episodes, ["3", "6", "9"]);
+        fs::remove_dir_all(dir).ok();
+
+        let (mut never, dir) = logger("never", LogFormat::Csv, 0);
+        assert!((0..=10).all(|episode| !never.snapshot_policy(episode, &q_table).unwrap()));
+        never.flush().unwrap();
+
+        let content = fs::read_to_string(dir.join("policies.csv")).unwrap();
+        assert_eq!(content.lines().count(), 1);
+        fs::remove_dir_all(dir).ok();
+    }
+}
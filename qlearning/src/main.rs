@@ -1,8 +1,34 @@
+mod agent;
+mod benchmark;
+mod comparison;
+mod dashboard;
+mod dqn;
+mod dynamics;
 mod environment;
+mod evaluation;
+mod features;
+mod generalization;
 mod graphics;
+mod linear;
+mod logger;
+mod mlp;
+mod observer;
+mod optimal;
+mod planning;
+mod pomdp;
+mod scenario;
 mod state;
 
-use crate::environment::Environment;
+use crate::{
+    agent::QLearningAgent,
+    dashboard::Dashboard,
+    dynamics::Dynamics,
+    environment::{Environment, StartDistribution},
+    logger::{LogFormat, LoggerConfig, TrainingLogger},
+    observer::{ConsoleProgress, TrainingObserver},
+    optimal::Optimum,
+    pomdp::{Controller, Localization},
+};
 
 pub const N_ROWS: usize = 12;
 pub const N_COLS: usize = 15;
@@ -14,13 +40,23 @@ pub const LEARNING_RATE: f32 = 0.1;
 pub const EPSILON_DECAY: f64 = 0.9;
 
 pub const DISCOUNT_FACTOR: f32 = 0.95;
+/// Recompensa adicional al chocar con un muro o el borde del mapa.
+pub const BUMP_PENALTY: f32 = 0.0;
 pub const SUCCESS_PROBABILITIES: [f32; 3] = [0.3, 0.7, 0.9];
 
-pub const GOAL_POSITION: [usize; 2] = [5, 8];
+/// Distancia mínima a la meta de los inicios `far`.
+pub const FAR_START_DISTANCE: usize = 10;
 
 pub const EPISODES: usize = 10000;
 pub const MAX_STEPS: usize = 1000;
 
+pub const RUNS_DIR: &str = "runs";
+pub const BENCHMARKS_DIR: &str = "benchmarks";
+pub const LOG_FORMAT: LogFormat = LogFormat::Csv;
+pub const POLICY_SNAPSHOT_INTERVAL: usize = 500;
+pub const PROGRESS_INTERVAL: usize = 500;
+pub const DASHBOARD_INTERVAL: usize = 25;
+
 #[rustfmt::skip]
 pub const RAW_MAP: [[&str; 15]; 12] = [
     [ "S0",  "S1",  "S2",  "S3",  "S4",  "S5",  "S6",  "S7",  "S8",  "S9",  "S10", "S11", "S12", "S13", "S14" ],
@@ -42,40 +78,302 @@ use raylib::{
     color::Color,
     prelude::{RaylibDraw, RaylibMode2DExt},
 };
-use utils::Raylib;
+use utils::{
+    Env, Raylib,
+    pomdp::{MAX_ERROR_RATE, WallSensor, qmdp_action},
+};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut reward_data = Vec::new();
     let mut step_data = Vec::new();
+    let mut metric_data = Vec::new();
+    let mut evaluation_data = Vec::new();
 
     let mut graphic_simulation = Environment::new(SUCCESS_PROBABILITIES[0]);
-    let mut graphic_q_table = vec![vec![0.0; 4]; N_STATES];
+    let mut graphic_agent = QLearningAgent::new(N_STATES, 4);
+
+    let headless = std::env::args().any(|arg| arg == "--headless");
+    let live = std::env::args().any(|arg| arg == "--live");
+    let dynamic_map = std::env::args().any(|arg| arg == "--dynamic");
+    let dynamics = match std::env::args().find_map(|arg| {
+        arg.strip_prefix("--dynamics=")
+            .map(|name| (Dynamics::from_name(name), name.to_string()))
+    }) {
+        Some((Some(dynamics), _)) => dynamics,
+        Some((None, name)) => return Err(format!("Unknown dynamics model: {name}").into()),
+        None => Dynamics::StayOnFailure,
+    };
+    let start = match std::env::args().find_map(|arg| {
+        arg.strip_prefix("--start=")
+            .map(|name| (StartDistribution::from_name(name), name.to_string()))
+    }) {
+        Some((Some(start), _)) => start,
+        Some((None, name)) => return Err(format!("Unknown start distribution: {name}").into()),
+        None => StartDistribution::Uniform,
+    };
+
+    // Con --pomdp[=<error>] el agente no conoce su celda: se localiza con sensores de muros
+    let sensor = match std::env::args().find_map(|arg| match arg.as_str() {
+        "--pomdp" => Some(pomdp::SENSOR_ERROR_RATE.to_string()),
+        _ => arg.strip_prefix("--pomdp=").map(str::to_string),
+    }) {
+        Some(value) => match value.parse::<f32>() {
+            Ok(error_rate) if (0.0..=MAX_ERROR_RATE).contains(&error_rate) => {
+                Some(WallSensor { error_rate })
+            }
+            _ => {
+                return Err(format!(
+                    "Invalid sensor error rate: {value} (expected 0.0 to {MAX_ERROR_RATE})"
+                )
+                .into());
+            }
+        },
+        None => None,
+    };
+
+    // Con --map=<archivo> se entrena en un mapa generado con `utils::mapgen`
+    let map_keys =
+        match std::env::args().find_map(|arg| arg.strip_prefix("--map=").map(str::to_string)) {
+            Some(path) => {
+                // Las celdas de `scenario::warehouse()` son las de `RAW_MAP`
+                if dynamic_map {
+                    return Err(format!("--dynamic cannot be combined with --map={path}").into());
+                }
 
-    let (mut rlib, thread) = Raylib::init_window("MDP Q-Learning Simulation", WINDOW_SIZE);
+                let keys = utils::mapgen::parse_keys(&std::fs::read_to_string(&path)?);
+                graphic_simulation =
+                    Environment::from_keys(&keys, SUCCESS_PROBABILITIES[0], dynamics.clone())
+                        .map_err(|error| format!("Invalid map {path}: {error}"))?;
+                Some(keys)
+            }
+            None => None,
+        };
+
+    // Todos los entornos comparten el mapa, así que basta con validar el inicio en uno
+    graphic_simulation.start = start.clone();
+    graphic_simulation.check_start()?;
+
+    // Entorno de cada experimento: el mapa de --map (o `RAW_MAP`) con el inicio de --start
+    let make_env = |prob: f32| -> Result<Environment, String> {
+        let mut env = match &map_keys {
+            Some(keys) => Environment::from_keys(keys, prob, dynamics.clone())?,
+            None => Environment::with_dynamics(prob, dynamics.clone()),
+        };
+        env.start = start.clone();
+
+        Ok(env)
+    };
+
+    // Con --benchmark=<carpeta> se entrena en cada mapa de la carpeta y se escribe un reporte
+    if let Some(dir) =
+        std::env::args().find_map(|arg| arg.strip_prefix("--benchmark=").map(str::to_string))
+    {
+        println!("Benchmarking Q-Learning on the maps in {}", dir);
+        let results = benchmark::run(std::path::Path::new(&dir), &dynamics)?;
+
+        for result in &results {
+            match result.episodes_to_target {
+                Some(episodes) => println!(
+                    "  {} (P = {}): {} episodes to {:.0}% success, final return {:.3}",
+                    result.map,
+                    result.success_prob,
+                    episodes,
+                    benchmark::TARGET_SUCCESS_RATE * 100.0,
+                    result.final_return
+                ),
+                None => println!(
+                    "  {} (P = {}): did not reach {:.0}% success, final return {:.3}",
+                    result.map,
+                    result.success_prob,
+                    benchmark::TARGET_SUCCESS_RATE * 100.0,
+                    result.final_return
+                ),
+            }
+        }
+
+        utils::benchmark::write_report(BENCHMARKS_DIR, "qlearning", &results)?;
+        println!(
+            "Wrote {}/qlearning.md and {}/qlearning.csv",
+            BENCHMARKS_DIR, BENCHMARKS_DIR
+        );
+
+        return Ok(());
+    }
+
+    // Con --generalization solo se compara cómo generalizan los agentes
+    if std::env::args().any(|arg| arg == "--generalization") {
+        for &prob in &SUCCESS_PROBABILITIES {
+            println!("Running generalization experiment with P = {}", prob);
+            let curves = generalization::run(make_env(prob)?)?;
+
+            for curve in &curves {
+                let last = curve.evaluations.last().copied().unwrap_or_default();
+                println!(
+                    "  {}: success rate {:.2}, mean steps {:.1} from unseen start cells",
+                    curve.agent, last.success_rate, last.mean_steps
+                );
+            }
+
+            plot_generalization(prob, &curves)?;
+        }
+
+        return Ok(());
+    }
+
+    // Con --dqn se compara la Q-table con una DQN en el mismo mapa
+    if std::env::args().any(|arg| arg == "--dqn") {
+        for &prob in &SUCCESS_PROBABILITIES {
+            println!("Training tabular and DQN agents with P = {}", prob);
+            let curves = comparison::dqn_vs_tabular(make_env(prob)?);
+
+            for curve in &curves {
+                let last = &curve.steps[curve.steps.len().saturating_sub(50)..];
+                println!(
+                    "  {}: mean steps over the last {} episodes {:.1}",
+                    curve.agent,
+                    last.len(),
+                    last.iter().sum::<usize>() as f64 / last.len().max(1) as f64
+                );
+            }
+
+            plot_training_curves("dqn", prob, &curves)?;
+        }
+
+        return Ok(());
+    }
+
+    // Con --planning se mide la eficiencia de muestras de replay y Dyna-Q
+    if std::env::args().any(|arg| arg == "--planning") {
+        for &prob in &SUCCESS_PROBABILITIES {
+            println!("Comparing planning variants with P = {}", prob);
+            let (curves, efficiency) = comparison::planning_vs_qlearning(make_env(prob)?);
+
+            for result in &efficiency {
+                match result.steps_to_target {
+                    Some(steps) => println!(
+                        "  {}: {} real steps to {:.0}% policy agreement ({} in total)",
+                        result.agent,
+                        steps,
+                        comparison::AGREEMENT_TARGET * 100.0,
+                        result.total_steps
+                    ),
+                    None => println!(
+                        "  {}: did not reach {:.0}% policy agreement in {} real steps",
+                        result.agent,
+                        comparison::AGREEMENT_TARGET * 100.0,
+                        result.total_steps
+                    ),
+                }
+            }
+
+            plot_training_curves("planning", prob, &curves)?;
+        }
+
+        return Ok(());
+    }
+
+    // Con --live la ventana se abre antes de entrenar para mostrar el dashboard
+    let mut window = live.then(|| {
+        Raylib::init_window(
+            "MDP Q-Learning Simulation",
+            (WINDOW_SIZE.0 + dashboard::PANEL_WIDTH, WINDOW_SIZE.1),
+        )
+    });
+
+    let mut console = ConsoleProgress {
+        interval: PROGRESS_INTERVAL,
+    };
+
+    let log_config = LoggerConfig::new_run(RUNS_DIR, LOG_FORMAT, POLICY_SNAPSHOT_INTERVAL);
+    println!("Writing training logs to {}", log_config.run_dir.display());
 
     for &prob in &SUCCESS_PROBABILITIES {
         println!("Running simulation with P = {}", prob);
-        let mut env = Environment::new(prob);
-        let (rewards, steps) = env.run();
+        let mut env = make_env(prob)?;
+        if dynamic_map {
+            env.schedule = Some(scenario::warehouse());
+        }
+        let mut agent = QLearningAgent::for_env(&env);
+        let mut logger = TrainingLogger::create(&log_config, &format!("p{prob}"))?;
+        // En un mapa dinámico el óptimo del mapa estático no es una referencia válida
+        let optimum = (!dynamic_map).then(|| Optimum::value_iteration(&env, DISCOUNT_FACTOR));
+
+        let mut dashboard = window
+            .as_mut()
+            .map(|(rlib, thread)| Dashboard::new(rlib, thread, DASHBOARD_INTERVAL, prob));
+
+        let mut observers: Vec<&mut dyn TrainingObserver> = vec![&mut console];
+        if let Some(dashboard) = dashboard.as_mut() {
+            observers.push(dashboard);
+        }
 
-        reward_data.push(rewards);
-        step_data.push(steps);
+        let history = agent.train(&mut env, optimum.as_ref(), &mut logger, &mut observers)?;
 
-        graphic_simulation = env.clone();
-        graphic_q_table = env.q_table.clone();
+        if dashboard.is_some_and(|dashboard| dashboard.should_stop()) {
+            println!("Window closed, stopping training");
+            return Ok(());
+        }
+
+        reward_data.push(history.rewards);
+        step_data.push(history.steps);
+        metric_data.push(history.metrics);
+        evaluation_data.push(history.evaluations);
+
+        graphic_simulation = env;
+        graphic_agent = agent;
     }
 
     plot_rewards_and_steps(&reward_data, &step_data)?;
+    if dynamic_map {
+        println!("Dynamic map: skipping the metrics against the static optimum");
+    } else {
+        plot_optimality_metrics(&metric_data)?;
+    }
+    plot_evaluations(&evaluation_data)?;
+
+    let mut localization = None;
+    if let Some(sensor) = sensor {
+        println!(
+            "POMDP mode: wall sensors with {:.0}% error",
+            sensor.error_rate * 100.0
+        );
+
+        for (name, controller) in [
+            ("Greedy (known cell)", Controller::FullyObservable),
+            ("QMDP (belief)", Controller::Qmdp),
+        ] {
+            let evaluation = pomdp::evaluate(
+                &graphic_simulation,
+                &graphic_agent.q_table,
+                sensor,
+                controller,
+            );
+            println!(
+                "  {}: success rate {:.2}, {:.1} steps per episode, localized {:.0}% of the steps",
+                name,
+                evaluation.success_rate,
+                evaluation.mean_steps,
+                evaluation.localization_accuracy * 100.0
+            );
+        }
+
+        localization = Some(Localization::new(&graphic_simulation, sensor));
+    }
+
+    if headless {
+        return Ok(());
+    }
 
-    graphic_simulation.q_table = graphic_q_table;
-    graphic_simulation.reset_agent();
+    // Sin --live la ventana se abre recién aquí: el entrenamiento no necesita pantalla
+    let (mut rlib, thread) =
+        window.unwrap_or_else(|| Raylib::init_window("MDP Q-Learning Simulation", WINDOW_SIZE));
 
     println!("Starting graphical simulation with optimal policy...");
 
     let mut episode_active = false;
-    let mut current_state = graphic_simulation.reset_agent();
-    let mut step_count = 0;
+    let mut obs = graphic_simulation.reset(None);
     let mut frame_counter = 0;
+    let mut rng = rand::rng();
 
     while !rlib.window_should_close() {
         let mut d = rlib.begin_drawing(&thread);
@@ -83,30 +381,44 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         let _ = d.begin_mode2D(utils::Raylib::camera_2d());
 
         graphic_simulation.draw();
+        if let Some(localization) = &localization {
+            pomdp::draw_belief(&localization.belief);
+        }
 
         frame_counter += 1;
         if frame_counter >= 30 {
             frame_counter = 0;
 
             if !episode_active {
-                current_state = graphic_simulation.reset_agent();
-                step_count = 0;
+                obs = graphic_simulation.reset(None);
                 episode_active = true;
+                if let Some(localization) = localization.as_mut() {
+                    let reading =
+                        pomdp::read_sensors(&localization.sensor, &graphic_simulation, &mut rng);
+                    localization.reset(&graphic_simulation, &reading);
+                }
                 println!("Starting new episode with optimal policy");
+                continue;
             }
 
-            if episode_active && step_count < MAX_STEPS {
-                let action = graphic_simulation.get_best_action(&current_state);
-                let next_state = graphic_simulation.step(&current_state, action);
+            // En modo POMDP la acción sale de la creencia y no de la celda real
+            let action = match &localization {
+                Some(localization) => qmdp_action(&localization.belief, &graphic_agent.q_table),
+                None => graphic_agent.best_action(obs),
+            };
+            let (next_obs, _, terminated, truncated, info) = graphic_simulation.step(action);
+            obs = next_obs;
 
-                current_state = next_state.clone();
-                step_count += 1;
+            if let Some(localization) = localization.as_mut() {
+                let reading =
+                    pomdp::read_sensors(&localization.sensor, &graphic_simulation, &mut rng);
+                localization.update(&graphic_simulation, action, &reading);
+            }
 
-                if current_state.r#type == crate::state::StatusType::Goal {
-                    println!("Goal reached in {} steps!", step_count);
-                    episode_active = false;
-                }
-            } else if step_count >= MAX_STEPS {
+            if terminated {
+                println!("Goal reached in {} steps!", info.steps);
+                episode_active = false;
+            } else if truncated {
                 println!("Episode ended - max steps reached");
                 episode_active = false;
                 std::thread::sleep(std::time::Duration::from_millis(1000));
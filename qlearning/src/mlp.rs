@@ -0,0 +1,290 @@
+use rand::random_range;
+
+const ADAM_BETA1: f32 = 0.9;
+const ADAM_BETA2: f32 = 0.999;
+const ADAM_EPSILON: f32 = 1e-8;
+
+/// Capa densa `y = W·x + b` con los momentos de Adam de cada parámetro.
+#[derive(Debug, Clone)]
+struct Dense {
+    weights: Vec<Vec<f32>>,
+    bias: Vec<f32>,
+    m_weights: Vec<Vec<f32>>,
+    v_weights: Vec<Vec<f32>>,
+    m_bias: Vec<f32>,
+    v_bias: Vec<f32>,
+}
+
+/// Perceptrón multicapa pequeño (ReLU en las capas ocultas, salida lineal)
+/// entrenado con Adam. Pensado para redes de unas pocas decenas de neuronas
+/// en CPU, sin dependencias externas.
+#[derive(Debug, Clone)]
+pub struct Mlp {
+    layers: Vec<Dense>,
+    learning_rate: f32,
+    updates: i32,
+}
+
+impl Dense {
+    /// Inicialización de Glorot/Xavier uniforme.
+    fn new(inputs: usize, outputs: usize) -> Self {
+        let limit = (6.0 / (inputs + outputs) as f32).sqrt();
+        let weights = (0..outputs)
+            .map(|_| (0..inputs).map(|_| random_range(-limit..limit)).collect())
+            .collect();
+
+        Self {
+            weights,
+            bias: vec![0.0; outputs],
+            m_weights: vec![vec![0.0; inputs]; outputs],
+            v_weights: vec![vec![0.0; inputs]; outputs],
+            m_bias: vec![0.0; outputs],
+            v_bias: vec![0.0; outputs],
+        }
+    }
+
+    fn forward(&self, input: &[f32]) -> Vec<f32> {
+        self.weights
+            .iter()
+            .zip(&self.bias)
+            .map(|(row, b)| row.iter().zip(input).map(|(w, x)| w * x).sum::<f32>() + b)
+            .collect()
+    }
+}
+
+impl Mlp {
+    /// `sizes` incluye la entrada y la salida, p. ej. `[2, 64, 64, 4]`.
+    pub fn new(sizes: &[usize], learning_rate: f32) -> Self {
+        let layers = sizes
+            .windows(2)
+            .map(|pair| Dense::new(pair[0], pair[1]))
+            .collect();
+
+        Self {
+            layers,
+            learning_rate,
+            updates: 0,
+        }
+    }
+
+    pub fn predict(&self, input: &[f32]) -> Vec<f32> {
+        self.forward(input).pop().unwrap_or_default()
+    }
+
+    /// Activaciones de cada capa, empezando por la entrada.
+    fn forward(&self, input: &[f32]) -> Vec<Vec<f32>> {
+        let mut activations = vec![input.to_vec()];
+
+        for (i, layer) in self.layers.iter().enumerate() {
+            let mut output = layer.forward(&activations[activations.len() - 1]);
+
+            if i + 1 < self.layers.len() {
+                output.iter_mut().for_each(|x| *x = x.max(0.0));
+            }
+
+            activations.push(output);
+        }
+
+        activations
+    }
+
+    /// Gradientes (sumados sobre el minibatch) de la pérdida de Huber respecto
+    /// de los pesos y sesgos de cada capa, más el error absoluto total.
+    #[allow(clippy::type_complexity)]
+    fn gradients(
+        &self,
+        batch: &[(Vec<f32>, usize, f32)],
+    ) -> (Vec<Vec<Vec<f32>>>, Vec<Vec<f32>>, f32) {
+        let mut grad_weights: Vec<Vec<Vec<f32>>> = self
+            .layers
+            .iter()
+            .map(|layer| vec![vec![0.0; layer.weights[0].len()]; layer.weights.len()])
+            .collect();
+        let mut grad_bias: Vec<Vec<f32>> = self
+            .layers
+            .iter()
+            .map(|layer| vec![0.0; layer.bias.len()])
+            .collect();
+
+        let mut total_error = 0.0;
+
+        for (input, output, target) in batch {
+            let activations = self.forward(input);
+            let prediction = activations[activations.len() - 1][*output];
+            let error = prediction - target;
+            total_error += error.abs();
+
+            let mut delta = vec![0.0; self.layers[self.layers.len() - 1].bias.len()];
+            delta[*output] = error.clamp(-1.0, 1.0);
+
+            for l in (0..self.layers.len()).rev() {
+                let input = &activations[l];
+
+                for (j, &d) in delta.iter().enumerate() {
+                    grad_bias[l][j] += d;
+                    for (k, &x) in input.iter().enumerate() {
+                        grad_weights[l][j][k] += d * x;
+                    }
+                }
+
+                if l == 0 {
+                    break;
+                }
+
+                // Retropropagación a través de la ReLU de la capa anterior
+                delta = (0..input.len())
+                    .map(|k| {
+                        if input[k] <= 0.0 {
+                            return 0.0;
+                        }
+
+                        delta
+                            .iter()
+                            .enumerate()
+                            .map(|(j, d)| d * self.layers[l].weights[j][k])
+                            .sum()
+                    })
+                    .collect();
+            }
+        }
+
+        (grad_weights, grad_bias, total_error)
+    }
+
+    /// Un paso de Adam sobre el minibatch `(entrada, salida, objetivo)`
+    /// minimizando el error cuadrático medio solo en la salida indicada (la
+    /// acción tomada). El gradiente se recorta a `[-1, 1]` (pérdida de Huber).
+    ///
+    /// Devuelve el error absoluto medio antes de la actualización.
+    pub fn train(&mut self, batch: &[(Vec<f32>, usize, f32)]) -> f32 {
+        let (grad_weights, grad_bias, total_error) = self.gradients(batch);
+
+        self.updates += 1;
+        let scale = 1.0 / batch.len().max(1) as f32;
+        let correction1 = 1.0 - ADAM_BETA1.powi(self.updates);
+        let correction2 = 1.0 - ADAM_BETA2.powi(self.updates);
+        let learning_rate = self.learning_rate;
+
+        let adam = |param: &mut f32, m: &mut f32, v: &mut f32, grad: f32| {
+            let grad = grad * scale;
+            *m = ADAM_BETA1 * *m + (1.0 - ADAM_BETA1) * grad;
+            *v = ADAM_BETA2 * *v + (1.0 - ADAM_BETA2) * grad * grad;
+
+            let m_hat = *m / correction1;
+            let v_hat = *v / correction2;
+            *param -= learning_rate * m_hat / (v_hat.sqrt() + ADAM_EPSILON);
+        };
+
+        let gradients = grad_weights.iter().zip(&grad_bias);
+        for (layer, (grad_weights, grad_bias)) in self.layers.iter_mut().zip(gradients) {
+            for (j, &grad) in grad_bias.iter().enumerate() {
+                adam(
+                    &mut layer.bias[j],
+                    &mut layer.m_bias[j],
+                    &mut layer.v_bias[j],
+                    grad,
+                );
+
+                for (k, &grad) in grad_weights[j].iter().enumerate() {
+                    adam(
+                        &mut layer.weights[j][k],
+                        &mut layer.m_weights[j][k],
+                        &mut layer.v_weights[j][k],
+                        grad,
+                    );
+                }
+            }
+        }
+
+        total_error * scale
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Red con pesos fijos, para que ninguna ReLU quede justo en el quiebre.
+    fn fixed_network() -> Mlp {
+        let mut mlp = Mlp::new(&[3, 5, 2], 0.01);
+        let mut seed = 0.0_f32;
+
+        for layer in &mut mlp.layers {
+            for row in &mut layer.weights {
+                for weight in row {
+                    seed += 1.0;
+                    *weight = (seed * 1.7).sin() * 0.8;
+                }
+            }
+            for bias in &mut layer.bias {
+                seed += 1.0;
+                *bias = (seed * 0.9).cos() * 0.3;
+            }
+        }
+
+        mlp
+    }
+
+    /// Pérdida cuadrática `½ (y - objetivo)²` sumada sobre el minibatch.
+    fn loss(mlp: &Mlp, batch: &[(Vec<f32>, usize, f32)]) -> f32 {
+        batch
+            .iter()
+            .map(|(input, output, target)| 0.5 * (mlp.predict(input)[*output] - target).powi(2))
+            .sum()
+    }
+
+    #[test]
+    fn backprop_matches_finite_differences() {
+        let mlp = fixed_network();
+        // Objetivos cerca de la predicción para que el recorte de Huber no actúe
+        let batch: Vec<_> = [([0.5, -0.2, 0.9], 0), ([-0.7, 0.4, 0.1], 1)]
+            .into_iter()
+            .map(|(input, output)| {
+                let target = mlp.predict(&input)[output] + 0.3;
+                (input.to_vec(), output, target)
+            })
+            .collect();
+
+        let (grad_weights, grad_bias, _) = mlp.gradients(&batch);
+        let epsilon = 1e-2;
+
+        let numeric = |perturb: &dyn Fn(&mut Mlp, f32)| {
+            let (mut plus, mut minus) = (mlp.clone(), mlp.clone());
+            perturb(&mut plus, epsilon);
+            perturb(&mut minus, -epsilon);
+
+            (loss(&plus, &batch) - loss(&minus, &batch)) / (2.0 * epsilon)
+        };
+
+        for (l, layer) in mlp.layers.iter().enumerate() {
+            for j in 0..layer.bias.len() {
+                let expected = numeric(&|mlp, h| mlp.layers[l].bias[j] += h);
+                assert!(
+                    (grad_bias[l][j] - expected).abs() < 1e-3,
+                    "bias {l}.{j}: {} vs {expected}",
+                    grad_bias[l][j]
+                );
+
+                for (k, &grad) in grad_weights[l][j].iter().enumerate() {
+                    let expected = numeric(&|mlp, h| mlp.layers[l].weights[j][k] += h);
+                    assert!(
+                        (grad - expected).abs() < 1e-3,
+                        "weight {l}.{j}.{k}: {grad} vs {expected}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn training_fits_a_single_output() {
+        let mut mlp = fixed_network();
+        let batch = vec![(vec![0.5, -0.2, 0.9], 1, 2.0)];
+
+        for _ in 0..2000 {
+            mlp.train(&batch);
+        }
+
+        assert!((mlp.predict(&batch[0].0)[1] - 2.0).abs() < 1e-2);
+    }
+}
@@ -0,0 +1,68 @@
+use crate::{
+    agent::{QLearningAgent, TrainingHistory},
+    environment::Environment,
+};
+
+/// Estado del entrenamiento que se entrega a los observadores.
+pub struct TrainingProgress<'a> {
+    pub episode: usize,
+    pub total_episodes: usize,
+    pub env: &'a Environment,
+    pub agent: &'a QLearningAgent,
+    pub history: &'a TrainingHistory,
+}
+
+/// Suscriptor del entrenamiento. `QLearningAgent::train` no dibuja nada: quien quiera
+/// mostrar el progreso (consola, ventana Raylib, etc.) implementa este trait y
+/// recibe una notificación cada `interval()` episodios.
+pub trait TrainingObserver {
+    fn interval(&self) -> usize;
+
+    fn on_episode(&mut self, progress: &TrainingProgress<'_>);
+
+    /// Si devuelve `true`, `QLearningAgent::train` detiene el entrenamiento.
+    fn should_stop(&self) -> bool {
+        false
+    }
+}
+
+/// Imprime un resumen por consola cada `interval` episodios.
+pub struct ConsoleProgress {
+    pub interval: usize,
+}
+
+impl TrainingObserver for ConsoleProgress {
+    fn interval(&self) -> usize {
+        self.interval
+    }
+
+    fn on_episode(&mut self, progress: &TrainingProgress<'_>) {
+        let history = progress.history;
+        let from = history.rewards.len().saturating_sub(self.interval);
+        let window = history.rewards.len() - from;
+        let mean_reward = history.rewards[from..].iter().sum::<f64>() / window.max(1) as f64;
+        let mean_steps = history.steps[from..].iter().sum::<usize>() as f64 / window.max(1) as f64;
+        let agreement = history
+            .metrics
+            .last()
+            .map_or("n/a".to_string(), |m| format!("{:.3}", m.policy_agreement));
+        let evaluation = history
+            .evaluations
+            .last()
+            .map(|(_, evaluation)| *evaluation)
+            .unwrap_or_default();
+
+        println!(
+            "Episode {}/{}: Mean Reward: {:.3}, Mean Steps: {:.1}, Policy Agreement: {}, \
+             Greedy Success: {:.3}, Greedy Steps: {:.1}, Epsilon: {}",
+            progress.episode,
+            progress.total_episodes,
+            mean_reward,
+            mean_steps,
+            agreement,
+            evaluation.success_rate,
+            evaluation.mean_steps,
+            progress.agent.epsilon
+        );
+    }
+}
@@ -0,0 +1,186 @@
+use crate::{
+    N_STATES,
+    environment::Environment,
+    state::{State, StatusType},
+};
+
+use utils::argmax_f32;
+
+const MAX_SWEEPS: usize = 10_000;
+const CONVERGENCE_THRESHOLD: f32 = 1e-6;
+const OPTIMAL_TOLERANCE: f32 = 1e-4;
+
+/// Solución exacta por programación dinámica del mismo problema que resuelve
+/// Q-Learning: mismo `RAW_MAP`, misma probabilidad de éxito y mismo modelo de
+/// `Dynamics` que `Env::step`. Sirve como referencia (Q*) para evaluar la Q-table.
+#[derive(Debug, Clone)]
+pub struct Optimum {
+    pub q_values: Vec<Vec<f32>>,
+    pub values: Vec<f32>,
+    pub discount_factor: f32,
+}
+
+/// Métricas de un episodio de entrenamiento respecto del óptimo.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EpisodeMetrics {
+    /// Fracción de estados donde la acción greedy aprendida es óptima.
+    pub policy_agreement: f64,
+    /// Norma infinito ‖Q − Q*‖ sobre los estados no terminales.
+    pub q_error: f64,
+    /// V*(s0) menos el retorno descontado obtenido en el episodio.
+    pub regret: f64,
+}
+
+impl Optimum {
+    /// Value Iteration sobre Q(s,a) usando el modelo de transición del entorno.
+    ///
+    /// La meta es absorbente: su valor es 0 y al llegar a ella termina el episodio,
+    /// igual que en `QLearningAgent::train`.
+    pub fn value_iteration(env: &Environment, discount_factor: f32) -> Self {
+        let mut q = vec![vec![0.0_f32; 4]; N_STATES];
+
+        for _ in 0..MAX_SWEEPS {
+            let mut delta = 0.0_f32;
+
+            for state in env.map.iter().flatten() {
+                if !Self::is_decision_state(state) {
+                    continue;
+                }
+
+                for action in 0..4 {
+                    let new_q = env
+                        .transitions(state, action)
+                        .iter()
+                        .map(|t| {
+                            t.prob
+                                * (t.reward + discount_factor * Self::state_value(&q, t.next_state))
+                        })
+                        .sum::<f32>();
+
+                    delta = delta.max((new_q - q[state.index][action]).abs());
+                    q[state.index][action] = new_q;
+                }
+            }
+
+            if delta < CONVERGENCE_THRESHOLD {
+                break;
+            }
+        }
+
+        let values = env
+            .map
+            .iter()
+            .flatten()
+            .map(|state| Self::state_value(&q, state))
+            .collect();
+
+        Self {
+            q_values: q,
+            values,
+            discount_factor,
+        }
+    }
+
+    pub fn metrics(
+        &self,
+        env: &Environment,
+        q_table: &[Vec<f32>],
+        start: usize,
+        discounted_return: f64,
+    ) -> EpisodeMetrics {
+        let mut agreeing = 0;
+        let mut total = 0;
+        let mut q_error = 0.0_f32;
+
+        for state in env.map.iter().flatten() {
+            if !Self::is_decision_state(state) {
+                continue;
+            }
+
+            let learned = &q_table[state.index];
+            let optimal = &self.q_values[state.index];
+
+            // Con empates en Q* cualquier acción de valor máximo es óptima
+            let greedy = argmax_f32(learned);
+            if optimal[greedy] >= self.values[state.index] - OPTIMAL_TOLERANCE {
+                agreeing += 1;
+            }
+            total += 1;
+
+            for (q, q_star) in learned.iter().zip(optimal.iter()) {
+                q_error = q_error.max((q - q_star).abs());
+            }
+        }
+
+        EpisodeMetrics {
+            policy_agreement: agreeing as f64 / total.max(1) as f64,
+            q_error: q_error as f64,
+            regret: self.values[start] as f64 - discounted_return,
+        }
+    }
+
+    fn state_value(q: &[Vec<f32>], state: &State) -> f32 {
+        if !Self::is_decision_state(state) {
+            return 0.0;
+        }
+
+        q[state.index]
+            .iter()
+            .copied()
+            .fold(f32::NEG_INFINITY, f32::max)
+    }
+
+    fn is_decision_state(state: &State) -> bool {
+        state.r#type == StatusType::Normal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{N_COLS, environment::tests::env_with};
+
+    #[test]
+    fn value_iteration_matches_hand_solved_values() {
+        // Un pasillo S0-G en la esquina y una celda encerrada, todo lo demás muro
+        let (p, gamma) = (0.7, 0.9);
+        let env = env_with(&[((0, 0), "S0"), ((0, 1), "G"), ((5, 5), "S1")], p);
+        let optimum = Optimum::value_iteration(&env, gamma);
+
+        // Desde S0 lo mejor es ir al Este: con p llega a la meta (valor 0) y si
+        // falla se queda. V = p · 1 + (1 - p) (-0.1 + γ V)
+        let corridor = (p - 0.1 * (1.0 - p)) / (1.0 - gamma * (1.0 - p));
+        // La celda encerrada choca para siempre: -0.1 / (1 - γ)
+        let enclosed = -0.1 / (1.0 - gamma);
+        let enclosed_index = 5 * N_COLS + 5;
+
+        assert!((optimum.values[0] - corridor).abs() < 1e-4);
+        assert!((optimum.values[enclosed_index] - enclosed).abs() < 1e-3);
+        assert_eq!(optimum.values[1], 0.0, "the goal is absorbing");
+        assert_eq!(argmax_f32(&optimum.q_values[0]), 3);
+
+        // Chocar cuesta un paso sin avanzar: Q = -0.1 + γ V
+        let bump = -0.1 + gamma * corridor;
+        assert!((optimum.q_values[0][0] - bump).abs() < 1e-4);
+    }
+
+    #[test]
+    fn metrics_compare_against_the_optimum() {
+        let env = env_with(&[((0, 0), "S0"), ((0, 1), "S1"), ((0, 2), "G")], 0.9);
+        let optimum = Optimum::value_iteration(&env, 0.9);
+
+        let perfect = optimum.metrics(&env, &optimum.q_values, 0, 0.5);
+        assert_eq!(perfect.policy_agreement, 1.0);
+        assert_eq!(perfect.q_error, 0.0);
+        assert!((perfect.regret - (optimum.values[0] as f64 - 0.5)).abs() < 1e-6);
+
+        // Una Q-table que prefiere el Oeste en S1 acierta solo en S0
+        let mut q_table = optimum.q_values.clone();
+        q_table[1] = vec![0.0, 0.0, 5.0, 0.0];
+        let metrics = optimum.metrics(&env, &q_table, 0, 0.0);
+
+        assert_eq!(metrics.policy_agreement, 0.5);
+        let expected_error = (5.0 - optimum.q_values[1][2]) as f64;
+        assert!((metrics.q_error - expected_error).abs() < 1e-5);
+    }
+}
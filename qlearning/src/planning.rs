@@ -0,0 +1,166 @@
+use std::collections::{HashMap, VecDeque};
+
+use rand::random_range;
+
+use crate::agent::Experience;
+
+/// Actualizaciones extra que hace el agente tabular después de cada paso real.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Planning {
+    /// Q-Learning clásico: cada transición se usa una sola vez.
+    None,
+    /// Guarda las últimas `capacity` transiciones y repite `updates` de ellas
+    /// al azar en cada paso.
+    Replay { capacity: usize, updates: usize },
+    /// Dyna-Q: aprende un modelo tabular del entorno y hace `steps`
+    /// actualizaciones simuladas con él en cada paso.
+    DynaQ { steps: usize },
+}
+
+/// Buffer circular de transiciones reales.
+#[derive(Debug, Clone, Default)]
+pub struct ReplayBuffer {
+    capacity: usize,
+    experiences: VecDeque<Experience>,
+}
+
+/// Modelo tabular aprendido: para cada `(estado, acción)` observado, cuántas
+/// veces se vio cada resultado. Como el entorno es estocástico se muestrea
+/// proporcionalmente a esas cuentas en lugar de guardar solo la última.
+#[derive(Debug, Clone, Default)]
+pub struct TabularModel {
+    outcomes: HashMap<(usize, usize), Vec<(Experience, usize)>>,
+    visited: Vec<(usize, usize)>,
+}
+
+impl ReplayBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            experiences: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn push(&mut self, experience: Experience) {
+        if self.experiences.len() == self.capacity {
+            self.experiences.pop_front();
+        }
+
+        self.experiences.push_back(experience);
+    }
+
+    pub fn len(&self) -> usize {
+        self.experiences.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.experiences.is_empty()
+    }
+
+    pub fn sample(&self) -> Option<Experience> {
+        if self.is_empty() {
+            return None;
+        }
+
+        Some(self.experiences[random_range(0..self.experiences.len())])
+    }
+}
+
+impl TabularModel {
+    pub fn observe(&mut self, experience: Experience) {
+        let key = (experience.obs, experience.action);
+        let outcomes = self.outcomes.entry(key).or_insert_with(|| {
+            self.visited.push(key);
+            Vec::new()
+        });
+
+        let seen = outcomes.iter_mut().find(|(e, _)| {
+            e.next_obs == experience.next_obs
+                && e.reward == experience.reward
+                && e.terminated == experience.terminated
+        });
+
+        match seen {
+            Some((_, count)) => *count += 1,
+            None => outcomes.push((experience, 1)),
+        }
+    }
+
+    /// Transición simulada desde un `(estado, acción)` ya visitado al azar.
+    pub fn sample(&self) -> Option<Experience> {
+        if self.visited.is_empty() {
+            return None;
+        }
+
+        let key = self.visited[random_range(0..self.visited.len())];
+        let outcomes = &self.outcomes[&key];
+        let total: usize = outcomes.iter().map(|(_, count)| count).sum();
+
+        let mut roll = random_range(0..total);
+        for &(experience, count) in outcomes {
+            if roll < count {
+                return Some(experience);
+            }
+            roll -= count;
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn experience(obs: usize, action: usize, next_obs: usize) -> Experience {
+        Experience {
+            obs,
+            action,
+            reward: -0.1,
+            next_obs,
+            terminated: false,
+        }
+    }
+
+    #[test]
+    fn model_samples_outcomes_by_their_counts() {
+        let mut model = TabularModel::default();
+        assert!(model.sample().is_none());
+
+        model.observe(experience(0, 3, 1));
+        model.observe(experience(0, 3, 1));
+        model.observe(experience(0, 3, 0));
+
+        let samples = 3000;
+        let mut moved = 0;
+
+        for _ in 0..samples {
+            let sampled = model.sample().unwrap();
+
+            assert_eq!((sampled.obs, sampled.action), (0, 3));
+            assert!(sampled.next_obs <= 1, "never observed {}", sampled.next_obs);
+            moved += (sampled.next_obs == 1) as usize;
+        }
+
+        let rate = moved as f64 / samples as f64;
+        assert!(
+            (rate - 2.0 / 3.0).abs() < 0.05,
+            "moved in {rate:.3} of the samples"
+        );
+    }
+
+    #[test]
+    fn replay_buffer_keeps_the_last_experiences() {
+        let mut buffer = ReplayBuffer::new(2);
+        assert!(buffer.sample().is_none());
+
+        for obs in 0..5 {
+            buffer.push(experience(obs, 0, obs + 1));
+        }
+
+        assert_eq!(buffer.len(), 2);
+        for _ in 0..100 {
+            assert!(buffer.sample().unwrap().obs >= 3);
+        }
+    }
+}
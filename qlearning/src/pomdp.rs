@@ -0,0 +1,305 @@
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use raylib::prelude::*;
+use utils::{
+    Discrete, Env, Raylib, Step, argmax_f32,
+    pomdp::{Belief, WallSensor, qmdp_action},
+};
+
+use crate::{
+    N_COLS, N_STATES, TILE_SIZE, environment::Environment, evaluation::EVALUATION_MAX_STEPS,
+    state::StatusType,
+};
+
+/// Error de los sensores de muros por defecto en el modo `--pomdp`.
+pub const SENSOR_ERROR_RATE: f32 = 0.1;
+
+/// Si hay muro o borde en la dirección de cada acción (arriba, abajo,
+/// izquierda, derecha) desde la celda `index`.
+pub fn walls_around(env: &Environment, index: usize) -> [bool; 4] {
+    let state = env.get_state(index / N_COLS, index % N_COLS);
+    [0, 1, 2, 3].map(|action| env.neighbor(state, action).is_none())
+}
+
+/// Lectura ruidosa de los sensores en la celda actual del agente.
+pub fn read_sensors(sensor: &WallSensor, env: &Environment, rng: &mut impl Rng) -> [bool; 4] {
+    sensor.read(walls_around(env, env.current_state().index), rng)
+}
+
+/// Creencia y sensores del agente que no conoce su celda.
+#[derive(Debug, Clone)]
+pub struct Localization {
+    pub belief: Belief,
+    pub sensor: WallSensor,
+}
+
+impl Localization {
+    pub fn new(env: &Environment, sensor: WallSensor) -> Self {
+        Self {
+            belief: Self::start_belief(env),
+            sensor,
+        }
+    }
+
+    /// Al comienzo de un episodio el agente solo sabe que está en una de las
+    /// celdas de inicio de `env.start`.
+    fn start_belief(env: &Environment) -> Belief {
+        let mut free = vec![false; N_STATES];
+        env.start_cells()
+            .into_iter()
+            .for_each(|index| free[index] = true);
+
+        Belief::uniform(&free)
+    }
+
+    /// Empieza un episodio nuevo y corrige con la primera lectura.
+    pub fn reset(&mut self, env: &Environment, reading: &[bool; 4]) {
+        self.belief = Self::start_belief(env);
+        self.correct(env, reading);
+    }
+
+    /// Un paso del filtro de Bayes después de ejecutar `action` y leer
+    /// `reading`. La predicción usa `Environment::transitions` sobre el mapa
+    /// actual, así que sigue a los obstáculos de `--dynamic`.
+    pub fn update(&mut self, env: &Environment, action: usize, reading: &[bool; 4]) {
+        self.belief.predict(|s| {
+            let state = env.get_state(s / N_COLS, s % N_COLS);
+            env.transitions(state, action)
+                .iter()
+                .map(|t| (t.next_state.index, t.prob))
+                .collect()
+        });
+        self.correct(env, reading);
+    }
+
+    /// Si la lectura es imposible para toda celda con probabilidad, la
+    /// creencia pasa a ser uniforme sobre las celdas normales.
+    fn correct(&mut self, env: &Environment, reading: &[bool; 4]) {
+        let sensor = self.sensor;
+        let consistent = self
+            .belief
+            .correct(|s| sensor.likelihood(&walls_around(env, s), reading));
+
+        if !consistent {
+            let normal: Vec<bool> = env
+                .map
+                .iter()
+                .flatten()
+                .map(|state| state.r#type == StatusType::Normal)
+                .collect();
+            self.belief = Belief::uniform(&normal);
+        }
+    }
+}
+
+/// Sombrea cada celda según su probabilidad y marca la más probable.
+pub fn draw_belief(belief: &Belief) {
+    let max = belief
+        .probs
+        .iter()
+        .copied()
+        .fold(0.0, f32::max)
+        .max(f32::EPSILON);
+    let tile = Vector2::new(TILE_SIZE, TILE_SIZE);
+    let corner = |s: usize| {
+        Vector2::new(
+            (s % N_COLS) as f32 * TILE_SIZE,
+            (s / N_COLS) as f32 * TILE_SIZE,
+        )
+    };
+
+    for (s, &prob) in belief.probs.iter().enumerate() {
+        if prob < 0.005 {
+            continue;
+        }
+
+        Raylib::draw_rectangle(corner(s), tile, Color::ORANGE.fade(0.7 * prob / max));
+        Raylib::draw_text(
+            &format!("{:.0}%", prob * 100.0),
+            corner(s) + Vector2::new(5.0, 5.0),
+            Color::BLACK,
+            18,
+        );
+    }
+
+    Raylib::draw_rectangle_lines(corner(belief.most_likely()), tile, Color::ORANGE);
+}
+
+/// `Environment` donde la observación ya no es la celda sino la lectura de
+/// los sensores de muros.
+pub struct PomdpEnv {
+    pub env: Environment,
+    pub sensor: WallSensor,
+    rng: StdRng,
+}
+
+impl PomdpEnv {
+    pub fn new(env: Environment, sensor: WallSensor) -> Self {
+        Self {
+            env,
+            sensor,
+            rng: StdRng::from_os_rng(),
+        }
+    }
+
+    /// Reinicia el episodio en la celda `index` y devuelve la primera lectura.
+    pub fn reset_to(&mut self, index: usize) -> [bool; 4] {
+        self.env.reset_to(index);
+        read_sensors(&self.sensor, &self.env, &mut self.rng)
+    }
+}
+
+impl Env for PomdpEnv {
+    type Obs = [bool; 4];
+
+    fn reset(&mut self, seed: Option<u64>) -> [bool; 4] {
+        if let Some(seed) = seed {
+            self.rng = StdRng::seed_from_u64(seed);
+        }
+
+        self.env.reset(seed);
+        read_sensors(&self.sensor, &self.env, &mut self.rng)
+    }
+
+    fn step(&mut self, action: usize) -> Step<[bool; 4]> {
+        let (_, reward, terminated, truncated, info) = self.env.step(action);
+        let reading = read_sensors(&self.sensor, &self.env, &mut self.rng);
+
+        (reading, reward, terminated, truncated, info)
+    }
+
+    fn action_space(&self) -> Discrete {
+        Discrete { n: 4 }
+    }
+
+    /// Las 16 combinaciones posibles de los cuatro sensores.
+    fn observation_space(&self) -> Discrete {
+        Discrete { n: 16 }
+    }
+}
+
+/// Quién decide la acción en `evaluate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Controller {
+    /// La política greedy con la celda real (referencia con observabilidad total).
+    FullyObservable,
+    /// QMDP sobre la creencia del filtro de Bayes.
+    Qmdp,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PomdpEvaluation {
+    pub success_rate: f64,
+    /// Pasos medios por episodio; los que no llegan cuentan `EVALUATION_MAX_STEPS`.
+    pub mean_steps: f64,
+    /// Fracción de pasos en que la celda más probable era la real.
+    pub localization_accuracy: f64,
+}
+
+/// Juega un episodio desde cada celda de inicio de `env` con la Q-table
+/// `q_table`. La creencia se mantiene también con `FullyObservable`, para
+/// comparar la precisión de la localización con la misma semilla.
+pub fn evaluate(
+    env: &Environment,
+    q_table: &[Vec<f32>],
+    sensor: WallSensor,
+    controller: Controller,
+) -> PomdpEvaluation {
+    let mut pomdp = PomdpEnv::new(env.clone(), sensor);
+    let mut localization = Localization::new(env, sensor);
+    pomdp.reset(Some(0));
+
+    let cells = env.start_cells();
+    let mut successes = 0;
+    let mut total_steps = 0;
+    let mut localized = 0;
+
+    for &cell in &cells {
+        let reading = pomdp.reset_to(cell);
+        localization.reset(&pomdp.env, &reading);
+
+        for step in 1..=EVALUATION_MAX_STEPS {
+            let action = match controller {
+                Controller::FullyObservable => {
+                    argmax_f32(&q_table[pomdp.env.current_state().index])
+                }
+                Controller::Qmdp => qmdp_action(&localization.belief, q_table),
+            };
+
+            let (reading, _, terminated, _, _) = pomdp.step(action);
+            localization.update(&pomdp.env, action, &reading);
+
+            if localization.belief.most_likely() == pomdp.env.current_state().index {
+                localized += 1;
+            }
+
+            if terminated || step == EVALUATION_MAX_STEPS {
+                successes += terminated as usize;
+                total_steps += step;
+                break;
+            }
+        }
+    }
+
+    PomdpEvaluation {
+        success_rate: successes as f64 / cells.len().max(1) as f64,
+        mean_steps: total_steps as f64 / cells.len().max(1) as f64,
+        localization_accuracy: localized as f64 / total_steps.max(1) as f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{environment::tests::env_with, optimal::Optimum};
+
+    #[test]
+    fn walls_follow_the_action_order() {
+        // S0 tiene muro arriba (borde) y a la izquierda, y libre abajo y a la derecha
+        let env = env_with(&[((0, 0), "S0"), ((0, 1), "S1"), ((1, 0), "G")], 1.0);
+
+        assert_eq!(walls_around(&env, 0), [true, false, true, false]);
+        assert_eq!(walls_around(&env, 1), [true, true, false, true]);
+    }
+
+    #[test]
+    fn belief_starts_on_the_start_cells_and_tracks_moves() {
+        // Pasillo horizontal de cinco celdas con la meta al final
+        let cells = [
+            ((0, 0), "S0"),
+            ((0, 1), "S1"),
+            ((0, 2), "S2"),
+            ((0, 3), "S3"),
+            ((0, 4), "G"),
+        ];
+        let env = env_with(&cells, 1.0);
+        let sensor = WallSensor { error_rate: 0.0 };
+        let mut localization = Localization::new(&env, sensor);
+
+        // Solo los extremos del pasillo tienen muro a los lados: S0 queda localizada
+        localization.reset(&env, &walls_around(&env, 0));
+        assert_eq!(localization.belief.probs[0], 1.0);
+
+        // Con movimientos exactos la creencia acompaña al agente
+        localization.update(&env, 3, &walls_around(&env, 1));
+        assert_eq!(localization.belief.most_likely(), 1);
+        assert!((localization.belief.probs[1] - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn qmdp_reaches_the_goal_with_noisy_sensors() {
+        let env = Environment::new(0.9);
+        let optimum = Optimum::value_iteration(&env, 0.95);
+        let sensor = WallSensor { error_rate: 0.1 };
+
+        let known = evaluate(&env, &optimum.q_values, sensor, Controller::FullyObservable);
+        let qmdp = evaluate(&env, &optimum.q_values, sensor, Controller::Qmdp);
+
+        assert_eq!(known.success_rate, 1.0);
+        assert!(
+            qmdp.success_rate > 0.5,
+            "QMDP success rate {}",
+            qmdp.success_rate
+        );
+        assert!(qmdp.mean_steps >= known.mean_steps);
+    }
+}
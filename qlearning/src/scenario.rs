@@ -0,0 +1,36 @@
+use utils::dynamic::{DynamicElement, DynamicMap};
+
+/// Escenario de bodega sobre `RAW_MAP`: dos grúas horquilla recorren la fila
+/// 10 y la columna 1, dos puertas cierran accesos a la meta y una celda del
+/// pasillo inferior se vuelve peligrosa por momentos.
+pub fn warehouse() -> DynamicMap {
+    DynamicMap {
+        elements: vec![
+            DynamicElement::Patrol {
+                path: (0..15).map(|col| (10, col)).collect(),
+                period: 2,
+            },
+            DynamicElement::Patrol {
+                path: (0..12).map(|row| (row, 1)).collect(),
+                period: 3,
+            },
+            DynamicElement::Door {
+                cell: (2, 5),
+                open: 20,
+                closed: 20,
+                offset: 0,
+            },
+            DynamicElement::Door {
+                cell: (5, 9),
+                open: 15,
+                closed: 15,
+                offset: 7,
+            },
+            DynamicElement::Hazard {
+                cell: (8, 8),
+                rewards: vec![-0.1, -1.0],
+                period: 50,
+            },
+        ],
+    }
+}
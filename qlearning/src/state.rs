@@ -1,10 +1,13 @@
 use crate::{N_COLS, TILE_SIZE};
 use raylib::prelude::*;
-use utils::Raylib;
+use utils::{
+    Raylib,
+    dynamic::{Blocker, DynamicCell},
+};
 
 #[derive(Debug, Clone)]
 pub struct State {
-    pub key: &'static str,
+    pub key: String,
     pub r#type: StatusType,
     pub reward: f32,
     pub position: Vector2,
@@ -20,7 +23,7 @@ pub enum StatusType {
 }
 
 impl State {
-    pub fn new(key: &'static str, i: usize, j: usize) -> State {
+    pub fn new(key: &str, i: usize, j: usize) -> State {
         let (r#type, reward, color) = match key.chars().next().unwrap() {
             'G' => (StatusType::Goal, 1.0, Color::GREEN),
             'W' => (StatusType::Wall, -0.1, Color::BLACK),
@@ -39,7 +42,7 @@ impl State {
         let index = norm_y * N_COLS + norm_x;
 
         State {
-            key,
+            key: key.to_string(),
             r#type,
             reward,
             color,
@@ -49,6 +52,37 @@ impl State {
     }
 
     pub fn draw(&self) {
-        Raylib::draw_tile(self.key, self.position, self.color);
+        Raylib::draw_tile(&self.key, self.position, self.color);
+    }
+}
+
+/// Un bloqueo se dibuja dorado (obstáculo) o café (puerta) y una recompensa
+/// peor que la del mapa base, en rojo.
+impl DynamicCell for State {
+    fn is_wall(&self) -> bool {
+        self.r#type == StatusType::Wall
+    }
+
+    fn is_goal(&self) -> bool {
+        self.r#type == StatusType::Goal
+    }
+
+    fn reward(&self) -> f32 {
+        self.reward
+    }
+
+    fn block(&mut self, blocker: Blocker) {
+        self.r#type = StatusType::Wall;
+        self.color = match blocker {
+            Blocker::Obstacle => Color::GOLD,
+            Blocker::Door => Color::BROWN,
+        };
+    }
+
+    fn set_reward(&mut self, reward: f32, hazard: bool) {
+        self.reward = reward;
+        if hazard {
+            self.color = Color::RED;
+        }
     }
 }
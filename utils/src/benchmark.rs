@@ -0,0 +1,96 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// Resultado de resolver un mapa con un método y una probabilidad de éxito.
+/// Los campos que no aplican al método quedan en `None` (un planificador no
+/// tiene episodios, un agente de aprendizaje no tiene barridos).
+#[derive(Debug, Clone)]
+pub struct BenchmarkResult {
+    pub map: String,
+    pub solver: String,
+    pub success_prob: f32,
+    pub solve_time: Duration,
+    pub sweeps: Option<usize>,
+    pub episodes_to_target: Option<usize>,
+    pub success_rate: f64,
+    pub final_return: f64,
+}
+
+/// Archivos `.map` de `dir`, ordenados por nombre para que los reportes
+/// siempre salgan en el mismo orden.
+pub fn map_files(dir: impl AsRef<Path>) -> io::Result<Vec<PathBuf>> {
+    let mut files = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+
+    files.retain(|path| path.extension().is_some_and(|extension| extension == "map"));
+    files.sort();
+
+    Ok(files)
+}
+
+/// Escribe `<name>.csv` y `<name>.md` en `dir`.
+pub fn write_report(
+    dir: impl AsRef<Path>,
+    name: &str,
+    results: &[BenchmarkResult],
+) -> io::Result<()> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir)?;
+
+    let optional = |value: Option<usize>, missing: &str| {
+        value.map_or(missing.to_string(), |value| value.to_string())
+    };
+
+    let mut csv = io::BufWriter::new(fs::File::create(dir.join(format!("{name}.csv")))?);
+    writeln!(
+        csv,
+        "map,solver,success_prob,solve_time_ms,sweeps,episodes_to_target,success_rate,final_return"
+    )?;
+
+    for result in results {
+        writeln!(
+            csv,
+            "{},{},{},{:.3},{},{},{:.4},{:.4}",
+            result.map,
+            result.solver,
+            result.success_prob,
+            result.solve_time.as_secs_f64() * 1000.0,
+            optional(result.sweeps, ""),
+            optional(result.episodes_to_target, ""),
+            result.success_rate,
+            result.final_return
+        )?;
+    }
+
+    csv.flush()?;
+
+    let mut markdown = io::BufWriter::new(fs::File::create(dir.join(format!("{name}.md")))?);
+    writeln!(markdown, "# Benchmark {name}\n")?;
+    writeln!(
+        markdown,
+        "| Mapa | Método | P | Tiempo (ms) | Barridos | Episodios al objetivo | Éxito | Retorno final |"
+    )?;
+    writeln!(markdown, "|---|---|---|---|---|---|---|---|")?;
+
+    for result in results {
+        writeln!(
+            markdown,
+            "| {} | {} | {} | {:.1} | {} | {} | {:.2} | {:.3} |",
+            result.map,
+            result.solver,
+            result.success_prob,
+            result.solve_time.as_secs_f64() * 1000.0,
+            optional(result.sweeps, "—"),
+            optional(result.episodes_to_target, "—"),
+            result.success_rate,
+            result.final_return
+        )?;
+    }
+
+    markdown.flush()
+}
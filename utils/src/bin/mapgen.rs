@@ -0,0 +1,38 @@
+use utils::mapgen::{KeyFormat, Layout, MapGenerator};
+
+/// Genera un mapa y lo escribe en la salida estándar.
+///
+/// `cargo run -p utils --bin mapgen -- --rows=12 --cols=15 --layout=prim --danger=2 --seed=7`
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let arg = |name: &str| {
+        std::env::args().find_map(|arg| arg.strip_prefix(&format!("--{name}=")).map(str::to_string))
+    };
+
+    let rows = arg("rows").map_or(Ok(12), |value| value.parse())?;
+    let cols = arg("cols").map_or(Ok(15), |value| value.parse())?;
+    let seed = arg("seed").map_or(Ok(0), |value| value.parse())?;
+    let danger_patches = arg("danger").map_or(Ok(0), |value| value.parse())?;
+    let danger_radius = arg("danger-radius").map_or(Ok(1), |value| value.parse())?;
+
+    let layout_name = arg("layout").unwrap_or_else(|| "dfs".to_string());
+    let layout =
+        Layout::from_name(&layout_name).ok_or_else(|| format!("Unknown layout: {layout_name}"))?;
+
+    let format_name = arg("format").unwrap_or_else(|| "qlearning".to_string());
+    let format = KeyFormat::from_name(&format_name)
+        .ok_or_else(|| format!("Unknown key format: {format_name}"))?;
+
+    if rows == 0 || cols == 0 {
+        return Err("The map needs at least one row and one column".into());
+    }
+
+    let generator = MapGenerator {
+        danger_patches,
+        danger_radius,
+        ..MapGenerator::new(rows, cols, layout, seed)
+    };
+
+    print!("{}", generator.generate().to_text(format));
+
+    Ok(())
+}
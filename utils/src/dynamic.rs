@@ -0,0 +1,220 @@
+/// Celda `(fila, columna)` de un mapa de cuadrícula.
+pub type GridCell = (usize, usize);
+
+/// Elemento del mapa que cambia con el tiempo, medido en pasos de simulación.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DynamicElement {
+    /// Obstáculo (por ejemplo una grúa horquilla) que recorre `path` de ida y
+    /// vuelta, avanzando una celda cada `period` pasos.
+    Patrol { path: Vec<GridCell>, period: usize },
+    /// Puerta en `cell`: abierta `open` pasos y cerrada `closed` pasos,
+    /// desfasada `offset` pasos.
+    Door {
+        cell: GridCell,
+        open: usize,
+        closed: usize,
+        offset: usize,
+    },
+    /// Celda cuya recompensa recorre `rewards`, cambiando cada `period` pasos.
+    Hazard {
+        cell: GridCell,
+        rewards: Vec<f32>,
+        period: usize,
+    },
+}
+
+/// Qué bloquea una celda en un instante dado.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Blocker {
+    Obstacle,
+    Door,
+}
+
+/// Estado de los elementos dinámicos en un instante: celdas bloqueadas y
+/// recompensas que reemplazan a las del mapa base.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Snapshot {
+    pub blocked: Vec<(GridCell, Blocker)>,
+    pub rewards: Vec<(GridCell, f32)>,
+}
+
+/// Celda de un mapa al que se le aplica un `Snapshot`. Cada crate decide
+/// cómo se ve una celda bloqueada o peligrosa; qué celdas cambian lo decide
+/// `apply_snapshot`.
+pub trait DynamicCell: Clone {
+    fn is_wall(&self) -> bool;
+    fn is_goal(&self) -> bool;
+    fn reward(&self) -> f32;
+    /// La celda pasa a ser un muro puesto por `blocker`.
+    fn block(&mut self, blocker: Blocker);
+    /// Nueva recompensa; `hazard` indica si es peor que la del mapa base.
+    fn set_reward(&mut self, reward: f32, hazard: bool);
+}
+
+/// Rehace `cells` a partir de `base` con los elementos de `snapshot`. Las
+/// celdas bloqueadas pasan a ser muro, salvo `keep_free` (la del agente) y
+/// la meta. Las recompensas reemplazan a las del mapa base salvo en muros y
+/// en la meta, y la celda es un peligro si paga menos que en el mapa base.
+pub fn apply_snapshot<C: DynamicCell>(
+    cells: &mut [Vec<C>],
+    base: &[Vec<C>],
+    snapshot: &Snapshot,
+    keep_free: GridCell,
+) {
+    cells.clone_from_slice(base);
+
+    for &((row, col), blocker) in &snapshot.blocked {
+        let cell = &mut cells[row][col];
+
+        if (row, col) == keep_free || cell.is_goal() {
+            continue;
+        }
+
+        cell.block(blocker);
+    }
+
+    for &((row, col), reward) in &snapshot.rewards {
+        let cell = &mut cells[row][col];
+
+        if cell.is_wall() || cell.is_goal() {
+            continue;
+        }
+
+        cell.set_reward(reward, reward < base[row][col].reward());
+    }
+}
+
+/// Conjunto de elementos dinámicos de un mapa. Es una función pura del
+/// tiempo: el mismo `time` siempre da el mismo `Snapshot`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DynamicMap {
+    pub elements: Vec<DynamicElement>,
+}
+
+impl DynamicElement {
+    fn patrol_position(path: &[GridCell], period: usize, time: usize) -> GridCell {
+        if path.len() < 2 {
+            return path[0];
+        }
+
+        // Ida y vuelta: 0, 1, ..., n-1, n-2, ..., 1, 0, 1, ...
+        let cycle = 2 * path.len() - 2;
+        let index = (time / period.max(1)) % cycle;
+
+        if index < path.len() {
+            path[index]
+        } else {
+            path[cycle - index]
+        }
+    }
+}
+
+impl DynamicMap {
+    pub fn at(&self, time: usize) -> Snapshot {
+        let mut snapshot = Snapshot::default();
+
+        for element in &self.elements {
+            match element {
+                DynamicElement::Patrol { path, period } if !path.is_empty() => {
+                    let cell = DynamicElement::patrol_position(path, *period, time);
+                    snapshot.blocked.push((cell, Blocker::Obstacle));
+                }
+                DynamicElement::Patrol { .. } => {}
+                DynamicElement::Door {
+                    cell,
+                    open,
+                    closed,
+                    offset,
+                } => {
+                    let cycle = (open + closed).max(1);
+                    if (time + offset) % cycle >= *open {
+                        snapshot.blocked.push((*cell, Blocker::Door));
+                    }
+                }
+                DynamicElement::Hazard {
+                    cell,
+                    rewards,
+                    period,
+                } if !rewards.is_empty() => {
+                    let index = (time / (*period).max(1)) % rewards.len();
+                    snapshot.rewards.push((*cell, rewards[index]));
+                }
+                DynamicElement::Hazard { .. } => {}
+            }
+        }
+
+        snapshot
+    }
+
+    /// Verdadero si algo cambió entre `time - 1` y `time`.
+    pub fn changed(&self, time: usize) -> bool {
+        time > 0 && self.at(time) != self.at(time - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Cell {
+        wall: bool,
+        goal: bool,
+        reward: f32,
+        hazard: bool,
+    }
+
+    impl DynamicCell for Cell {
+        fn is_wall(&self) -> bool {
+            self.wall
+        }
+
+        fn is_goal(&self) -> bool {
+            self.goal
+        }
+
+        fn reward(&self) -> f32 {
+            self.reward
+        }
+
+        fn block(&mut self, _: Blocker) {
+            self.wall = true;
+        }
+
+        fn set_reward(&mut self, reward: f32, hazard: bool) {
+            self.reward = reward;
+            self.hazard = hazard;
+        }
+    }
+
+    #[test]
+    fn snapshot_blocks_and_rewards_only_free_cells() {
+        let cell = |goal| Cell {
+            wall: false,
+            goal,
+            reward: -0.1,
+            hazard: false,
+        };
+        let base = vec![vec![cell(false), cell(false), cell(true)]];
+        let mut cells = base.clone();
+        let snapshot = Snapshot {
+            blocked: vec![((0, 0), Blocker::Door), ((0, 1), Blocker::Obstacle)],
+            rewards: vec![((0, 0), -1.0), ((0, 1), -1.0), ((0, 2), -1.0)],
+        };
+
+        // La celda del agente y la meta no se bloquean, y un muro no cambia de recompensa
+        apply_snapshot(&mut cells, &base, &snapshot, (0, 0));
+        assert!(!cells[0][0].wall && cells[0][1].wall && !cells[0][2].wall);
+        assert_eq!((cells[0][0].reward, cells[0][0].hazard), (-1.0, true));
+        assert_eq!(cells[0][1].reward, -0.1);
+        assert_eq!(cells[0][2], base[0][2]);
+
+        // Una recompensa igual a la del mapa base no es un peligro
+        let snapshot = Snapshot {
+            blocked: vec![],
+            rewards: vec![((0, 1), -0.1)],
+        };
+        apply_snapshot(&mut cells, &base, &snapshot, (0, 0));
+        assert!(!cells[0][1].wall && !cells[0][1].hazard);
+    }
+}
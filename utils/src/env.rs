@@ -0,0 +1,37 @@
+/// Espacio discreto `{0, 1, ..., n - 1}`, usado para acciones y observaciones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Discrete {
+    pub n: usize,
+}
+
+/// Información adicional de una transición que no forma parte de la observación.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StepInfo {
+    /// Pasos transcurridos desde el último `reset`.
+    pub steps: usize,
+    /// El movimiento chocó con un muro o con el borde del mapa.
+    pub bumped: bool,
+}
+
+/// Resultado de `Env::step`: `(observación, recompensa, terminated, truncated, info)`.
+///
+/// `terminated` indica que se llegó a un estado terminal del MDP (la meta) y
+/// `truncated` que el episodio se cortó por límite de pasos.
+pub type Step<O> = (O, f32, bool, bool, StepInfo);
+
+/// Interfaz estilo Gym para los entornos de cuadrícula. El entorno solo guarda
+/// el estado del mundo; los agentes viven aparte y solo interactúan a través
+/// de `reset` y `step`.
+pub trait Env {
+    type Obs;
+
+    /// Reinicia el episodio. Con `Some(seed)` el generador aleatorio del entorno
+    /// se reinicia con esa semilla, para que los episodios sean reproducibles.
+    fn reset(&mut self, seed: Option<u64>) -> Self::Obs;
+
+    fn step(&mut self, action: usize) -> Step<Self::Obs>;
+
+    fn action_space(&self) -> Discrete;
+
+    fn observation_space(&self) -> Discrete;
+}
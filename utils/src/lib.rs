@@ -1,3 +1,11 @@
+pub mod benchmark;
+pub mod dynamic;
+pub mod env;
+pub mod mapgen;
+pub mod pomdp;
+
+pub use env::{Discrete, Env, Step, StepInfo};
+
 use raylib::{color::Color, prelude::*};
 
 const TILE_SIZE: f32 = 75.0;
@@ -81,11 +89,40 @@ impl Raylib {
             let center_x = position.x + (TILE_SIZE / 2.0);
             let center_y = position.y + (TILE_SIZE / 2.0);
 
-            raylib::ffi::DrawCircle(
-                center_x as i32,
-                center_y as i32,
-                radius as f32,
-                Color::RED.into(),
+            raylib::ffi::DrawCircle(center_x as i32, center_y as i32, radius, Color::RED.into());
+        }
+    }
+
+    pub fn draw_triangle(a: Vector2, b: Vector2, c: Vector2, color: Color) {
+        // Raylib solo dibuja triángulos con vértices en sentido antihorario
+        let cross = (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x);
+        let (b, c) = if cross > 0.0 { (c, b) } else { (b, c) };
+
+        unsafe {
+            raylib::ffi::DrawTriangle(a.into(), b.into(), c.into(), color.into());
+        }
+    }
+
+    pub fn draw_line(start: Vector2, end: Vector2, thickness: f32, color: Color) {
+        unsafe {
+            raylib::ffi::DrawLineEx(start.into(), end.into(), thickness, color.into());
+        }
+    }
+
+    pub fn draw_rectangle(position: Vector2, size: Vector2, color: Color) {
+        unsafe {
+            raylib::ffi::DrawRectangleV(position.into(), size.into(), color.into());
+        }
+    }
+
+    pub fn draw_rectangle_lines(position: Vector2, size: Vector2, color: Color) {
+        unsafe {
+            raylib::ffi::DrawRectangleLines(
+                position.x as i32,
+                position.y as i32,
+                size.x as i32,
+                size.y as i32,
+                color.into(),
             );
         }
     }
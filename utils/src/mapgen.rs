@@ -0,0 +1,632 @@
+use std::collections::VecDeque;
+
+use rand::{Rng, SeedableRng, rngs::StdRng, seq::SliceRandom};
+
+/// Tipo de celda de un mapa generado, independiente del formato de cada crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cell {
+    Free,
+    Wall,
+    Danger,
+    Goal,
+}
+
+/// Algoritmo para generar laberintos perfectos (un único camino entre celdas).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MazeAlgorithm {
+    /// Backtracking recursivo: pasillos largos y pocas bifurcaciones.
+    Dfs,
+    /// Prim aleatorizado: muchas bifurcaciones cortas.
+    Prim,
+}
+
+/// Estructura general del mapa antes de agregar zonas de peligro.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Layout {
+    Maze(MazeAlgorithm),
+    /// Cada celda es muro con probabilidad `density`.
+    Obstacles {
+        density: f32,
+    },
+    /// Habitaciones de `room_size × room_size` separadas por muros, con una
+    /// puerta entre cada par de habitaciones vecinas.
+    Rooms {
+        room_size: usize,
+    },
+}
+
+/// Formato de claves de cada crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyFormat {
+    /// `S`/`W`/`G` de `qlearning::State::new`. No tiene peligro: esas celdas quedan como `S`.
+    QLearning,
+    /// `S`/`P`/`O`/`M` de `markov-dp::Map::create_state`.
+    MarkovDp,
+}
+
+/// Generador de mapas de cuadrícula. Con la misma configuración y `seed`
+/// siempre produce el mismo mapa, para poder repetir benchmarks.
+#[derive(Debug, Clone)]
+pub struct MapGenerator {
+    pub rows: usize,
+    pub cols: usize,
+    pub layout: Layout,
+    pub danger_patches: usize,
+    /// Radio (distancia Manhattan) de cada zona de peligro.
+    pub danger_radius: usize,
+    /// Celda de la meta; si es `None` se elige una celda libre al azar.
+    pub goal: Option<(usize, usize)>,
+    pub seed: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedMap {
+    pub cells: Vec<Vec<Cell>>,
+    pub goal: (usize, usize),
+}
+
+impl Layout {
+    /// Lee la estructura por nombre: `dfs`, `prim`, `obstacles:<densidad>` o `rooms:<tamaño>`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "dfs" => Some(Self::Maze(MazeAlgorithm::Dfs)),
+            "prim" => Some(Self::Maze(MazeAlgorithm::Prim)),
+            _ => {
+                let (kind, value) = name.split_once(':')?;
+
+                match kind {
+                    "obstacles" => Some(Self::Obstacles {
+                        density: value.parse().ok()?,
+                    }),
+                    "rooms" => Some(Self::Rooms {
+                        room_size: value.parse().ok()?,
+                    }),
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+impl KeyFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "qlearning" => Some(Self::QLearning),
+            "markov-dp" => Some(Self::MarkovDp),
+            _ => None,
+        }
+    }
+
+    fn prefix(&self, cell: Cell) -> &'static str {
+        match (self, cell) {
+            (Self::QLearning, Cell::Free | Cell::Danger) => "S",
+            (Self::QLearning, Cell::Wall) => "W",
+            (Self::QLearning, Cell::Goal) => "G",
+            (Self::MarkovDp, Cell::Free) => "S",
+            (Self::MarkovDp, Cell::Danger) => "P",
+            (Self::MarkovDp, Cell::Wall) => "O",
+            (Self::MarkovDp, Cell::Goal) => "M",
+        }
+    }
+}
+
+impl MapGenerator {
+    pub fn new(rows: usize, cols: usize, layout: Layout, seed: u64) -> Self {
+        Self {
+            rows,
+            cols,
+            layout,
+            danger_patches: 0,
+            danger_radius: 1,
+            goal: None,
+            seed,
+        }
+    }
+
+    pub fn generate(&self) -> GeneratedMap {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+
+        let mut cells = match self.layout {
+            Layout::Maze(MazeAlgorithm::Dfs) => self.dfs_maze(&mut rng),
+            Layout::Maze(MazeAlgorithm::Prim) => self.prim_maze(&mut rng),
+            Layout::Obstacles { density } => (0..self.rows)
+                .map(|_| {
+                    (0..self.cols)
+                        .map(|_| {
+                            if rng.random::<f32>() < density {
+                                Cell::Wall
+                            } else {
+                                Cell::Free
+                            }
+                        })
+                        .collect()
+                })
+                .collect(),
+            Layout::Rooms { room_size } => self.rooms(room_size.max(1), &mut rng),
+        };
+
+        let goal = match self.goal {
+            Some(goal) => goal,
+            None => {
+                let free = cells_of(&cells, Cell::Free);
+                free.get(rng.random_range(0..free.len().max(1)))
+                    .copied()
+                    .unwrap_or((0, 0))
+            }
+        };
+        cells[goal.0][goal.1] = Cell::Goal;
+
+        connect_to_goal(&mut cells, goal);
+        self.add_danger(&mut cells, &mut rng);
+
+        GeneratedMap { cells, goal }
+    }
+
+    /// Pasillos en las celdas de coordenadas pares; los muros entre ellas se
+    /// abren al avanzar.
+    fn dfs_maze(&self, rng: &mut StdRng) -> Vec<Vec<Cell>> {
+        let mut cells = vec![vec![Cell::Wall; self.cols]; self.rows];
+        let mut stack = vec![(0, 0)];
+        cells[0][0] = Cell::Free;
+
+        while let Some(&current) = stack.last() {
+            let mut options = self.maze_neighbors(current, &cells);
+
+            if options.is_empty() {
+                stack.pop();
+                continue;
+            }
+
+            options.shuffle(rng);
+            let next = options[0];
+            open_between(&mut cells, current, next);
+            stack.push(next);
+        }
+
+        cells
+    }
+
+    fn prim_maze(&self, rng: &mut StdRng) -> Vec<Vec<Cell>> {
+        let mut cells = vec![vec![Cell::Wall; self.cols]; self.rows];
+        cells[0][0] = Cell::Free;
+        let mut frontier: Vec<((usize, usize), (usize, usize))> = self
+            .maze_neighbors((0, 0), &cells)
+            .into_iter()
+            .map(|next| ((0, 0), next))
+            .collect();
+
+        while !frontier.is_empty() {
+            let (from, next) = frontier.swap_remove(rng.random_range(0..frontier.len()));
+
+            if cells[next.0][next.1] != Cell::Wall {
+                continue;
+            }
+
+            open_between(&mut cells, from, next);
+            frontier.extend(
+                self.maze_neighbors(next, &cells)
+                    .into_iter()
+                    .map(|after| (next, after)),
+            );
+        }
+
+        cells
+    }
+
+    /// Celdas de pasillo a dos pasos de `cell` que todavía no se visitaron.
+    fn maze_neighbors(
+        &self,
+        (row, col): (usize, usize),
+        cells: &[Vec<Cell>],
+    ) -> Vec<(usize, usize)> {
+        let mut neighbors = Vec::new();
+
+        if row >= 2 {
+            neighbors.push((row - 2, col));
+        }
+        if row + 2 < self.rows {
+            neighbors.push((row + 2, col));
+        }
+        if col >= 2 {
+            neighbors.push((row, col - 2));
+        }
+        if col + 2 < self.cols {
+            neighbors.push((row, col + 2));
+        }
+
+        neighbors.retain(|&(r, c)| cells[r][c] == Cell::Wall);
+        neighbors
+    }
+
+    fn rooms(&self, room_size: usize, rng: &mut StdRng) -> Vec<Vec<Cell>> {
+        let step = room_size + 1;
+        let is_wall_line = |i: usize| i % step == room_size;
+
+        let mut cells: Vec<Vec<Cell>> = (0..self.rows)
+            .map(|row| {
+                (0..self.cols)
+                    .map(|col| {
+                        if is_wall_line(row) || is_wall_line(col) {
+                            Cell::Wall
+                        } else {
+                            Cell::Free
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // Una puerta en cada tramo de muro que separa dos habitaciones
+        for line in (room_size..self.rows).step_by(step) {
+            for start in (0..self.cols).step_by(step) {
+                let end = (start + room_size).min(self.cols);
+                if line + 1 < self.rows && start < end {
+                    cells[line][rng.random_range(start..end)] = Cell::Free;
+                }
+            }
+        }
+
+        for line in (room_size..self.cols).step_by(step) {
+            for start in (0..self.rows).step_by(step) {
+                let end = (start + room_size).min(self.rows);
+                if line + 1 < self.cols && start < end {
+                    cells[rng.random_range(start..end)][line] = Cell::Free;
+                }
+            }
+        }
+
+        cells
+    }
+
+    /// Zonas de peligro alrededor de celdas libres al azar. El peligro se
+    /// puede atravesar, así que no afecta la alcanzabilidad.
+    fn add_danger(&self, cells: &mut [Vec<Cell>], rng: &mut StdRng) {
+        let radius = self.danger_radius as isize;
+
+        for _ in 0..self.danger_patches {
+            let free = cells_of(cells, Cell::Free);
+            if free.is_empty() {
+                return;
+            }
+
+            let (center_row, center_col) = free[rng.random_range(0..free.len())];
+
+            for (row, line) in cells.iter_mut().enumerate() {
+                for (col, cell) in line.iter_mut().enumerate() {
+                    let distance = (row as isize - center_row as isize).abs()
+                        + (col as isize - center_col as isize).abs();
+
+                    if distance <= radius && *cell == Cell::Free {
+                        *cell = Cell::Danger;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl GeneratedMap {
+    /// Lee un mapa escrito con las claves de cualquiera de los dos crates, para
+    /// poder pasarlo al formato del otro con `to_keys`.
+    pub fn from_keys(keys: &[Vec<String>]) -> Result<Self, String> {
+        if keys.is_empty() || keys.iter().any(|row| row.len() != keys[0].len()) {
+            return Err("All rows of the map must have the same length".to_string());
+        }
+
+        let mut goals = Vec::new();
+        let mut cells = Vec::new();
+
+        for (row, line) in keys.iter().enumerate() {
+            let mut cell_row = Vec::new();
+
+            for (col, key) in line.iter().enumerate() {
+                let cell = match key.chars().next() {
+                    Some('S') => Cell::Free,
+                    Some('P') => Cell::Danger,
+                    Some('W' | 'O') => Cell::Wall,
+                    Some('G' | 'M') => Cell::Goal,
+                    _ => return Err(format!("Invalid state key: {key}")),
+                };
+
+                if cell == Cell::Goal {
+                    goals.push((row, col));
+                }
+
+                cell_row.push(cell);
+            }
+
+            cells.push(cell_row);
+        }
+
+        match goals[..] {
+            [goal] => Ok(Self { cells, goal }),
+            _ => Err(format!(
+                "The map must have exactly one goal, found {}",
+                goals.len()
+            )),
+        }
+    }
+
+    /// Claves por celda en el formato de `format`, numeradas por tipo en orden
+    /// de lectura (`S0`, `S1`, ..., `W0`, ...).
+    pub fn to_keys(&self, format: KeyFormat) -> Vec<Vec<String>> {
+        let mut counters = std::collections::HashMap::new();
+
+        self.cells
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&cell| {
+                        let prefix = format.prefix(cell);
+                        if cell == Cell::Goal {
+                            return prefix.to_string();
+                        }
+
+                        let counter = counters.entry(prefix).or_insert(0);
+                        *counter += 1;
+                        format!("{}{}", prefix, *counter - 1)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Texto de un archivo de mapa: una fila por línea, claves separadas por espacios.
+    pub fn to_text(&self, format: KeyFormat) -> String {
+        self.to_keys(format)
+            .iter()
+            .map(|row| row.join(" "))
+            .collect::<Vec<_>>()
+            .join("\n")
+            + "\n"
+    }
+
+    /// Verdadero si desde toda celda que no es muro se puede llegar a la meta.
+    pub fn all_reachable(&self) -> bool {
+        let reachable = reachable_from(&self.cells, self.goal);
+
+        self.cells.iter().enumerate().all(|(row, line)| {
+            line.iter()
+                .enumerate()
+                .all(|(col, &cell)| cell == Cell::Wall || reachable[row][col])
+        })
+    }
+}
+
+/// Lee un archivo de mapa (ver `GeneratedMap::to_text`). Las líneas vacías y
+/// las que empiezan con `#` se ignoran.
+pub fn parse_keys(text: &str) -> Vec<Vec<String>> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.split_whitespace().map(str::to_string).collect())
+        .collect()
+}
+
+fn cells_of(cells: &[Vec<Cell>], kind: Cell) -> Vec<(usize, usize)> {
+    cells
+        .iter()
+        .enumerate()
+        .flat_map(|(row, line)| {
+            line.iter()
+                .enumerate()
+                .filter(move |&(_, &cell)| cell == kind)
+                .map(move |(col, _)| (row, col))
+        })
+        .collect()
+}
+
+fn open_between(cells: &mut [Vec<Cell>], from: (usize, usize), to: (usize, usize)) {
+    let middle = ((from.0 + to.0) / 2, (from.1 + to.1) / 2);
+    cells[middle.0][middle.1] = Cell::Free;
+    cells[to.0][to.1] = Cell::Free;
+}
+
+fn grid_neighbors(cells: &[Vec<Cell>], (row, col): (usize, usize)) -> Vec<(usize, usize)> {
+    let (rows, cols) = (cells.len(), cells[0].len());
+    let mut neighbors = Vec::with_capacity(4);
+
+    if row > 0 {
+        neighbors.push((row - 1, col));
+    }
+    if row + 1 < rows {
+        neighbors.push((row + 1, col));
+    }
+    if col > 0 {
+        neighbors.push((row, col - 1));
+    }
+    if col + 1 < cols {
+        neighbors.push((row, col + 1));
+    }
+
+    neighbors
+}
+
+fn reachable_from(cells: &[Vec<Cell>], start: (usize, usize)) -> Vec<Vec<bool>> {
+    let mut reachable = vec![vec![false; cells[0].len()]; cells.len()];
+    let mut queue = VecDeque::from([start]);
+    reachable[start.0][start.1] = true;
+
+    while let Some(cell) = queue.pop_front() {
+        for (row, col) in grid_neighbors(cells, cell) {
+            if !reachable[row][col] && cells[row][col] != Cell::Wall {
+                reachable[row][col] = true;
+                queue.push_back((row, col));
+            }
+        }
+    }
+
+    reachable
+}
+
+/// Garantiza que toda celda libre llegue a la meta: mientras quede una región
+/// aislada se busca el camino más corto (atravesando muros) hasta la parte
+/// conectada y se abren los muros de ese camino.
+fn connect_to_goal(cells: &mut [Vec<Cell>], goal: (usize, usize)) {
+    loop {
+        let reachable = reachable_from(cells, goal);
+        let isolated = cells_of(cells, Cell::Free)
+            .into_iter()
+            .find(|&(row, col)| !reachable[row][col]);
+
+        let Some(start) = isolated else {
+            return;
+        };
+
+        let mut parent = vec![vec![None; cells[0].len()]; cells.len()];
+        let mut queue = VecDeque::from([start]);
+        let mut end = start;
+
+        'search: while let Some(cell) = queue.pop_front() {
+            for next in grid_neighbors(cells, cell) {
+                if next == start || parent[next.0][next.1].is_some() {
+                    continue;
+                }
+
+                parent[next.0][next.1] = Some(cell);
+
+                if reachable[next.0][next.1] {
+                    end = next;
+                    break 'search;
+                }
+
+                queue.push_back(next);
+            }
+        }
+
+        while let Some(previous) = parent[end.0][end.1] {
+            if cells[end.0][end.1] == Cell::Wall {
+                cells[end.0][end.1] = Cell::Free;
+            }
+            end = previous;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEEDS: std::ops::Range<u64> = 0..20;
+
+    /// Todas las estructuras, con densidades extremas y tamaños de habitación
+    /// que no dividen el mapa.
+    fn layouts() -> Vec<Layout> {
+        let mut layouts = vec![
+            Layout::Maze(MazeAlgorithm::Dfs),
+            Layout::Maze(MazeAlgorithm::Prim),
+        ];
+        layouts.extend([0.0, 0.2, 0.45, 0.7, 1.0].map(|density| Layout::Obstacles { density }));
+        layouts.extend([1, 2, 3, 5].map(|room_size| Layout::Rooms { room_size }));
+
+        layouts
+    }
+
+    fn generators() -> impl Iterator<Item = MapGenerator> {
+        layouts().into_iter().flat_map(|layout| {
+            SEEDS.flat_map(move |seed| {
+                [(12, 15, 0), (6, 8, 3), (7, 7, 1)].map(|(rows, cols, danger_patches)| {
+                    MapGenerator {
+                        danger_patches,
+                        ..MapGenerator::new(rows, cols, layout, seed)
+                    }
+                })
+            })
+        })
+    }
+
+    #[test]
+    fn every_layout_reaches_the_goal() {
+        for generator in generators() {
+            let map = generator.generate();
+
+            assert!(
+                map.all_reachable(),
+                "{:?} with seed {} has isolated cells",
+                generator.layout,
+                generator.seed
+            );
+            assert_eq!(map.cells[map.goal.0][map.goal.1], Cell::Goal);
+            assert_eq!(cells_of(&map.cells, Cell::Goal), vec![map.goal]);
+        }
+    }
+
+    #[test]
+    fn fixed_goal_is_kept() {
+        for layout in layouts() {
+            let generator = MapGenerator {
+                goal: Some((5, 8)),
+                ..MapGenerator::new(12, 15, layout, 3)
+            };
+            let map = generator.generate();
+
+            assert_eq!(map.goal, (5, 8));
+            assert!(map.all_reachable(), "{layout:?}");
+        }
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_map() {
+        for generator in generators() {
+            assert_eq!(generator.generate(), generator.generate());
+        }
+
+        let layout = Layout::Maze(MazeAlgorithm::Prim);
+        let first = MapGenerator::new(12, 15, layout, 1).generate();
+        let second = MapGenerator::new(12, 15, layout, 2).generate();
+        assert_ne!(first, second, "different seeds should differ");
+    }
+
+    #[test]
+    fn text_round_trips_through_both_key_formats() {
+        for generator in generators() {
+            let map = generator.generate();
+
+            let markov_dp = parse_keys(&map.to_text(KeyFormat::MarkovDp));
+            assert_eq!(GeneratedMap::from_keys(&markov_dp).unwrap(), map);
+
+            // Las claves de qlearning no tienen peligro: vuelve como celda libre
+            let qlearning = parse_keys(&map.to_text(KeyFormat::QLearning));
+            let parsed = GeneratedMap::from_keys(&qlearning).unwrap();
+            let without_danger: Vec<Vec<Cell>> = map
+                .cells
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|&cell| match cell {
+                            Cell::Danger => Cell::Free,
+                            cell => cell,
+                        })
+                        .collect()
+                })
+                .collect();
+
+            assert_eq!(parsed.cells, without_danger);
+            assert_eq!(parsed.goal, map.goal);
+        }
+    }
+
+    #[test]
+    fn layouts_and_formats_parse_by_name() {
+        assert_eq!(
+            Layout::from_name("obstacles:0.3"),
+            Some(Layout::Obstacles { density: 0.3 })
+        );
+        assert_eq!(
+            Layout::from_name("rooms:4"),
+            Some(Layout::Rooms { room_size: 4 })
+        );
+        assert_eq!(Layout::from_name("rooms:x"), None);
+        assert_eq!(Layout::from_name("spiral"), None);
+        assert_eq!(KeyFormat::from_name("markov-dp"), Some(KeyFormat::MarkovDp));
+    }
+
+    #[test]
+    fn comments_and_blank_lines_are_skipped() {
+        let keys = parse_keys("# mapa\n\nS0 G\n  W0 S1  \n");
+
+        assert_eq!(keys, vec![vec!["S0", "G"], vec!["W0", "S1"]]);
+        assert!(GeneratedMap::from_keys(&keys).unwrap().all_reachable());
+        assert!(GeneratedMap::from_keys(&parse_keys("S0 S1\nW0 S2")).is_err());
+    }
+}
@@ -0,0 +1,180 @@
+use rand::Rng;
+
+use crate::argmax_f32;
+
+/// Error máximo de un sensor: con 0.5 la lectura no dice nada y con más engaña.
+pub const MAX_ERROR_RATE: f32 = 0.5;
+
+/// Sensores de contacto en las cuatro direcciones. Cada uno informa si hay
+/// muro (o borde del mapa) y se equivoca con probabilidad `error_rate`,
+/// independiente de los demás. El orden de las direcciones lo fija cada crate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WallSensor {
+    pub error_rate: f32,
+}
+
+impl WallSensor {
+    /// Lectura ruidosa de los muros reales `walls`.
+    pub fn read(&self, walls: [bool; 4], rng: &mut impl Rng) -> [bool; 4] {
+        walls.map(|wall| wall ^ (rng.random::<f32>() < self.error_rate))
+    }
+
+    /// P(lectura | muros), producto de los cuatro sensores.
+    pub fn likelihood(&self, walls: &[bool; 4], reading: &[bool; 4]) -> f32 {
+        walls
+            .iter()
+            .zip(reading)
+            .map(|(wall, read)| {
+                if wall == read {
+                    1.0 - self.error_rate
+                } else {
+                    self.error_rate
+                }
+            })
+            .product()
+    }
+}
+
+/// Creencia sobre la celda del agente: una probabilidad por estado. Se
+/// actualiza con un filtro de Bayes discreto (predicción con el modelo de
+/// movimiento y corrección con la lectura de los sensores).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Belief {
+    pub probs: Vec<f32>,
+}
+
+impl Belief {
+    /// Misma probabilidad en todos los estados donde `free` es verdadero.
+    pub fn uniform(free: &[bool]) -> Self {
+        let n = free.iter().filter(|&&free| free).count().max(1) as f32;
+
+        Self {
+            probs: free
+                .iter()
+                .map(|&free| if free { 1.0 / n } else { 0.0 })
+                .collect(),
+        }
+    }
+
+    /// b'(s') = Σ_s P(s' | s, a) b(s). `outcomes(s)` da los pares
+    /// `(s', P(s' | s, a))` de la acción ejecutada.
+    pub fn predict(&mut self, outcomes: impl Fn(usize) -> Vec<(usize, f32)>) {
+        let mut next = vec![0.0; self.probs.len()];
+
+        for (s, &prob) in self.probs.iter().enumerate() {
+            if prob == 0.0 {
+                continue;
+            }
+
+            for (s_, p) in outcomes(s) {
+                next[s_] += p * prob;
+            }
+        }
+
+        self.probs = next;
+    }
+
+    /// b(s) ∝ P(z | s) b(s), con `likelihood(s)` = P(z | s). Si la lectura es
+    /// imposible para toda celda con probabilidad la creencia no cambia y se
+    /// devuelve `false`, para que quien la usa decida cómo reiniciarla.
+    pub fn correct(&mut self, likelihood: impl Fn(usize) -> f32) -> bool {
+        let corrected: Vec<f32> = self
+            .probs
+            .iter()
+            .enumerate()
+            .map(|(s, prob)| prob * likelihood(s))
+            .collect();
+        let total: f32 = corrected.iter().sum();
+
+        if total <= 0.0 {
+            return false;
+        }
+
+        self.probs = corrected.into_iter().map(|prob| prob / total).collect();
+        true
+    }
+
+    pub fn most_likely(&self) -> usize {
+        self.probs
+            .iter()
+            .enumerate()
+            .fold(
+                (0, f32::MIN),
+                |best, (s, &prob)| {
+                    if prob > best.1 { (s, prob) } else { best }
+                },
+            )
+            .0
+    }
+}
+
+/// QMDP: la acción que maximiza Σ_s b(s) Q(s, a), suponiendo que la
+/// incertidumbre desaparece después de un paso.
+pub fn qmdp_action(belief: &Belief, q_values: &[Vec<f32>]) -> usize {
+    let mut expected = [0.0_f32; 4];
+
+    for (prob, q) in belief.probs.iter().zip(q_values) {
+        for (value, q) in expected.iter_mut().zip(q) {
+            *value += prob * q;
+        }
+    }
+
+    argmax_f32(&expected)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+
+    #[test]
+    fn exact_sensors_read_the_walls() {
+        let sensor = WallSensor { error_rate: 0.0 };
+        let walls = [true, false, false, true];
+        let mut rng = StdRng::seed_from_u64(0);
+
+        assert_eq!(sensor.read(walls, &mut rng), walls);
+        assert_eq!(sensor.likelihood(&walls, &walls), 1.0);
+        assert_eq!(sensor.likelihood(&walls, &[true; 4]), 0.0);
+    }
+
+    #[test]
+    fn filter_predicts_and_corrects() {
+        // Pasillo de tres celdas: la acción avanza una con probabilidad 0.8
+        let mut belief = Belief::uniform(&[true, true, false]);
+        assert_eq!(belief.probs, vec![0.5, 0.5, 0.0]);
+
+        belief.predict(|s| match s {
+            2 => vec![(2, 1.0)],
+            s => vec![(s + 1, 0.8), (s, 0.2)],
+        });
+        assert!((belief.probs[0] - 0.1).abs() < 1e-6);
+        assert!((belief.probs[1] - 0.5).abs() < 1e-6);
+        assert!((belief.probs[2] - 0.4).abs() < 1e-6);
+
+        // Una lectura que solo es posible en la última celda
+        assert!(belief.correct(|s| if s == 2 { 0.9 } else { 0.0 }));
+        assert_eq!(belief.most_likely(), 2);
+        assert!((belief.probs[2] - 1.0).abs() < 1e-6);
+
+        let before = belief.clone();
+        assert!(!belief.correct(|_| 0.0));
+        assert_eq!(belief, before, "an impossible reading keeps the belief");
+    }
+
+    #[test]
+    fn qmdp_weights_q_values_by_the_belief() {
+        // En la celda 0 conviene la acción 0 y en la 1 la acción 1, pero la
+        // acción 2 es buena en las dos
+        let q_values = vec![vec![10.0, -10.0, 6.0, 0.0], vec![-10.0, 10.0, 6.0, 0.0]];
+
+        let mut belief = Belief {
+            probs: vec![0.5, 0.5],
+        };
+        assert_eq!(qmdp_action(&belief, &q_values), 2);
+
+        belief.probs = vec![0.9, 0.1];
+        assert_eq!(qmdp_action(&belief, &q_values), 0);
+    }
+}